@@ -0,0 +1,43 @@
+#!/bin/sh
+# Builds and tests the core without features, every feature on its own
+# and all of them together, then checks that every pair of features
+# builds. Features which pull in others (e.g. `arrow` enables `std` and
+# `codecs`) are checked with exactly those.
+#
+# Codec features share the encoding helpers in `codecs` and switch
+# between `std` and `alloc` paths, so every combination of them is
+# tested with and without `std`. Other combinations of three or more
+# features aren't covered.
+set -ex
+
+FEATURES="std codecs protocols rayon flatbuffers borsh arbitrary im arrow serde uuid proptest quickcheck rand"
+CODECS="serde borsh uuid arrow"
+
+cargo test --no-default-features
+for feature in $FEATURES; do
+    cargo test --no-default-features --features "$feature"
+done
+cargo test --all-features
+
+rest="$FEATURES"
+for first in $FEATURES; do
+    rest="${rest#*$first}"
+    for second in $rest; do
+        cargo check --no-default-features --features "$first $second"
+    done
+done
+
+# every subset of $CODECS, encoded as bits of a counter
+count=$(echo $CODECS | wc -w)
+subset=0
+while [ $subset -lt $((1 << count)) ]; do
+    features="codecs"
+    bit=0
+    for codec in $CODECS; do
+        [ $((subset >> bit & 1)) -eq 1 ] && features="$features $codec"
+        bit=$((bit + 1))
+    done
+    cargo test --no-default-features --features "$features"
+    cargo test --no-default-features --features "std $features"
+    subset=$((subset + 1))
+done
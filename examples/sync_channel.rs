@@ -0,0 +1,45 @@
+//! Two replicas converging over an in-memory channel.
+//!
+//! Run with `cargo run --example sync_channel`.
+
+extern crate version_vec;
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use version_vec::protocols::SyncSession;
+use version_vec::VersionVec;
+
+type Clock = VersionVec<u32, u64>;
+
+fn replica(mut session: SyncSession<u32, u64>, initiator: bool, tx: Sender<Clock>, rx: Receiver<Clock>) -> Clock {
+    if initiator {
+        tx.send(session.start()).expect("peer hung up");
+    }
+    while !session.is_synced() {
+        let clock = rx.recv().expect("peer hung up");
+        if let Some(reply) = session.receive(&clock) {
+            tx.send(reply).expect("peer hung up");
+        }
+    }
+    session.into_version_vec()
+}
+
+fn main() {
+    let mut a = Clock::new();
+    a.bump_for(1);
+    a.bump_for(1);
+    let mut b = Clock::new();
+    b.bump_for(2);
+
+    let (to_b, from_a) = channel();
+    let (to_a, from_b) = channel();
+    let b = thread::spawn(move || replica(SyncSession::new(b), false, to_a, from_a));
+    let a = replica(SyncSession::new(a), true, to_b, from_b);
+    let b = b.join().expect("replica b panicked");
+
+    println!("a: {:?}", a.as_ref());
+    println!("b: {:?}", b.as_ref());
+    assert_eq!(a.as_ref(), [(1, 2), (2, 1)]);
+    assert_eq!(a.as_ref(), b.as_ref());
+}
@@ -0,0 +1,68 @@
+//! Two replicas converging over TCP on localhost.
+//!
+//! Clocks travel in canonical encoding, each framed by its length as a
+//! big-endian `u32`. Run with `cargo run --example sync_tcp`.
+
+extern crate version_vec;
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use version_vec::codecs::canonical;
+use version_vec::protocols::SyncSession;
+use version_vec::VersionVec;
+
+type Clock = VersionVec<u32, u64>;
+
+fn send(stream: &mut TcpStream, clock: &Clock) -> io::Result<()> {
+    let bytes = clock.canonical_bytes();
+    stream.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    stream.write_all(&bytes)
+}
+
+fn recv(stream: &mut TcpStream) -> io::Result<Clock> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let mut bytes = vec![0; u32::from_be_bytes(len) as usize];
+    stream.read_exact(&mut bytes)?;
+    canonical::decode(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn replica(mut session: SyncSession<u32, u64>, initiator: bool, mut stream: TcpStream) -> io::Result<Clock> {
+    if initiator {
+        send(&mut stream, &session.start())?;
+    }
+    while !session.is_synced() {
+        let clock = recv(&mut stream)?;
+        if let Some(reply) = session.receive(&clock) {
+            send(&mut stream, &reply)?;
+        }
+    }
+    Ok(session.into_version_vec())
+}
+
+fn main() -> io::Result<()> {
+    let mut a = Clock::new();
+    a.bump_for(1);
+    a.bump_for(3);
+    let mut b = Clock::new();
+    b.bump_for(2);
+    b.bump_for(3);
+    b.bump_for(3);
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let b = thread::spawn(move || {
+        let (stream, _) = listener.accept()?;
+        replica(SyncSession::new(b), false, stream)
+    });
+    let a = replica(SyncSession::new(a), true, TcpStream::connect(addr)?)?;
+    let b = b.join().expect("replica b panicked")?;
+
+    println!("a: {:?}", a.as_ref());
+    println!("b: {:?}", b.as_ref());
+    assert_eq!(a.as_ref(), [(1, 1), (2, 1), (3, 2)]);
+    assert_eq!(a.as_ref(), b.as_ref());
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Actor ids.
+//!
+//! Anything `Ord + Clone` works as an actor id for clocks themselves.
+//! Features which need to hash ids into buckets or compact wire forms
+//! additionally require `Actor`, whose fingerprint is the same on every
+//! platform, run and release, unlike `Hash` output.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::Hasher;
+
+use digest::Fnv64;
+use namespace::NamespacedId;
+
+/// Actor id with a stable 64-bit fingerprint
+pub trait Actor: Ord {
+    /// FNV-1a based fingerprint. Integers up to 64 bits are
+    /// fingerprinted as 8-byte little-endian values, so equal numbers of
+    /// different widths share fingerprints.
+    fn fingerprint(&self) -> u64;
+}
+
+fn fnv(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv64::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+macro_rules! unsigned_actor {
+    ($($t:ty),*) => {$(
+        impl Actor for $t {
+            fn fingerprint(&self) -> u64 {
+                fnv(&(*self as u64).to_le_bytes())
+            }
+        }
+    )*}
+}
+
+macro_rules! signed_actor {
+    ($($t:ty),*) => {$(
+        impl Actor for $t {
+            fn fingerprint(&self) -> u64 {
+                fnv(&(*self as i64).to_le_bytes())
+            }
+        }
+    )*}
+}
+
+unsigned_actor!(u8, u16, u32, u64, usize);
+signed_actor!(i8, i16, i32, i64, isize);
+
+impl Actor for str {
+    fn fingerprint(&self) -> u64 {
+        fnv(self.as_bytes())
+    }
+}
+
+impl Actor for String {
+    fn fingerprint(&self) -> u64 {
+        fnv(self.as_bytes())
+    }
+}
+
+impl<A: Actor + ?Sized> Actor for &A {
+    fn fingerprint(&self) -> u64 {
+        (**self).fingerprint()
+    }
+}
+
+impl<const N: usize> Actor for [u8; N] {
+    fn fingerprint(&self) -> u64 {
+        fnv(self)
+    }
+}
+
+impl Actor for u128 {
+    fn fingerprint(&self) -> u64 {
+        fnv(&self.to_le_bytes())
+    }
+}
+
+impl Actor for Vec<u8> {
+    fn fingerprint(&self) -> u64 {
+        fnv(self)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Actor for ::uuid::Uuid {
+    fn fingerprint(&self) -> u64 {
+        fnv(self.as_bytes())
+    }
+}
+
+impl<N: Actor, A: Actor> Actor for NamespacedId<N, A> {
+    fn fingerprint(&self) -> u64 {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.namespace.fingerprint().to_le_bytes());
+        bytes[8..].copy_from_slice(&self.node.fingerprint().to_le_bytes());
+        fnv(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::Actor;
+    use namespace::NamespacedId;
+
+    #[test]
+    fn stable_fingerprints() {
+        assert_eq!(5u8.fingerprint(), 5u64.fingerprint());
+        assert_eq!((-1i8).fingerprint(), (-1i64).fingerprint());
+        assert_eq!("a".fingerprint(), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!("a".to_string().fingerprint(), "a".fingerprint());
+        assert_eq!([0x61u8].fingerprint(), "a".fingerprint());
+        assert!(NamespacedId::new(1u8, 2u8).fingerprint() != NamespacedId::new(2u8, 1u8).fingerprint());
+    }
+}
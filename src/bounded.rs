@@ -0,0 +1,120 @@
+//! Clocks with a configured maximum counter.
+//!
+//! Some protocols reserve high bits of counters for their own use, so a
+//! counter growing past a limit would silently corrupt them.
+//! `BoundedClock` refuses to go beyond its maximum and reports
+//! `CounterExhausted` instead, at which point the application can start
+//! a new epoch (see `epoch`) or retire the actor.
+
+use core::error;
+use core::fmt;
+
+use num_traits::Num;
+
+use {Dot, VersionVec};
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Counter of the actor reached the maximum
+pub struct CounterExhausted<I>(pub I);
+
+impl<I: fmt::Debug> fmt::Display for CounterExhausted<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "counter of actor {:?} exhausted", self.0)
+    }
+}
+
+impl<I: fmt::Debug> error::Error for CounterExhausted<I> {}
+
+#[derive(Clone, Debug)]
+/// Version vector whose counters never exceed `max`
+pub struct BoundedClock<I, T> {
+    clock: VersionVec<I, T>,
+    max: T
+}
+
+impl<I, T> BoundedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates an empty clock with counters limited by `max`
+    pub fn new(max: T) -> BoundedClock<I, T> {
+        BoundedClock { clock: VersionVec::new(), max }
+    }
+
+    /// Wraps `clock`, failing if any of its counters is above `max`
+    pub fn from_clock(clock: VersionVec<I, T>, max: T) -> Result<BoundedClock<I, T>, CounterExhausted<I>> {
+        check(&clock, max)?;
+        Ok(BoundedClock { clock, max })
+    }
+
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    pub fn max(&self) -> T {
+        self.max
+    }
+
+    /// Increments counter of `id`, returning the new value.
+    ///
+    /// Fails without modifying the clock if the counter is at `max`.
+    pub fn bump_for(&mut self, id: I) -> Result<T, CounterExhausted<I>> {
+        if self.clock.get(&id).unwrap_or_else(T::zero) >= self.max {
+            return Err(CounterExhausted(id))
+        }
+        Ok(self.clock.increment(id))
+    }
+
+    /// Records `dot` as seen, failing if its counter is above `max`
+    pub fn witness(&mut self, dot: Dot<I, T>) -> Result<(), CounterExhausted<I>> {
+        if dot.counter > self.max {
+            return Err(CounterExhausted(dot.actor))
+        }
+        self.clock.raise(dot.actor, dot.counter);
+        Ok(())
+    }
+
+    /// Merges `other` in. Fails without modifying the clock if any
+    /// counter of `other` is above `max`.
+    pub fn merge(&mut self, other: &VersionVec<I, T>) -> Result<(), CounterExhausted<I>> {
+        check(other, self.max)?;
+        self.clock.merge(other);
+        Ok(())
+    }
+
+    pub fn into_version_vec(self) -> VersionVec<I, T> {
+        self.clock
+    }
+}
+
+fn check<I, T>(clock: &VersionVec<I, T>, max: T) -> Result<(), CounterExhausted<I>>
+    where I: Copy, T: Ord + Copy {
+    match clock.inner.iter().find(|e| e.1 > max) {
+        Some(e) => Err(CounterExhausted(e.0)),
+        None => Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BoundedClock, CounterExhausted};
+    use {Dot, VersionVec};
+
+    #[test]
+    fn exhaustion() {
+        // top bit is reserved
+        let mut clock = BoundedClock::new(0x7fu8);
+        clock.witness(Dot::new(1u32, 0x7e)).unwrap();
+        assert_eq!(clock.bump_for(1), Ok(0x7f));
+        assert_eq!(clock.bump_for(1), Err(CounterExhausted(1)));
+        assert_eq!(clock.clock().get(&1), Some(0x7f));
+        assert_eq!(clock.bump_for(2), Ok(1));
+
+        assert_eq!(clock.witness(Dot::new(3, 0x80)), Err(CounterExhausted(3)));
+        let remote = VersionVec::from_vec(vec![(2, 5), (4, 0x81)]);
+        assert_eq!(clock.merge(&remote), Err(CounterExhausted(4)));
+        assert_eq!(clock.clock().get(&2), Some(1));
+        assert_eq!(BoundedClock::from_clock(remote, 0x7f).err(), Some(CounterExhausted(4)));
+
+        let mut zero = BoundedClock::new(0u8);
+        assert_eq!(zero.bump_for(1u32), Err(CounterExhausted(1)));
+        assert!(zero.clock().as_ref().is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+//! Migrating between clock schemes without a flag day.
+//!
+//! During a migration window, e.g. from per-key vectors over client ids
+//! to node-wide dots, writers which already know the new scheme record
+//! events in both clocks while legacy writers keep updating only the
+//! old one. `BridgedClock` carries both. Two bridged clocks are ordered
+//! only if both schemes agree, so a write unknown to either scheme is
+//! never silently dropped. Once every writer has been upgraded and all
+//! stored clocks carry the new part, `finish` drops the old one.
+
+use num_traits::Num;
+
+use {Ordering, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Pair of clocks of an old and a new scheme
+pub struct BridgedClock<I, T, J, U> {
+    old: VersionVec<I, T>,
+    new: VersionVec<J, U>
+}
+
+impl<I, T, J, U> BridgedClock<I, T, J, U>
+    where I: Ord + Copy, T: Ord + Copy + Num, J: Ord + Copy, U: Ord + Copy + Num {
+    pub fn new(old: VersionVec<I, T>, new: VersionVec<J, U>) -> BridgedClock<I, T, J, U> {
+        BridgedClock { old, new }
+    }
+
+    /// Wraps a clock stored before the migration started
+    pub fn from_old(old: VersionVec<I, T>) -> BridgedClock<I, T, J, U> {
+        BridgedClock::new(old, VersionVec::new())
+    }
+
+    pub fn old(&self) -> &VersionVec<I, T> {
+        &self.old
+    }
+
+    pub fn new_clock(&self) -> &VersionVec<J, U> {
+        &self.new
+    }
+
+    /// Records an event of an upgraded writer in both schemes
+    pub fn bump(&mut self, old_actor: I, new_actor: J) {
+        self.old.bump_for(old_actor);
+        self.new.bump_for(new_actor);
+    }
+
+    /// Records an event of a legacy writer
+    pub fn bump_old(&mut self, actor: I) {
+        self.old.bump_for(actor);
+    }
+
+    pub fn merge(&mut self, other: &BridgedClock<I, T, J, U>) {
+        self.old.merge(&other.old);
+        self.new.merge(&other.new);
+    }
+
+    /// Compares both parts, clocks are ordered only if the orders agree
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &BridgedClock<I, T, J, U>) -> Ordering {
+        let mut result = self.old.cmp(&other.old);
+        result.absorb(self.new.cmp(&other.new));
+        result
+    }
+
+    /// Ends the migration, keeping only the new scheme clock
+    pub fn finish(self) -> VersionVec<J, U> {
+        self.new
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BridgedClock;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn combined_order() {
+        let stored = BridgedClock::<&str, u32, u8, u64>::from_old(VersionVec::from_dot("client-a", 2));
+
+        let mut upgraded = stored.clone();
+        upgraded.bump("client-b", 1);
+        assert_eq!(upgraded.cmp(&stored), Ordering::Greater);
+
+        let mut legacy = stored.clone();
+        legacy.bump_old("client-c");
+        assert_eq!(legacy.cmp(&stored), Ordering::Greater);
+        assert_eq!(legacy.cmp(&upgraded), Ordering::Concurrent);
+
+        // an old part ahead with a new part behind isn't ordered
+        let mut lagging = legacy.clone();
+        lagging.bump_old("client-b");
+        assert_eq!(lagging.cmp(&upgraded), Ordering::Concurrent);
+
+        legacy.merge(&upgraded);
+        assert_eq!(legacy.cmp(&upgraded), Ordering::Greater);
+        assert_eq!(legacy.finish().as_ref(), [(1, 1)]);
+    }
+}
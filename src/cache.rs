@@ -0,0 +1,142 @@
+//! Memoization of comparison results.
+
+use alloc::collections::{BTreeMap, VecDeque};
+use core::hash::Hash;
+
+use num_traits::Num;
+
+use {Ordering, VersionVec};
+
+/// Bounded cache of `cmp` results keyed by `(digest, digest)`.
+///
+/// Useful when the same few clocks get compared over and over, e.g. the
+/// read path comparing against a hot key's stored clock. Any mutation
+/// changes a clock's digest, so stale results are never returned for a
+/// mutated clock; they simply age out. Once full, the oldest result is
+/// evicted.
+///
+/// Digests are 64-bit and not collision resistant, so the cache must not
+/// be fed with clocks from an adversary able to craft collisions.
+pub struct CachedComparator {
+    capacity: usize,
+    results: BTreeMap<(u64, u64), Ordering>,
+    order: VecDeque<(u64, u64)>,
+    hits: u64,
+    misses: u64
+}
+
+impl CachedComparator {
+    /// Creates a cache holding at most `capacity` results
+    pub fn new(capacity: usize) -> CachedComparator {
+        CachedComparator {
+            capacity,
+            results: BTreeMap::new(),
+            order: VecDeque::with_capacity(capacity),
+            hits: 0,
+            misses: 0
+        }
+    }
+
+    /// Compares `a` with `b`, reusing a cached result when possible
+    pub fn cmp<I, T>(&mut self, a: &VersionVec<I, T>, b: &VersionVec<I, T>) -> Ordering
+        where I: Ord + Copy + Hash, T: Ord + Copy + Num + Hash {
+        self.cmp_with_digests(a, a.digest(), b, b.digest())
+    }
+
+    /// Like `cmp`, but uses digests computed by the caller, e.g. kept
+    /// alongside a hot clock to avoid rehashing it on every comparison.
+    /// Digests must be the ones returned by `VersionVec::digest`.
+    pub fn cmp_with_digests<I, T>(&mut self, a: &VersionVec<I, T>, a_digest: u64,
+                                  b: &VersionVec<I, T>, b_digest: u64) -> Ordering
+        where I: Ord + Copy, T: Ord + Copy + Num {
+        let key = (a_digest, b_digest);
+        if let Some(result) = self.results.get(&key) {
+            self.hits += 1;
+            return *result
+        }
+
+        self.misses += 1;
+        let result = a.cmp(b);
+        if self.capacity > 0 {
+            if self.order.len() == self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.results.remove(&oldest);
+                }
+            }
+            self.results.insert(key, result);
+            self.order.push_back(key);
+        }
+        result
+    }
+
+    /// Number of cached results
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns true if there are no cached results
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Number of comparisons answered from cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of comparisons which had to be computed
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Drops all cached results
+    pub fn clear(&mut self) {
+        self.results.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CachedComparator;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn hits_and_invalidation() {
+        let mut cache = CachedComparator::new(4);
+        let mut a = VersionVec::from_vec(vec![(1u32, 2u32)]);
+        let b = VersionVec::from_vec(vec![(1, 1)]);
+
+        assert_eq!(cache.cmp(&a, &b), Ordering::Greater);
+        assert_eq!(cache.cmp(&a, &b), Ordering::Greater);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        a.bump_for(2);
+        let mut c = b.clone();
+        c.bump_for(3);
+        assert_eq!(cache.cmp(&a, &c), Ordering::Concurrent);
+        assert_eq!(cache.cmp(&c, &a), Ordering::Concurrent);
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+    }
+
+    #[test]
+    fn bounded() {
+        let mut cache = CachedComparator::new(2);
+        let base = VersionVec::from_vec(vec![(1u32, 1u32)]);
+
+        for counter in 0..5 {
+            let other = VersionVec::from_vec(vec![(1, counter)]);
+            cache.cmp(&base, &other);
+        }
+        assert_eq!(cache.len(), 2);
+
+        // oldest was evicted, newest is still there
+        cache.cmp(&base, &VersionVec::from_vec(vec![(1, 0)]));
+        cache.cmp(&base, &VersionVec::from_vec(vec![(1, 4)]));
+        assert_eq!((cache.hits(), cache.misses()), (1, 6));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(CachedComparator::new(0).cmp(&base, &base) == Ordering::Equal);
+    }
+}
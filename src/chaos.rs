@@ -0,0 +1,159 @@
+//! Misbehaving clocks for chaos testing.
+//!
+//! `FaultyClock` wraps a `VersionVec` and, following a schedule derived
+//! from a seed, drops merges, delays bumps or hands out duplicate dots.
+//! Runs with the same seed and the same sequence of calls misbehave
+//! identically, so failures found this way can be replayed.
+
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use {Dot, VersionVec};
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq, Default)]
+/// Chances of every fault, in percent
+pub struct Faults {
+    /// Merge is silently ignored
+    pub drop_merges: u8,
+    /// Bumped counter shows up in the clock only later
+    pub delay_bumps: u8,
+    /// Bump returns the previous dot again without advancing
+    pub duplicate_dots: u8
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Fault which actually happened
+pub enum Fault<I, T> {
+    DroppedMerge,
+    DelayedBump(Dot<I, T>),
+    DuplicatedDot(Dot<I, T>)
+}
+
+#[derive(Clone, Debug)]
+/// Version vector misbehaving according to a seeded schedule
+pub struct FaultyClock<I, T> {
+    clock: VersionVec<I, T>,
+    faults: Faults,
+    rng: u64,
+    delayed: Vec<Dot<I, T>>,
+    last: Option<Dot<I, T>>,
+    log: Vec<Fault<I, T>>
+}
+
+impl<I, T> FaultyClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new(clock: VersionVec<I, T>, faults: Faults, seed: u64) -> FaultyClock<I, T> {
+        FaultyClock { clock, faults, rng: seed, delayed: vec![], last: None, log: vec![] }
+    }
+
+    /// Clock as others can observe it, without delayed bumps
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    /// Faults injected so far, oldest first
+    pub fn log(&self) -> &[Fault<I, T>] {
+        &self.log
+    }
+
+    /// Issues the next dot of `id`, unless a fault intervenes
+    pub fn bump_for(&mut self, id: I) -> Dot<I, T> {
+        if let Some(last) = self.last.filter(|dot| dot.actor == id) {
+            if self.roll(self.faults.duplicate_dots) {
+                self.log.push(Fault::DuplicatedDot(last));
+                return last
+            }
+        }
+
+        let current = self.delayed.iter().filter(|dot| dot.actor == id).map(|dot| dot.counter).max()
+            .unwrap_or_else(|| self.clock.get(&id).unwrap_or_else(T::zero));
+        let dot = Dot::new(id, current + T::one());
+        self.last = Some(dot);
+        if self.roll(self.faults.delay_bumps) {
+            self.delayed.push(dot);
+            self.log.push(Fault::DelayedBump(dot));
+        } else {
+            self.delayed.retain(|delayed| delayed.actor != id);
+            self.clock.raise(id, dot.counter);
+        }
+        dot
+    }
+
+    /// Merges `other` in, unless the merge is dropped. Returns `false`
+    /// if it was.
+    pub fn merge(&mut self, other: &VersionVec<I, T>) -> bool {
+        if self.roll(self.faults.drop_merges) {
+            self.log.push(Fault::DroppedMerge);
+            return false
+        }
+        self.clock.merge(other);
+        true
+    }
+
+    /// Applies all delayed bumps
+    pub fn flush_delayed(&mut self) {
+        for dot in self.delayed.drain(..) {
+            self.clock.raise(dot.actor, dot.counter);
+        }
+    }
+
+    pub fn into_version_vec(mut self) -> VersionVec<I, T> {
+        self.flush_delayed();
+        self.clock
+    }
+
+    fn roll(&mut self, percent: u8) -> bool {
+        percent > 0 && self.next() % 100 < percent as u64
+    }
+
+    /// splitmix64
+    fn next(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Fault, Faults, FaultyClock};
+    use {Dot, VersionVec};
+
+    #[test]
+    fn always_faulty() {
+        let faults = Faults { drop_merges: 100, delay_bumps: 100, duplicate_dots: 0 };
+        let mut clock = FaultyClock::new(VersionVec::new(), faults, 1);
+        assert_eq!(clock.bump_for(1u32), Dot::new(1, 1u32));
+        assert_eq!(clock.bump_for(1), Dot::new(1, 2));
+        assert!(clock.clock().get(&1).is_none());
+        assert!(!clock.merge(&VersionVec::singleton(2)));
+        clock.flush_delayed();
+        assert_eq!(clock.clock().as_ref(), [(1, 2)]);
+        assert_eq!(clock.log().len(), 3);
+
+        let duplicating = Faults { duplicate_dots: 100, ..Faults::default() };
+        let mut clock = FaultyClock::new(VersionVec::new(), duplicating, 1);
+        assert_eq!(clock.bump_for(1u32), Dot::new(1, 1u32));
+        assert_eq!(clock.bump_for(1), Dot::new(1, 1));
+        assert_eq!(clock.log(), [Fault::DuplicatedDot(Dot::new(1, 1))]);
+    }
+
+    #[test]
+    fn replays_with_seed() {
+        let faults = Faults { drop_merges: 30, delay_bumps: 30, duplicate_dots: 30 };
+        let run = |seed| {
+            let mut clock = FaultyClock::new(VersionVec::new(), faults, seed);
+            for i in 0..50u32 {
+                clock.bump_for(i % 3);
+                clock.merge(&VersionVec::from_dot(10 + i % 4, i));
+            }
+            (clock.log().to_vec(), clock.into_version_vec().as_ref().to_vec())
+        };
+        let (log, entries) = run(7);
+        assert!(!log.is_empty());
+        assert_eq!(run(7), (log, entries));
+        assert!(run(8).0 != run(7).0);
+    }
+}
@@ -0,0 +1,171 @@
+//! Conversion of clocks to and from Apache Arrow arrays.
+//!
+//! A collection of clocks maps to a `ListArray` with a row per clock,
+//! every row being a list of `{actor, counter}` structs of `UInt64`
+//! columns in actor order. Zero entries are kept, so clocks load back
+//! exactly as stored.
+//!
+//! Enabled by the `arrow` feature.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use arrow_array::builder::OffsetBufferBuilder;
+use arrow_array::{Array, ArrayRef, ListArray, StructArray, UInt64Array};
+use arrow_schema::{DataType, Field, Fields};
+use num_traits::{FromPrimitive, Num, ToPrimitive};
+
+use {ColumnsError, VersionVec};
+
+/// Name of the actor column
+pub const ACTOR: &str = "actor";
+
+/// Name of the counter column
+pub const COUNTER: &str = "counter";
+
+#[derive(Clone, Eq, Debug, PartialEq)]
+/// Reasons an Arrow array can't be loaded as clocks
+pub enum ArrowDecodeError {
+    /// Array doesn't have the type of `data_type`
+    Schema(DataType),
+    /// Array contains nulls
+    Null,
+    /// Actor or counter doesn't fit into target type
+    Overflow,
+    /// Entries of a clock are unsorted or duplicated
+    Columns(ColumnsError)
+}
+
+impl fmt::Display for ArrowDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArrowDecodeError::Schema(ref t) => write!(f, "unexpected clock array type {}", t),
+            ArrowDecodeError::Null => f.write_str("clock array contains nulls"),
+            ArrowDecodeError::Overflow => f.write_str("clock array value out of range"),
+            ArrowDecodeError::Columns(ref e) => write!(f, "invalid clock in array: {}", e)
+        }
+    }
+}
+
+impl error::Error for ArrowDecodeError {}
+
+/// Fields of a single entry struct
+pub fn entry_fields() -> Fields {
+    Fields::from(vec![
+        Field::new(ACTOR, DataType::UInt64, false),
+        Field::new(COUNTER, DataType::UInt64, false),
+    ])
+}
+
+/// Type of arrays produced by `to_arrow`
+pub fn data_type() -> DataType {
+    DataType::List(Arc::new(item_field()))
+}
+
+fn item_field() -> Field {
+    Field::new("item", DataType::Struct(entry_fields()), false)
+}
+
+/// Converts `clocks` to an Arrow list array with a row per clock
+///
+/// # Panics
+///
+/// If an id or counter can't be represented as `u64`.
+pub fn to_arrow<'a, I, T, C>(clocks: C) -> ListArray
+    where I: 'a + Ord + Copy + ToPrimitive, T: 'a + Ord + Copy + Num + ToPrimitive,
+          C: IntoIterator<Item = &'a VersionVec<I, T>> {
+    let mut offsets = OffsetBufferBuilder::new(0);
+    let mut actors = vec![];
+    let mut counters = vec![];
+    for clock in clocks {
+        offsets.push_length(clock.inner.len());
+        for entry in &clock.inner {
+            actors.push(entry.0.to_u64().expect("id doesn't fit into u64"));
+            counters.push(entry.1.to_u64().expect("counter doesn't fit into u64"));
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![Arc::new(UInt64Array::from(actors)), Arc::new(UInt64Array::from(counters))];
+    let values = StructArray::new(entry_fields(), columns, None);
+    ListArray::new(Arc::new(item_field()), offsets.finish(), Arc::new(values), None)
+}
+
+/// Loads clocks from an array produced by `to_arrow`
+pub fn from_arrow<I, T>(array: &ListArray) -> Result<Vec<VersionVec<I, T>>, ArrowDecodeError>
+    where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+    if *array.data_type() != data_type() {
+        return Err(ArrowDecodeError::Schema(array.data_type().clone()))
+    }
+    let values = array.values().as_any().downcast_ref::<StructArray>()
+        .ok_or_else(|| ArrowDecodeError::Schema(array.data_type().clone()))?;
+    let column = |name| values.column_by_name(name)
+        .and_then(|c| c.as_any().downcast_ref::<UInt64Array>())
+        .ok_or_else(|| ArrowDecodeError::Schema(array.data_type().clone()));
+    let (actors, counters) = (column(ACTOR)?, column(COUNTER)?);
+    if array.null_count() > 0 || values.null_count() > 0 || actors.null_count() > 0 || counters.null_count() > 0 {
+        return Err(ArrowDecodeError::Null)
+    }
+
+    let offsets = array.value_offsets();
+    let mut clocks = Vec::with_capacity(array.len());
+    for range in offsets.windows(2) {
+        let (start, end) = (range[0] as usize, range[1] as usize);
+        let ids = actors.values()[start..end].iter()
+            .map(|&a| I::from_u64(a).ok_or(ArrowDecodeError::Overflow))
+            .collect::<Result<Vec<_>, _>>()?;
+        let values = counters.values()[start..end].iter()
+            .map(|&c| T::from_u64(c).ok_or(ArrowDecodeError::Overflow))
+            .collect::<Result<Vec<_>, _>>()?;
+        clocks.push(VersionVec::from_columns(&ids, &values).map_err(ArrowDecodeError::Columns)?);
+    }
+    Ok(clocks)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+
+    use arrow_array::builder::OffsetBufferBuilder;
+    use arrow_array::{Array, ArrayRef, ListArray, StructArray, UInt64Array};
+    use arrow_schema::DataType;
+
+    use super::{entry_fields, from_arrow, item_field, to_arrow, ArrowDecodeError};
+    use {ColumnsError, VersionVec};
+
+    #[test]
+    fn round_trip() {
+        let clocks = vec![
+            VersionVec::from_vec(vec![(1u32, 2u32), (3, 0)]),
+            VersionVec::new(),
+            VersionVec::from_vec(vec![(7, 1)]),
+        ];
+        let array = to_arrow(&clocks);
+        assert_eq!(array.len(), 3);
+        assert_eq!(array.value_length(0), 2);
+
+        let loaded: Vec<VersionVec<u32, u32>> = from_arrow(&array).unwrap();
+        let entries: Vec<_> = loaded.iter().map(|c| c.as_ref().to_vec()).collect();
+        assert_eq!(entries, [vec![(1, 2), (3, 0)], vec![], vec![(7, 1)]]);
+
+        let big = to_arrow(&[VersionVec::from_vec(vec![(1000u64, 1u64)])]);
+        assert_eq!((from_arrow(&big) as Result<Vec<VersionVec<u8, u8>>, _>).err(), Some(ArrowDecodeError::Overflow));
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        let mut offsets = OffsetBufferBuilder::new(1);
+        offsets.push_length(2);
+        let columns: Vec<ArrayRef> = vec![Arc::new(UInt64Array::from(vec![3, 1])), Arc::new(UInt64Array::from(vec![1, 1]))];
+        let values = StructArray::new(entry_fields(), columns, None);
+        let unsorted = ListArray::new(Arc::new(item_field()), offsets.finish(), Arc::new(values), None);
+        assert_eq!((from_arrow(&unsorted) as Result<Vec<VersionVec<u64, u64>>, _>).err(),
+                   Some(ArrowDecodeError::Columns(ColumnsError::Unsorted(1))));
+
+        let ints = ListArray::from_iter_primitive::<arrow_array::types::UInt64Type, _, _>(vec![Some(vec![Some(1)])]);
+        let err = (from_arrow(&ints) as Result<Vec<VersionVec<u64, u64>>, _>).err();
+        assert!(matches!(err, Some(ArrowDecodeError::Schema(DataType::List(_)))));
+    }
+}
@@ -0,0 +1,112 @@
+//! Borsh encoding.
+//!
+//! Layout is that of `Vec<(I, T)>`: `u32` little-endian entry count
+//! followed by entries. Since version vectors are kept sorted, encoding
+//! is deterministic byte-for-byte. Decoding is strict and only accepts
+//! that canonical layout: entries must be sorted by id and ids must be
+//! unique.
+//!
+//! `Normalized` clocks share the layout and apply their zero policy to
+//! decoded entries.
+//!
+//! Enabled by the `borsh` feature.
+
+use alloc::vec::Vec;
+
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+use num_traits::Num;
+
+use {Dot, Normalized, VersionVec, ZeroPolicy};
+
+impl<I: BorshSerialize, T: BorshSerialize> BorshSerialize for VersionVec<I, T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.inner.serialize(writer)
+    }
+}
+
+impl<I: BorshDeserialize + Ord, T: BorshDeserialize> BorshDeserialize for VersionVec<I, T> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<VersionVec<I, T>> {
+        let inner: Vec<(I, T)> = BorshDeserialize::deserialize_reader(reader)?;
+        if inner.windows(2).any(|w| w[0].0 >= w[1].0) {
+            return Err(Error::new(ErrorKind::InvalidData, "version vector entries are not strictly sorted"))
+        }
+        Ok(VersionVec { inner })
+    }
+}
+
+impl<I: BorshSerialize, T: BorshSerialize> BorshSerialize for Dot<I, T> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.actor.serialize(writer)?;
+        self.counter.serialize(writer)
+    }
+}
+
+impl<I: BorshDeserialize, T: BorshDeserialize> BorshDeserialize for Dot<I, T> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Dot<I, T>> {
+        let actor = I::deserialize_reader(reader)?;
+        let counter = T::deserialize_reader(reader)?;
+        Ok(Dot { actor, counter })
+    }
+}
+
+impl<I, T, Z> BorshSerialize for Normalized<I, T, Z>
+    where I: BorshSerialize + Ord + Copy, T: BorshSerialize + Ord + Copy + Num, Z: ZeroPolicy {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.clock().serialize(writer)
+    }
+}
+
+impl<I, T, Z> BorshDeserialize for Normalized<I, T, Z>
+    where I: BorshDeserialize + Ord + Copy, T: BorshDeserialize + Ord + Copy + Num, Z: ZeroPolicy {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Normalized<I, T, Z>> {
+        VersionVec::deserialize_reader(reader).map(Normalized::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use borsh::{from_slice, to_vec};
+    use {Dot, KeepZeros, Normalized, VersionVec};
+
+    #[test]
+    fn layout() {
+        let v = VersionVec::from_vec(vec![(2u8, 0x0102u16), (1, 3)]);
+        assert_eq!(to_vec(&v).unwrap(), [2, 0, 0, 0, 1, 3, 0, 2, 2, 1]);
+
+        let dot = Dot::new(7u8, 1u32);
+        assert_eq!(to_vec(&dot).unwrap(), [7, 1, 0, 0, 0]);
+        assert_eq!(from_slice::<Dot<u8, u32>>(&[7, 1, 0, 0, 0]).unwrap(), dot);
+    }
+
+    #[test]
+    fn round_trip() {
+        let v = VersionVec::from_vec(vec![(10u64, 1u64), (3, 0), (1, 1 << 40)]);
+        let decoded: VersionVec<u64, u64> = from_slice(&to_vec(&v).unwrap()).unwrap();
+        assert_eq!(decoded.as_ref(), v.as_ref());
+
+        // deterministic regardless of construction order
+        let w = VersionVec::from_vec(vec![(1u64, 1u64 << 40), (10, 1), (3, 0)]);
+        assert_eq!(to_vec(&v).unwrap(), to_vec(&w).unwrap());
+    }
+
+    #[test]
+    fn zero_policies() {
+        let bytes = to_vec(&VersionVec::from_vec(vec![(1u8, 2u8), (3, 0)])).unwrap();
+        let stripped: Normalized<u8, u8> = from_slice(&bytes).unwrap();
+        assert_eq!(stripped.as_ref(), [(1, 2)]);
+        let kept: Normalized<u8, u8, KeepZeros> = from_slice(&bytes).unwrap();
+        assert_eq!(to_vec(&kept).unwrap(), bytes);
+    }
+
+    #[test]
+    fn rejects_non_canonical() {
+        // unsorted
+        assert!(from_slice::<VersionVec<u8, u8>>(&[2, 0, 0, 0, 2, 1, 1, 1]).is_err());
+        // duplicate ids
+        assert!(from_slice::<VersionVec<u8, u8>>(&[2, 0, 0, 0, 1, 1, 1, 2]).is_err());
+        // truncated and trailing bytes
+        assert!(from_slice::<VersionVec<u8, u8>>(&[2, 0, 0, 0, 1, 1]).is_err());
+        assert!(from_slice::<VersionVec<u8, u8>>(&[1, 0, 0, 0, 1, 1, 0]).is_err());
+    }
+}
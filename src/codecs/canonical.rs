@@ -0,0 +1,300 @@
+//! Canonical encoding, suitable for content addressing.
+//!
+//! Every clock has exactly one canonical encoding, and clocks which
+//! compare as `Equal` share it:
+//!
+//! * zero entries are dropped,
+//! * entries are sorted by actor,
+//! * all integers are fixed width and big-endian.
+//!
+//! Layout:
+//!
+//! ```text
+//! version: u8 = 1
+//! count:   u32
+//! count times:
+//!     actor:   u64
+//!     counter: u64
+//! ```
+//!
+//! Decoding rejects anything which isn't canonical.
+
+use alloc::vec::Vec;
+use core::cmp;
+use core::error;
+use core::fmt;
+use core::hash::Hasher;
+
+use num_traits::{FromPrimitive, Num, ToPrimitive};
+
+use digest::Fnv64;
+use {Ordering, VersionVec};
+
+/// Version byte of the current layout
+pub const VERSION: u8 = 1;
+
+/// Length of the header: version byte and entry count
+pub const HEADER_LEN: usize = 5;
+
+/// Length of a single entry
+pub const ENTRY_LEN: usize = 16;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons canonical bytes can be rejected
+pub enum DecodeError {
+    /// Input ends before declared number of entries
+    Truncated,
+    /// There are bytes after the last entry
+    TrailingBytes,
+    /// Unknown layout version
+    UnknownVersion(u8),
+    /// Entries are unsorted, duplicated or zero
+    NotCanonical,
+    /// Actor or counter doesn't fit into target type
+    Overflow
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::Truncated => f.write_str("truncated canonical clock"),
+            DecodeError::TrailingBytes => f.write_str("trailing bytes after canonical clock"),
+            DecodeError::UnknownVersion(v) => write!(f, "unknown canonical clock version {}", v),
+            DecodeError::NotCanonical => f.write_str("clock entries are not in canonical form"),
+            DecodeError::Overflow => f.write_str("canonical clock value out of range")
+        }
+    }
+}
+
+impl error::Error for DecodeError {}
+
+impl<I, T> VersionVec<I, T> where I: Ord + Copy + ToPrimitive, T: Ord + Copy + Num + ToPrimitive {
+    /// Returns canonical encoding of the vector, see `codecs::canonical`
+    ///
+    /// # Panics
+    ///
+    /// If an id or counter can't be represented as `u64`.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let entries: Vec<_> = self.inner.iter().filter(|e| e.1 != T::zero()).collect();
+        let count = entries.len() as u32;
+
+        let mut result = Vec::with_capacity(HEADER_LEN + entries.len() * ENTRY_LEN);
+        result.push(VERSION);
+        result.extend_from_slice(&count.to_be_bytes());
+        for entry in entries {
+            result.extend_from_slice(&entry.0.to_u64().expect("id doesn't fit into u64").to_be_bytes());
+            result.extend_from_slice(&entry.1.to_u64().expect("counter doesn't fit into u64").to_be_bytes());
+        }
+        result
+    }
+
+    /// Returns 64-bit FNV-1a hash of canonical encoding.
+    ///
+    /// Easy to reproduce in other systems, but not collision resistant:
+    /// if clocks come from untrusted parties, hash `canonical_bytes`
+    /// with a cryptographic hash instead.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv64::new();
+        hasher.write(&self.canonical_bytes());
+        hasher.finish()
+    }
+}
+
+/// Decodes canonical encoding
+pub fn decode<I, T>(bytes: &[u8]) -> Result<VersionVec<I, T>, DecodeError>
+    where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+    let mut entries = Entries::new(bytes)?;
+    let mut inner = Vec::with_capacity(entries.len());
+    for entry in &mut entries {
+        let (actor, counter) = entry?;
+        inner.push((I::from_u64(actor).ok_or(DecodeError::Overflow)?,
+                    T::from_u64(counter).ok_or(DecodeError::Overflow)?));
+    }
+    Ok(VersionVec { inner })
+}
+
+/// Validating iterator over entries of canonical encoding, yields
+/// `(actor, counter)` pairs without allocating.
+///
+/// Header and total length are checked on creation, ordering and zero
+/// entries while iterating.
+pub struct Entries<'a> {
+    rest: &'a [u8],
+    last: Option<u64>
+}
+
+impl<'a> Entries<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Entries<'a>, DecodeError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(DecodeError::Truncated)
+        }
+        if bytes[0] != VERSION {
+            return Err(DecodeError::UnknownVersion(bytes[0]))
+        }
+
+        let count = read_u32(&bytes[1..]) as usize;
+        let rest = &bytes[HEADER_LEN..];
+        match count.checked_mul(ENTRY_LEN) {
+            Some(len) if len == rest.len() => Ok(Entries { rest, last: None }),
+            Some(len) if len < rest.len() => Err(DecodeError::TrailingBytes),
+            _ => Err(DecodeError::Truncated)
+        }
+    }
+
+    /// Number of remaining entries
+    pub fn len(&self) -> usize {
+        self.rest.len() / ENTRY_LEN
+    }
+
+    /// Returns true if there are no remaining entries
+    pub fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<(u64, u64), DecodeError>;
+
+    fn next(&mut self) -> Option<Result<(u64, u64), DecodeError>> {
+        if self.rest.is_empty() {
+            return None
+        }
+
+        let actor = read_u64(&self.rest[..8]);
+        let counter = read_u64(&self.rest[8..ENTRY_LEN]);
+        self.rest = &self.rest[ENTRY_LEN..];
+
+        if counter == 0 || self.last.is_some_and(|last| last >= actor) {
+            self.rest = &[];
+            return Some(Err(DecodeError::NotCanonical))
+        }
+        self.last = Some(actor);
+        Some(Ok((actor, counter)))
+    }
+}
+
+/// Compares two canonical encodings without decoding them, same as
+/// `VersionVec::cmp` on the decoded clocks.
+///
+/// Both inputs are validated completely, even once the result is known.
+pub fn cmp_encoded(a: &[u8], b: &[u8]) -> Result<Ordering, DecodeError> {
+    let mut left = Entries::new(a)?;
+    let mut right = Entries::new(b)?;
+    let mut l = left.next().transpose()?;
+    let mut r = right.next().transpose()?;
+    let mut result = Ordering::Equal;
+
+    loop {
+        // entries are never zero, so a missing counterpart is always behind
+        let order = match (l, r) {
+            (None, None) => return Ok(result),
+            (Some(_), None) => cmp::Ordering::Greater,
+            (None, Some(_)) => cmp::Ordering::Less,
+            (Some(le), Some(re)) => match le.0.cmp(&re.0) {
+                cmp::Ordering::Less => cmp::Ordering::Greater,
+                cmp::Ordering::Greater => cmp::Ordering::Less,
+                cmp::Ordering::Equal => le.1.cmp(&re.1)
+            }
+        };
+
+        match (l, r) {
+            (Some(le), Some(re)) if le.0 == re.0 => {
+                l = left.next().transpose()?;
+                r = right.next().transpose()?;
+            },
+            (Some(le), Some(re)) if le.0 < re.0 => l = left.next().transpose()?,
+            (Some(_), None) => l = left.next().transpose()?,
+            _ => r = right.next().transpose()?
+        }
+        result.eat(order);
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    u32::from_be_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_be_bytes(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cmp_encoded, decode, DecodeError};
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn compares_encoded() {
+        let clocks: [VersionVec<u32, u32>; 5] = [
+            VersionVec::from_vec(vec![(1, 2), (3, 1)]),
+            VersionVec::from_vec(vec![(1, 2), (2, 0), (3, 1)]),
+            VersionVec::from_vec(vec![(1, 3), (3, 1)]),
+            VersionVec::from_vec(vec![(2, 1)]),
+            VersionVec::new(),
+        ];
+        for a in &clocks {
+            for b in &clocks {
+                assert_eq!(cmp_encoded(&a.canonical_bytes(), &b.canonical_bytes()), Ok(a.cmp(b)));
+            }
+        }
+        assert_eq!(cmp_encoded(&clocks[0].canonical_bytes(), &clocks[1].canonical_bytes()), Ok(Ordering::Equal));
+
+        let mut unsorted = clocks[0].canonical_bytes();
+        unsorted[5..21].swap_with_slice(&mut clocks[0].canonical_bytes()[21..37].to_vec());
+        assert_eq!(cmp_encoded(&unsorted, &clocks[3].canonical_bytes()), Err(DecodeError::NotCanonical));
+        assert_eq!(cmp_encoded(&clocks[3].canonical_bytes(), &[1, 0]), Err(DecodeError::Truncated));
+    }
+
+    #[test]
+    fn layout() {
+        let v = VersionVec::from_vec(vec![(0x0102u16, 3u8), (1, 0)]);
+        assert_eq!(v.canonical_bytes(), [
+            1, 0, 0, 0, 1,
+            0, 0, 0, 0, 0, 0, 1, 2,
+            0, 0, 0, 0, 0, 0, 0, 3,
+        ]);
+        assert_eq!(VersionVec::<u8, u8>::new().canonical_bytes(), [1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn normalized() {
+        let a = VersionVec::from_vec(vec![(1u32, 2u32), (5, 0), (3, 1)]);
+        let b = VersionVec::from_vec(vec![(3u64, 1u64), (1, 2)]);
+        assert_eq!(a.canonical_bytes(), b.canonical_bytes());
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        let c = VersionVec::from_vec(vec![(3u64, 1u64), (1, 3)]);
+        assert!(c.content_hash() != b.content_hash());
+
+        let decoded: VersionVec<u8, u32> = decode(&a.canonical_bytes()).unwrap();
+        assert_eq!(decoded.as_ref(), [(1, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn rejects_non_canonical() {
+        type Result = ::core::result::Result<VersionVec<u64, u64>, DecodeError>;
+        let valid = VersionVec::from_vec(vec![(1u64, 2u64), (3, 1)]).canonical_bytes();
+
+        assert_eq!((decode(&valid[..3]) as Result).err(), Some(DecodeError::Truncated));
+        assert_eq!((decode(&valid[..20]) as Result).err(), Some(DecodeError::Truncated));
+        let mut trailing = valid.clone();
+        trailing.push(0);
+        assert_eq!((decode(&trailing) as Result).err(), Some(DecodeError::TrailingBytes));
+        let mut version = valid.clone();
+        version[0] = 2;
+        assert_eq!((decode(&version) as Result).err(), Some(DecodeError::UnknownVersion(2)));
+        let mut unsorted = valid.clone();
+        unsorted[12] = 5;
+        assert_eq!((decode(&unsorted) as Result).err(), Some(DecodeError::NotCanonical));
+        let mut zero = valid.clone();
+        zero[36] = 0;
+        assert_eq!((decode(&zero) as Result).err(), Some(DecodeError::NotCanonical));
+
+        let big = VersionVec::from_vec(vec![(1000u64, 1u64)]).canonical_bytes();
+        assert_eq!((decode(&big) as ::core::result::Result<VersionVec<u8, u8>, _>).err(), Some(DecodeError::Overflow));
+    }
+}
@@ -0,0 +1,301 @@
+//! Zero-copy access to clocks embedded in FlatBuffers messages.
+//!
+//! Schema is in `schema/version_vec.fbs`; `Entry` and `Clock` below
+//! follow what `flatc --rust` generates for it, so messages embedding a
+//! `Clock` table can be built either with generated code or with
+//! `write_clock`. `ClockRef` compares an embedded clock against a native
+//! `VersionVec` without materializing it.
+//!
+//! Enabled by the `flatbuffers` feature.
+
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use flatbuffers::{self, FlatBufferBuilder, ForwardsUOffset, InvalidFlatbuffer, Vector, WIPOffset};
+use num_traits::Num;
+
+use storage;
+use {Ordering, VersionVec};
+
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Default)]
+/// `Entry` struct: little-endian actor and counter
+pub struct Entry(pub [u8; 16]);
+
+impl Entry {
+    pub fn new(actor: u64, counter: u64) -> Entry {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&actor.to_le_bytes());
+        bytes[8..].copy_from_slice(&counter.to_le_bytes());
+        Entry(bytes)
+    }
+
+    pub fn actor(&self) -> u64 {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.0[..8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    pub fn counter(&self) -> u64 {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.0[8..]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Entry").field("actor", &self.actor()).field("counter", &self.counter()).finish()
+    }
+}
+
+impl flatbuffers::SimpleToVerifyInSlice for Entry {}
+
+impl<'a> flatbuffers::Follow<'a> for Entry {
+    type Inner = &'a Entry;
+
+    #[inline]
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> &'a Entry {
+        <&'a Entry>::follow(buf, loc)
+    }
+}
+
+impl<'a> flatbuffers::Follow<'a> for &'a Entry {
+    type Inner = &'a Entry;
+
+    #[inline]
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> &'a Entry {
+        flatbuffers::follow_cast_ref::<Entry>(buf, loc)
+    }
+}
+
+impl flatbuffers::Push for Entry {
+    type Output = Entry;
+
+    #[inline]
+    unsafe fn push(&self, dst: &mut [u8], _written_len: usize) {
+        dst.copy_from_slice(&self.0);
+    }
+
+    #[inline]
+    fn alignment() -> flatbuffers::PushAlignment {
+        flatbuffers::PushAlignment::new(8)
+    }
+}
+
+impl flatbuffers::Verifiable for Entry {
+    #[inline]
+    fn run_verifier(v: &mut flatbuffers::Verifier, pos: usize) -> Result<(), InvalidFlatbuffer> {
+        v.in_buffer::<Self>(pos)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+/// `Clock` table
+pub struct Clock<'a> {
+    pub _tab: flatbuffers::Table<'a>
+}
+
+impl<'a> flatbuffers::Follow<'a> for Clock<'a> {
+    type Inner = Clock<'a>;
+
+    #[inline]
+    unsafe fn follow(buf: &'a [u8], loc: usize) -> Clock<'a> {
+        Clock { _tab: flatbuffers::Table::new(buf, loc) }
+    }
+}
+
+impl<'a> Clock<'a> {
+    pub const VT_ENTRIES: flatbuffers::VOffsetT = 4;
+
+    pub fn entries(&self) -> Option<Vector<'a, Entry>> {
+        // Safety: created from a verified buffer
+        unsafe { self._tab.get::<ForwardsUOffset<Vector<'a, Entry>>>(Clock::VT_ENTRIES, None) }
+    }
+}
+
+impl flatbuffers::Verifiable for Clock<'_> {
+    #[inline]
+    fn run_verifier(v: &mut flatbuffers::Verifier, pos: usize) -> Result<(), InvalidFlatbuffer> {
+        v.visit_table(pos)?
+            .visit_field::<ForwardsUOffset<Vector<'_, Entry>>>("entries", Clock::VT_ENTRIES, false)?
+            .finish();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Reasons a buffer can't be used as a clock
+pub enum FlatbufError {
+    /// Buffer doesn't pass FlatBuffers verification
+    Invalid(InvalidFlatbuffer),
+    /// Entries aren't strictly sorted by actor
+    Unsorted
+}
+
+impl fmt::Display for FlatbufError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FlatbufError::Invalid(ref e) => write!(f, "invalid flatbuffer: {}", e),
+            FlatbufError::Unsorted => f.write_str("clock entries are not sorted")
+        }
+    }
+}
+
+impl error::Error for FlatbufError {}
+
+impl From<InvalidFlatbuffer> for FlatbufError {
+    fn from(e: InvalidFlatbuffer) -> FlatbufError {
+        FlatbufError::Invalid(e)
+    }
+}
+
+/// Verified view of a `Clock` table
+#[derive(Copy, Clone)]
+pub struct ClockRef<'a> {
+    entries: Option<Vector<'a, Entry>>
+}
+
+impl<'a> ClockRef<'a> {
+    /// Verifies a finished buffer whose root is a `Clock`
+    pub fn from_bytes(buf: &'a [u8]) -> Result<ClockRef<'a>, FlatbufError> {
+        ClockRef::new(flatbuffers::root::<Clock>(buf)?)
+    }
+
+    /// Wraps a `Clock` table, e.g. accessed as a field of a verified
+    /// message, checking that its entries are sorted
+    pub fn new(clock: Clock<'a>) -> Result<ClockRef<'a>, FlatbufError> {
+        let result = ClockRef { entries: clock.entries() };
+        let mut prev = None;
+        for (actor, _) in result.iter() {
+            if prev.is_some_and(|p| p >= actor) {
+                return Err(FlatbufError::Unsorted)
+            }
+            prev = Some(actor);
+        }
+        Ok(result)
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.map_or(0, |e| e.len())
+    }
+
+    /// Returns true if there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Entries as `(actor, counter)`, sorted by actor
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + 'a {
+        self.entries.into_iter().flat_map(|e| e.iter()).map(|e| (e.actor(), e.counter()))
+    }
+
+    /// Returns counter for `actor`, binary searching the buffer
+    pub fn get(&self, actor: u64) -> Option<u64> {
+        let entries = self.entries?;
+        let (mut lo, mut hi) = (0, entries.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let entry = entries.get(mid);
+            if entry.actor() == actor {
+                return Some(entry.counter())
+            } else if entry.actor() < actor {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        None
+    }
+
+    /// Compares embedded clock with `other`, same as `VersionVec::cmp`
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp<I, T>(&self, other: &VersionVec<I, T>) -> Ordering
+        where I: Ord + Copy + Into<u64>, T: Ord + Copy + Num + Into<u64> {
+        storage::cmp_sorted(self.iter(), other.inner.iter().map(|e| (e.0.into(), e.1.into())))
+    }
+
+    /// Returns true if embedded clock has seen everything `other` has
+    pub fn descends<I, T>(&self, other: &VersionVec<I, T>) -> bool
+        where I: Ord + Copy + Into<u64>, T: Ord + Copy + Num + Into<u64> {
+        matches!(self.cmp(other), Ordering::Greater | Ordering::Equal)
+    }
+
+    /// Materializes embedded clock
+    pub fn to_version_vec(&self) -> VersionVec<u64, u64> {
+        VersionVec { inner: self.iter().collect() }
+    }
+}
+
+/// Writes `clock` as a `Clock` table, e.g. to embed it into a message
+pub fn write_clock<'b, I, T, A>(fbb: &mut FlatBufferBuilder<'b, A>, clock: &VersionVec<I, T>) -> WIPOffset<Clock<'b>>
+    where I: Copy + Into<u64>, T: Copy + Into<u64>, A: flatbuffers::Allocator + 'b {
+    let entries: Vec<Entry> = clock.inner.iter().map(|e| Entry::new(e.0.into(), e.1.into())).collect();
+    let entries = fbb.create_vector(&entries);
+
+    let start = fbb.start_table();
+    fbb.push_slot_always::<WIPOffset<_>>(Clock::VT_ENTRIES, entries);
+    let end = fbb.end_table(start);
+    WIPOffset::new(end.value())
+}
+
+/// Encodes `clock` as a standalone buffer with `Clock` root
+pub fn encode<I, T>(clock: &VersionVec<I, T>) -> Vec<u8> where I: Copy + Into<u64>, T: Copy + Into<u64> {
+    let mut fbb = FlatBufferBuilder::new();
+    let root = write_clock(&mut fbb, clock);
+    fbb.finish(root, None);
+    fbb.finished_data().to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use flatbuffers::FlatBufferBuilder;
+    use super::{encode, write_clock, ClockRef, FlatbufError};
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn round_trip() {
+        let clock = VersionVec::from_vec(vec![(1u32, 10u32), (7, 0), (9, 3)]);
+        let buf = encode(&clock);
+
+        let embedded = ClockRef::from_bytes(&buf).unwrap();
+        assert_eq!(embedded.len(), 3);
+        assert_eq!(embedded.get(9), Some(3));
+        assert_eq!(embedded.get(8), None);
+        assert_eq!(embedded.to_version_vec().as_ref(), [(1, 10), (7, 0), (9, 3)]);
+    }
+
+    #[test]
+    fn compare_without_decoding() {
+        let buf = encode(&VersionVec::from_vec(vec![(1u32, 10u32), (2, 5)]));
+        let embedded = ClockRef::from_bytes(&buf).unwrap();
+
+        assert_eq!(embedded.cmp(&VersionVec::from_vec(vec![(1u32, 10u32), (2, 5), (3, 0)])), Ordering::Equal);
+        assert_eq!(embedded.cmp(&VersionVec::from_vec(vec![(1u32, 9u32)])), Ordering::Greater);
+        assert_eq!(embedded.cmp(&VersionVec::from_vec(vec![(1u8, 9u8), (3, 1)])), Ordering::Concurrent);
+        assert!(!embedded.descends(&VersionVec::from_vec(vec![(4u16, 1u16)])));
+
+        let mut fbb = FlatBufferBuilder::new();
+        let root = write_clock(&mut fbb, &VersionVec::<u32, u32>::new());
+        fbb.finish(root, None);
+        let empty = ClockRef::from_bytes(fbb.finished_data()).unwrap();
+        assert!(empty.is_empty());
+        assert!(embedded.descends(&empty.to_version_vec()));
+    }
+
+    #[test]
+    fn rejects_bad_buffers() {
+        match ClockRef::from_bytes(&[1, 2, 3]) {
+            Err(FlatbufError::Invalid(_)) => (),
+            _ => panic!("expected verification error")
+        }
+
+        let unsorted = VersionVec { inner: vec![(2u32, 1u32), (1, 1)] };
+        match ClockRef::from_bytes(&encode(&unsorted)) {
+            Err(FlatbufError::Unsorted) => (),
+            _ => panic!("expected unsorted error")
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Sending 8-byte hashes in place of large actor ids.
+//!
+//! UUID or string actor ids can dominate the size of a clock on the wire.
+//! Peers which share a `HashRegistry` can transmit `to_hashed` clocks
+//! with a `u64` per actor instead and `resolve` them back to full ids.
+//! Registering every actor before its hash is sent catches collisions
+//! up front rather than silently conflating two actors: `to_hashed`
+//! itself merges colliding actors into a single entry.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use num_traits::Num;
+
+use {Actor, VersionVec};
+
+/// Wire hash of `id`, its `Actor::fingerprint`
+pub fn hash_actor<I: Actor>(id: &I) -> u64 {
+    id.fingerprint()
+}
+
+/// Replaces actor ids of `clock` with their hashes.
+///
+/// Actors sharing a hash end up in one entry with the largest of their
+/// counters, use `HashRegistry::register` to detect such collisions.
+pub fn to_hashed<I, T>(clock: &VersionVec<I, T>) -> VersionVec<u64, T>
+    where I: Actor, T: Ord + Copy + Num {
+    let mut entries: Vec<_> = clock.inner.iter().map(|e| (hash_actor(&e.0), e.1)).collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    entries.dedup_by_key(|e| e.0);
+    VersionVec { inner: entries }
+}
+
+#[derive(Clone, Eq, Debug, PartialEq)]
+/// Two different actors hash to the same value
+pub struct Collision<I> {
+    pub hash: u64,
+    pub existing: I,
+    pub new: I
+}
+
+impl<I: fmt::Debug> fmt::Display for Collision<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "actors {:?} and {:?} share hash {:016x}", self.existing, self.new, self.hash)
+    }
+}
+
+impl<I: fmt::Debug> error::Error for Collision<I> {}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Hash which doesn't belong to any registered actor
+pub struct UnknownHash(pub u64);
+
+impl fmt::Display for UnknownHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown actor hash {:016x}", self.0)
+    }
+}
+
+impl error::Error for UnknownHash {}
+
+#[derive(Clone, Debug)]
+/// Known actors by their hashes
+pub struct HashRegistry<I> {
+    actors: BTreeMap<u64, I>
+}
+
+impl<I> HashRegistry<I> where I: Actor + Copy {
+    pub fn new() -> HashRegistry<I> {
+        HashRegistry { actors: BTreeMap::new() }
+    }
+
+    /// Registers `id`, returning its hash. Registering the same id again
+    /// is fine, a different id with the same hash is rejected.
+    pub fn register(&mut self, id: I) -> Result<u64, Collision<I>> {
+        let hash = hash_actor(&id);
+        match self.actors.get(&hash) {
+            Some(&existing) if existing != id => Err(Collision { hash, existing, new: id }),
+            Some(_) => Ok(hash),
+            None => {
+                self.actors.insert(hash, id);
+                Ok(hash)
+            }
+        }
+    }
+
+    /// Full id registered for `hash`
+    pub fn lookup(&self, hash: u64) -> Option<I> {
+        self.actors.get(&hash).cloned()
+    }
+
+    /// Restores full actor ids of a clock produced by `to_hashed`
+    pub fn resolve<T>(&self, clock: &VersionVec<u64, T>) -> Result<VersionVec<I, T>, UnknownHash>
+        where T: Ord + Copy + Num {
+        let entries = clock.inner.iter()
+            .map(|e| self.lookup(e.0).map(|id| (id, e.1)).ok_or(UnknownHash(e.0)))
+            .collect::<Result<_, _>>()?;
+        Ok(VersionVec::from_vec(entries))
+    }
+
+    pub fn len(&self) -> usize {
+        self.actors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+}
+
+impl<I> Default for HashRegistry<I> where I: Actor + Copy {
+    fn default() -> HashRegistry<I> {
+        HashRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hash_actor, to_hashed, Collision, HashRegistry, UnknownHash};
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn round_trip() {
+        let a = [0x11u8; 16];
+        let b = [0x22u8; 16];
+        let clock: VersionVec<[u8; 16], u32> = VersionVec::from_vec(vec![(a, 3), (b, 1)]);
+
+        let mut registry = HashRegistry::new();
+        assert_eq!(registry.register(a), Ok(hash_actor(&a)));
+        assert_eq!(registry.register(a), Ok(hash_actor(&a)));
+
+        let hashed = to_hashed(&clock);
+        assert_eq!(registry.resolve(&hashed).err(), Some(UnknownHash(hash_actor(&b))));
+
+        registry.register(b).unwrap();
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.resolve(&hashed).unwrap().cmp(&clock), Ordering::Equal);
+    }
+
+    #[test]
+    fn collisions() {
+        #[derive(Copy, Clone, Eq, Debug, PartialEq, PartialOrd, Ord)]
+        struct Colliding(u8);
+
+        impl ::Actor for Colliding {
+            fn fingerprint(&self) -> u64 {
+                0
+            }
+        }
+
+        let mut registry = HashRegistry::new();
+        let hash = registry.register(Colliding(1)).unwrap();
+        assert_eq!(registry.register(Colliding(2)), Err(Collision { hash, existing: Colliding(1), new: Colliding(2) }));
+        assert_eq!(registry.lookup(hash), Some(Colliding(1)));
+
+        let clock = VersionVec::from_vec(vec![(Colliding(1), 4u32), (Colliding(2), 9), (Colliding(3), 2)]);
+        assert_eq!(to_hashed(&clock).as_ref(), [(0, 9)]);
+    }
+}
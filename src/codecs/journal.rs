@@ -0,0 +1,160 @@
+//! Compact encoding of `MutationRecord`s for write-ahead logs.
+//!
+//! Each record is a tag byte (0 - bump, 1 - witness, 2 - merge entry)
+//! followed by LEB128 actor id and, except for bumps, LEB128 counter.
+//! Records are self-delimiting, so a log is just their concatenation.
+
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use num_traits::{FromPrimitive, Num, ToPrimitive};
+
+use super::varint;
+use journal::MutationRecord;
+use {Dot, VersionVec};
+
+const BUMP: u8 = 0;
+const WITNESS: u8 = 1;
+const MERGE_ENTRY: u8 = 2;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons a journal can't be decoded
+pub enum JournalError {
+    /// Input ends in the middle of a record
+    Truncated,
+    /// Record tag is not known
+    UnknownTag(u8),
+    /// Id or counter doesn't fit into the target type
+    Overflow
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JournalError::Truncated => f.write_str("truncated journal record"),
+            JournalError::UnknownTag(tag) => write!(f, "unknown journal record tag {}", tag),
+            JournalError::Overflow => f.write_str("journal value out of range")
+        }
+    }
+}
+
+impl error::Error for JournalError {}
+
+/// Appends encoded `record` to `out`.
+///
+/// # Panics
+///
+/// If an id or counter can't be represented as `u64`.
+pub fn encode<I, T>(record: &MutationRecord<I, T>, out: &mut Vec<u8>)
+    where I: ToPrimitive, T: ToPrimitive {
+    fn id<I: ToPrimitive>(actor: &I) -> u64 {
+        actor.to_u64().expect("id doesn't fit into u64")
+    }
+
+    fn counter<T: ToPrimitive>(counter: &T) -> u64 {
+        counter.to_u64().expect("counter doesn't fit into u64")
+    }
+
+    match *record {
+        MutationRecord::Bump(ref actor) => {
+            out.push(BUMP);
+            varint::write(out, id(actor));
+        },
+        MutationRecord::Witness(ref dot) => {
+            out.push(WITNESS);
+            varint::write(out, id(&dot.actor));
+            varint::write(out, counter(&dot.counter));
+        },
+        MutationRecord::MergeEntry(ref actor, ref value) => {
+            out.push(MERGE_ENTRY);
+            varint::write(out, id(actor));
+            varint::write(out, counter(value));
+        }
+    }
+}
+
+/// Decodes a single record from the start of `input`, advancing it past
+/// the record
+pub fn decode<I, T>(input: &mut &[u8]) -> Result<MutationRecord<I, T>, JournalError>
+    where I: FromPrimitive, T: FromPrimitive {
+    let (tag, mut rest) = match input.split_first() {
+        Some((&tag, rest)) => (tag, rest),
+        None => return Err(JournalError::Truncated)
+    };
+
+    let actor = varint::read(&mut rest).ok_or(JournalError::Truncated)?;
+    let actor = I::from_u64(actor).ok_or(JournalError::Overflow)?;
+    let record = match tag {
+        BUMP => MutationRecord::Bump(actor),
+        WITNESS | MERGE_ENTRY => {
+            let counter = varint::read(&mut rest).ok_or(JournalError::Truncated)?;
+            let counter = T::from_u64(counter).ok_or(JournalError::Overflow)?;
+            if tag == WITNESS {
+                MutationRecord::Witness(Dot::new(actor, counter))
+            } else {
+                MutationRecord::MergeEntry(actor, counter)
+            }
+        },
+        _ => return Err(JournalError::UnknownTag(tag))
+    };
+
+    *input = rest;
+    Ok(record)
+}
+
+/// Rebuilds a clock by applying every record of `log` to `base`
+pub fn replay<I, T>(base: &mut VersionVec<I, T>, mut log: &[u8]) -> Result<(), JournalError>
+    where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+    while !log.is_empty() {
+        decode(&mut log)?.apply(base);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::{decode, encode, replay, JournalError};
+    use journal::MutationRecord;
+    use {Dot, VersionVec};
+
+    #[test]
+    fn replays_log() {
+        let records = [
+            MutationRecord::Bump(1u32),
+            MutationRecord::Bump(1),
+            MutationRecord::Witness(Dot::new(2, 5u32)),
+            MutationRecord::MergeEntry(3, 300),
+            MutationRecord::MergeEntry(2, 1),
+        ];
+        let mut log = Vec::new();
+        for record in &records {
+            encode(record, &mut log);
+        }
+        assert_eq!(log.len(), 2 + 2 + 3 + 4 + 3);
+
+        let mut input = &log[..];
+        for record in &records {
+            assert_eq!(decode::<u32, u32>(&mut input), Ok(*record));
+        }
+        assert!(input.is_empty());
+
+        let mut clock = VersionVec::new();
+        replay(&mut clock, &log).unwrap();
+        assert_eq!(clock.as_ref(), [(1, 2), (2, 5), (3, 300)]);
+    }
+
+    #[test]
+    fn bad_records() {
+        fn err(mut input: &[u8]) -> Option<JournalError> {
+            decode::<u8, u8>(&mut input).err()
+        }
+
+        assert_eq!(err(&[]), Some(JournalError::Truncated));
+        assert_eq!(err(&[1, 1]), Some(JournalError::Truncated));
+        assert_eq!(err(&[3, 1]), Some(JournalError::UnknownTag(3)));
+        assert_eq!(err(&[0, 0x80, 0x04]), Some(JournalError::Overflow));
+    }
+}
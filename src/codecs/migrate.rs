@@ -0,0 +1,222 @@
+//! Converting persisted clocks into the canonical encoding.
+//!
+//! Supported sources, all with unsigned integer actors and counters:
+//!
+//! * `Debug` output of `VersionVec`: `Versions: [(1, 3), (2, 1)]`
+//! * `Display` output of `VersionVec`: `{1: 3, 2: 1}`
+//! * JSON objects: `{"1": 3, "2": 1}`
+//! * CSV pairs, one `actor,counter` per line
+//!
+//! `migrate_all` converts a stream of stored clocks lazily, so large
+//! databases can be migrated record by record.
+
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use VersionVec;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Layout of a persisted clock
+pub enum Source {
+    Debug,
+    Display,
+    Json,
+    Csv
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons a persisted clock can't be migrated
+pub enum MigrateError {
+    /// Input doesn't match the source layout, with byte offset
+    Syntax(usize),
+    /// Number doesn't fit into `u64`
+    Overflow,
+    /// Same actor appears twice with different counters
+    DuplicateActor(u64)
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MigrateError::Syntax(offset) => write!(f, "unexpected input at offset {}", offset),
+            MigrateError::Overflow => f.write_str("number out of range"),
+            MigrateError::DuplicateActor(actor) => write!(f, "conflicting entries for actor {}", actor)
+        }
+    }
+}
+
+impl error::Error for MigrateError {}
+
+/// Converts a single persisted clock into canonical encoding
+pub fn migrate(input: &str, source: Source) -> Result<Vec<u8>, MigrateError> {
+    Ok(parse(input, source)?.canonical_bytes())
+}
+
+/// Converts every clock of `inputs`, one result per input
+pub fn migrate_all<'a, S>(inputs: S, source: Source) -> impl Iterator<Item = Result<Vec<u8>, MigrateError>> + 'a
+    where S: IntoIterator<Item = &'a str>, S::IntoIter: 'a {
+    inputs.into_iter().map(move |input| migrate(input, source))
+}
+
+/// Parses a persisted clock
+pub fn parse(input: &str, source: Source) -> Result<VersionVec<u64, u64>, MigrateError> {
+    let mut p = Parser { input: input.as_bytes(), pos: 0 };
+    let entries = match source {
+        Source::Debug => {
+            p.literal("Versions:")?;
+            p.list(b'[', b']', |p| {
+                p.literal("(")?;
+                let actor = p.number()?;
+                p.literal(",")?;
+                let counter = p.number()?;
+                p.literal(")")?;
+                Ok((actor, counter))
+            })?
+        },
+        Source::Display => p.list(b'{', b'}', |p| {
+            let actor = p.number()?;
+            p.literal(":")?;
+            Ok((actor, p.number()?))
+        })?,
+        Source::Json => p.list(b'{', b'}', |p| {
+            p.literal("\"")?;
+            let actor = p.number()?;
+            p.literal("\"")?;
+            p.literal(":")?;
+            Ok((actor, p.number()?))
+        })?,
+        Source::Csv => {
+            let mut entries = vec![];
+            while !p.at_end() {
+                let actor = p.number()?;
+                p.literal(",")?;
+                entries.push((actor, p.number()?));
+            }
+            entries
+        }
+    };
+    if !p.at_end() {
+        return Err(MigrateError::Syntax(p.pos))
+    }
+
+    // identical duplicates are harmless, conflicting ones are not
+    let mut v = VersionVec::from_vec(entries);
+    v.inner.dedup();
+    if let Some(w) = v.inner.windows(2).find(|w| w[0].0 == w[1].0) {
+        return Err(MigrateError::DuplicateActor(w[0].0))
+    }
+    Ok(v)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.pos == self.input.len()
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.pos).cloned()
+    }
+
+    fn literal(&mut self, lit: &str) -> Result<(), MigrateError> {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(lit.as_bytes()) {
+            self.pos += lit.len();
+            Ok(())
+        } else {
+            Err(MigrateError::Syntax(self.pos))
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, MigrateError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut value: u64 = 0;
+        while let Some(digit) = self.input.get(self.pos).filter(|b| b.is_ascii_digit()) {
+            value = value.checked_mul(10)
+                .and_then(|v| v.checked_add((digit - b'0') as u64))
+                .ok_or(MigrateError::Overflow)?;
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(MigrateError::Syntax(start))
+        }
+        Ok(value)
+    }
+
+    /// Parses `open item, item, ... close`
+    fn list<F>(&mut self, open: u8, close: u8, mut item: F) -> Result<Vec<(u64, u64)>, MigrateError>
+        where F: FnMut(&mut Parser<'a>) -> Result<(u64, u64), MigrateError> {
+        let mut entries = vec![];
+        if self.peek() != Some(open) {
+            return Err(MigrateError::Syntax(self.pos))
+        }
+        self.pos += 1;
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(entries)
+        }
+
+        loop {
+            entries.push(item(self)?);
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(entries)
+                },
+                _ => return Err(MigrateError::Syntax(self.pos))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::{migrate, migrate_all, MigrateError, Source};
+    use VersionVec;
+
+    #[test]
+    fn sources() {
+        let clock: VersionVec<u64, u64> = VersionVec::from_vec(vec![(1, 3), (7, 1), (9, 0)]);
+        let expected = clock.canonical_bytes();
+
+        assert_eq!(migrate(&format!("{:?}", clock), Source::Debug), Ok(expected.clone()));
+        assert_eq!(migrate(&format!("{}", clock), Source::Display), Ok(expected.clone()));
+        assert_eq!(migrate(r#" {"7": 1, "1":3} "#, Source::Json), Ok(expected.clone()));
+        assert_eq!(migrate("1,3\n7,1\n", Source::Csv), Ok(expected.clone()));
+
+        let empty = VersionVec::<u64, u64>::new().canonical_bytes();
+        assert_eq!(migrate("Versions: []", Source::Debug), Ok(empty.clone()));
+        assert_eq!(migrate("{}", Source::Json), Ok(empty.clone()));
+        assert_eq!(migrate("", Source::Csv), Ok(empty));
+    }
+
+    #[test]
+    fn errors_and_streaming() {
+        assert_eq!(migrate("{1: 3,}", Source::Display), Err(MigrateError::Syntax(6)));
+        assert_eq!(migrate("{1: 3} x", Source::Display), Err(MigrateError::Syntax(7)));
+        assert_eq!(migrate(r#"{"1": 99999999999999999999}"#, Source::Json), Err(MigrateError::Overflow));
+        assert_eq!(migrate("1,3\n1,4", Source::Csv), Err(MigrateError::DuplicateActor(1)));
+        assert!(migrate("1,3\n1,3", Source::Csv).is_ok());
+
+        let results: Vec<_> = migrate_all(vec!["{1: 2}", "{", "{}"], Source::Display).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok() && results[1].is_err() && results[2].is_ok());
+    }
+}
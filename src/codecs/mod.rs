@@ -0,0 +1,27 @@
+//! Wire and storage encodings of clocks.
+//!
+//! Enabled by the `codecs` feature.
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "borsh")]
+mod borsh;
+pub mod canonical;
+#[cfg(feature = "flatbuffers")]
+pub mod flatbuf;
+pub mod hashed;
+pub mod journal;
+pub mod migrate;
+pub mod segment;
+#[cfg(feature = "serde")]
+mod serde;
+pub mod syncthing;
+pub mod token;
+#[cfg(feature = "uuid")]
+pub mod uuid;
+mod varint;
+
+pub use self::canonical::DecodeError;
+pub use self::journal::JournalError;
+pub use self::segment::{Segment, SegmentError};
+pub use self::token::TokenError;
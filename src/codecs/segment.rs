@@ -0,0 +1,259 @@
+//! Immutable segment files of a `ClockMap`.
+//!
+//! A segment stores a frozen snapshot of clocks for many keys and is
+//! designed to be memory mapped: lookups binary search an index directly
+//! in the mapped bytes, so a restarting node can answer `descends` for
+//! millions of keys without loading them. `Segment` works over any byte
+//! slice, mapping the file (e.g. with `memmap2`) is up to the caller.
+//!
+//! Layout, all integers big-endian:
+//!
+//! ```text
+//! records, sorted by key bytes:
+//!     key:   key_len bytes
+//!     clock: clock_len bytes, canonical encoding
+//! index, one entry per record:
+//!     offset:    u64
+//!     key_len:   u32
+//!     clock_len: u32
+//! footer:
+//!     count:        u64
+//!     index_offset: u64
+//!     magic:        "VVS1"
+//! ```
+
+use alloc::vec::Vec;
+use core::cmp;
+use core::error;
+use core::fmt;
+
+use num_traits::{FromPrimitive, Num, ToPrimitive};
+
+use super::canonical::{self, DecodeError, Entries};
+use map::ClockMap;
+use VersionVec;
+
+/// Trailing bytes identifying a segment and its layout version
+pub const MAGIC: &[u8; 4] = b"VVS1";
+
+const INDEX_ENTRY_LEN: usize = 16;
+const FOOTER_LEN: usize = 20;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons a segment can be rejected
+pub enum SegmentError {
+    /// Missing or unknown magic
+    BadMagic,
+    /// Footer or index point outside of the segment
+    Corrupt,
+    /// Stored clock can't be decoded
+    Clock(DecodeError)
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SegmentError::BadMagic => f.write_str("not a clock segment"),
+            SegmentError::Corrupt => f.write_str("corrupt clock segment"),
+            SegmentError::Clock(e) => write!(f, "bad clock in segment: {}", e)
+        }
+    }
+}
+
+impl error::Error for SegmentError {}
+
+impl From<DecodeError> for SegmentError {
+    fn from(e: DecodeError) -> SegmentError {
+        SegmentError::Clock(e)
+    }
+}
+
+/// Writes `map` as a segment.
+///
+/// # Panics
+///
+/// If an id or counter can't be represented as `u64`.
+pub fn write_segment<K, I, T>(map: &ClockMap<K, I, T>) -> Vec<u8>
+    where K: Ord + AsRef<[u8]>, I: Ord + Copy + ToPrimitive, T: Ord + Copy + Num + ToPrimitive {
+    let mut records: Vec<_> = map.iter().collect();
+    // `K` order may differ from byte order of keys
+    records.sort_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+
+    let mut out = Vec::new();
+    let mut index = Vec::with_capacity(records.len() * INDEX_ENTRY_LEN);
+    for (key, clock) in &records {
+        let clock = clock.canonical_bytes();
+        index.extend_from_slice(&(out.len() as u64).to_be_bytes());
+        index.extend_from_slice(&(key.as_ref().len() as u32).to_be_bytes());
+        index.extend_from_slice(&(clock.len() as u32).to_be_bytes());
+        out.extend_from_slice(key.as_ref());
+        out.extend_from_slice(&clock);
+    }
+
+    let index_offset = out.len() as u64;
+    out.extend_from_slice(&index);
+    out.extend_from_slice(&(records.len() as u64).to_be_bytes());
+    out.extend_from_slice(&index_offset.to_be_bytes());
+    out.extend_from_slice(MAGIC);
+    out
+}
+
+#[derive(Copy, Clone, Debug)]
+/// Read-only view of a segment
+pub struct Segment<'a> {
+    data: &'a [u8],
+    index: &'a [u8]
+}
+
+impl<'a> Segment<'a> {
+    /// Checks footer and index bounds. Records themselves are validated
+    /// lazily on access.
+    pub fn open(bytes: &'a [u8]) -> Result<Segment<'a>, SegmentError> {
+        if bytes.len() < FOOTER_LEN || &bytes[bytes.len() - MAGIC.len()..] != MAGIC {
+            return Err(SegmentError::BadMagic)
+        }
+
+        let footer = &bytes[bytes.len() - FOOTER_LEN..];
+        let count = read_u64(&footer[..8]);
+        let index_offset = read_u64(&footer[8..16]);
+        let index_end = bytes.len() - FOOTER_LEN;
+        let index_len = count.checked_mul(INDEX_ENTRY_LEN as u64).ok_or(SegmentError::Corrupt)?;
+        if index_offset.checked_add(index_len) != Some(index_end as u64) {
+            return Err(SegmentError::Corrupt)
+        }
+
+        let index_offset = index_offset as usize;
+        Ok(Segment { data: &bytes[..index_offset], index: &bytes[index_offset..index_end] })
+    }
+
+    /// Number of keys
+    pub fn len(&self) -> usize {
+        self.index.len() / INDEX_ENTRY_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Key and canonical clock bytes of `idx`-th record
+    pub fn record(&self, idx: usize) -> Result<(&'a [u8], &'a [u8]), SegmentError> {
+        let entry = &self.index[idx * INDEX_ENTRY_LEN..(idx + 1) * INDEX_ENTRY_LEN];
+        let offset = read_u64(&entry[..8]) as usize;
+        let key_len = read_u32(&entry[8..12]) as usize;
+        let clock_len = read_u32(&entry[12..16]) as usize;
+
+        let end = offset.checked_add(key_len).and_then(|e| e.checked_add(clock_len));
+        match end {
+            Some(end) if end <= self.data.len() => {
+                Ok((&self.data[offset..offset + key_len], &self.data[offset + key_len..end]))
+            },
+            _ => Err(SegmentError::Corrupt)
+        }
+    }
+
+    /// Canonical encoding of the clock stored for `key`
+    pub fn clock_bytes(&self, key: &[u8]) -> Result<Option<&'a [u8]>, SegmentError> {
+        let (mut lo, mut hi) = (0, self.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let (candidate, clock) = self.record(mid)?;
+            match candidate.cmp(key) {
+                cmp::Ordering::Less => lo = mid + 1,
+                cmp::Ordering::Greater => hi = mid,
+                cmp::Ordering::Equal => return Ok(Some(clock))
+            }
+        }
+        Ok(None)
+    }
+
+    /// Decodes the clock stored for `key`
+    pub fn get<I, T>(&self, key: &[u8]) -> Result<Option<VersionVec<I, T>>, SegmentError>
+        where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+        match self.clock_bytes(key)? {
+            Some(bytes) => Ok(Some(canonical::decode(bytes)?)),
+            None => Ok(None)
+        }
+    }
+
+    /// True if the clock stored for `key` descends `clock`, checked
+    /// without decoding the stored clock into memory
+    pub fn descends<I, T>(&self, key: &[u8], clock: &VersionVec<I, T>) -> Result<bool, SegmentError>
+        where I: Ord + Copy + ToPrimitive, T: Ord + Copy + Num + ToPrimitive {
+        let mut stored = match self.clock_bytes(key)? {
+            Some(bytes) => Entries::new(bytes)?,
+            None => Entries::new(&[canonical::VERSION, 0, 0, 0, 0])?
+        };
+
+        let mut current = stored.next().transpose()?;
+        for entry in clock.inner.iter().filter(|e| !e.1.is_zero()) {
+            let actor = entry.0.to_u64().expect("id doesn't fit into u64");
+            let counter = entry.1.to_u64().expect("counter doesn't fit into u64");
+            while current.is_some_and(|c| c.0 < actor) {
+                current = stored.next().transpose()?;
+            }
+            match current {
+                Some((a, c)) if a == actor && c >= counter => (),
+                _ => return Ok(false)
+            }
+        }
+        Ok(true)
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bytes[..4]);
+    u32::from_be_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_be_bytes(buf)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::{String, ToString};
+
+    use super::{write_segment, Segment, SegmentError};
+    use map::ClockMap;
+    use VersionVec;
+
+    #[test]
+    fn lookups() {
+        let mut map: ClockMap<String, u32, u32> = ClockMap::new();
+        for key in 0..100u32 {
+            map.insert(format!("key-{}", key), VersionVec::from_vec(vec![(1, key), (key % 7 + 2, 1)]));
+        }
+        map.insert("empty".to_string(), VersionVec::new());
+
+        let bytes = write_segment(&map);
+        let segment = Segment::open(&bytes).unwrap();
+        assert_eq!(segment.len(), 101);
+
+        for (key, clock) in map.iter() {
+            let stored: VersionVec<u32, u32> = segment.get(key.as_bytes()).unwrap().unwrap();
+            assert!(stored.descends(clock) && clock.descends(&stored));
+            assert!(segment.descends(key.as_bytes(), clock).unwrap());
+        }
+
+        assert!(segment.descends(b"key-42", &VersionVec::from_vec(vec![(1u32, 40u32), (2, 1)])).unwrap());
+        assert!(!segment.descends(b"key-42", &VersionVec::from_vec(vec![(1u32, 43u32)])).unwrap());
+        assert!(!segment.descends(b"key-42", &VersionVec::from_vec(vec![(5u32, 1u32)])).unwrap());
+        assert!(segment.descends(b"missing", &VersionVec::<u32, u32>::from_vec(vec![(1, 0)])).unwrap());
+        assert!(!segment.descends(b"missing", &VersionVec::<u32, u32>::singleton(1)).unwrap());
+        assert!(segment.get::<u32, u32>(b"missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_damaged() {
+        let mut map: ClockMap<&str, u32, u32> = ClockMap::new();
+        map.insert("a", VersionVec::singleton(1));
+        let bytes = write_segment(&map);
+
+        assert_eq!(Segment::open(&bytes[..bytes.len() - 1]).err(), Some(SegmentError::BadMagic));
+        assert_eq!(Segment::open(&bytes[1..]).err(), Some(SegmentError::Corrupt));
+        assert!(Segment::open(&write_segment(&ClockMap::<&str, u32, u32>::new())).unwrap().is_empty());
+    }
+}
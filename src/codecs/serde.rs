@@ -0,0 +1,278 @@
+//! Serde support.
+//!
+//! Representation depends on `is_human_readable` of the format:
+//!
+//! * human-readable formats (JSON, YAML, ...) get a map from actor to
+//!   counter, `Dot` is a struct with `actor` and `counter` fields
+//! * compact formats (bincode, postcard, ...) get a sequence of
+//!   `(actor, counter)` tuples in actor order, `Dot` is a tuple
+//!
+//! Maps may list actors in any order, sequences have to be strictly
+//! sorted by actor like in the Borsh encoding. Duplicate actors are
+//! rejected in both.
+//!
+//! `Normalized` clocks use the representation of `VersionVec` and apply
+//! their zero policy to decoded entries.
+//!
+//! Enabled by the `serde` feature.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple, Serializer};
+use num_traits::Num;
+use serde::{Deserialize, Serialize};
+
+use {Dot, Normalized, VersionVec, ZeroPolicy};
+
+impl<I: Serialize, T: Serialize> Serialize for VersionVec<I, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let mut map = serializer.serialize_map(Some(self.inner.len()))?;
+            for (id, counter) in &self.inner {
+                map.serialize_entry(id, counter)?;
+            }
+            map.end()
+        } else {
+            let mut seq = serializer.serialize_seq(Some(self.inner.len()))?;
+            for entry in &self.inner {
+                seq.serialize_element(entry)?;
+            }
+            seq.end()
+        }
+    }
+}
+
+struct ClockVisitor<I, T>(PhantomData<(I, T)>);
+
+impl<'de, I: Deserialize<'de> + Ord, T: Deserialize<'de>> Visitor<'de> for ClockVisitor<I, T> {
+    type Value = VersionVec<I, T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of actors to counters or a sorted sequence of entries")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<VersionVec<I, T>, A::Error> {
+        let mut inner: Vec<(I, T)> = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(entry) = map.next_entry()? {
+            inner.push(entry);
+        }
+        inner.sort_by(|a, b| a.0.cmp(&b.0));
+        if inner.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err(de::Error::custom("duplicate actor in version vector"))
+        }
+        Ok(VersionVec { inner })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<VersionVec<I, T>, A::Error> {
+        let mut inner: Vec<(I, T)> = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(entry) = seq.next_element::<(I, T)>()? {
+            if inner.last().is_some_and(|last| last.0 >= entry.0) {
+                return Err(de::Error::custom("version vector entries are not strictly sorted"))
+            }
+            inner.push(entry);
+        }
+        Ok(VersionVec { inner })
+    }
+}
+
+impl<'de, I: Deserialize<'de> + Ord, T: Deserialize<'de>> Deserialize<'de> for VersionVec<I, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<VersionVec<I, T>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_map(ClockVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_seq(ClockVisitor(PhantomData))
+        }
+    }
+}
+
+impl<I: Serialize, T: Serialize> Serialize for Dot<I, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let mut dot = serializer.serialize_struct("Dot", 2)?;
+            dot.serialize_field("actor", &self.actor)?;
+            dot.serialize_field("counter", &self.counter)?;
+            dot.end()
+        } else {
+            let mut dot = serializer.serialize_tuple(2)?;
+            dot.serialize_element(&self.actor)?;
+            dot.serialize_element(&self.counter)?;
+            dot.end()
+        }
+    }
+}
+
+const DOT_FIELDS: &[&str] = &["actor", "counter"];
+
+enum DotField {
+    Actor,
+    Counter
+}
+
+struct DotFieldVisitor;
+
+impl<'de> Visitor<'de> for DotFieldVisitor {
+    type Value = DotField;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("`actor` or `counter`")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<DotField, E> {
+        match value {
+            "actor" => Ok(DotField::Actor),
+            "counter" => Ok(DotField::Counter),
+            _ => Err(de::Error::unknown_field(value, DOT_FIELDS))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DotField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<DotField, D::Error> {
+        deserializer.deserialize_identifier(DotFieldVisitor)
+    }
+}
+
+struct DotVisitor<I, T>(PhantomData<(I, T)>);
+
+impl<'de, I: Deserialize<'de>, T: Deserialize<'de>> Visitor<'de> for DotVisitor<I, T> {
+    type Value = Dot<I, T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a dot")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Dot<I, T>, A::Error> {
+        let (mut actor, mut counter) = (None, None);
+        while let Some(key) = map.next_key()? {
+            match key {
+                DotField::Actor if actor.is_some() => return Err(de::Error::duplicate_field("actor")),
+                DotField::Actor => actor = Some(map.next_value()?),
+                DotField::Counter if counter.is_some() => return Err(de::Error::duplicate_field("counter")),
+                DotField::Counter => counter = Some(map.next_value()?)
+            }
+        }
+        Ok(Dot {
+            actor: actor.ok_or_else(|| de::Error::missing_field("actor"))?,
+            counter: counter.ok_or_else(|| de::Error::missing_field("counter"))?
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Dot<I, T>, A::Error> {
+        let actor = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let counter = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Dot { actor, counter })
+    }
+}
+
+impl<'de, I: Deserialize<'de>, T: Deserialize<'de>> Deserialize<'de> for Dot<I, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Dot<I, T>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_struct("Dot", DOT_FIELDS, DotVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_tuple(2, DotVisitor(PhantomData))
+        }
+    }
+}
+
+impl<I, T, Z> Serialize for Normalized<I, T, Z>
+    where I: Serialize + Ord + Copy, T: Serialize + Ord + Copy + Num, Z: ZeroPolicy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.clock().serialize(serializer)
+    }
+}
+
+impl<'de, I, T, Z> Deserialize<'de> for Normalized<I, T, Z>
+    where I: Deserialize<'de> + Ord + Copy, T: Deserialize<'de> + Ord + Copy + Num, Z: ZeroPolicy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Normalized<I, T, Z>, D::Error> {
+        VersionVec::deserialize(deserializer).map(Normalized::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Deserializer};
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, assert_tokens, Compact, Configure, Readable, Token};
+
+    use {Dot, KeepZeros, Normalized, VersionVec};
+
+    /// `VersionVec` comparing by entries, as `serde_test` needs `PartialEq`
+    #[derive(Debug)]
+    struct Entries(VersionVec<u32, u32>);
+
+    impl PartialEq for Entries {
+        fn eq(&self, other: &Entries) -> bool {
+            self.0.as_ref() == other.0.as_ref()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Entries {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Entries, D::Error> {
+            VersionVec::deserialize(deserializer).map(Entries)
+        }
+    }
+
+    #[test]
+    fn representations() {
+        let clock = VersionVec::from_vec(vec![(1u32, 2u32), (3, 1)]);
+        let readable = [
+            Token::Map { len: Some(2) },
+            Token::U32(1), Token::U32(2),
+            Token::U32(3), Token::U32(1),
+            Token::MapEnd,
+        ];
+        assert_ser_tokens(&clock.clone().readable(), &readable);
+        assert_de_tokens(&Entries(clock.clone()).readable(), &readable);
+
+        let compact = [
+            Token::Seq { len: Some(2) },
+            Token::Tuple { len: 2 }, Token::U32(1), Token::U32(2), Token::TupleEnd,
+            Token::Tuple { len: 2 }, Token::U32(3), Token::U32(1), Token::TupleEnd,
+            Token::SeqEnd,
+        ];
+        assert_ser_tokens(&clock.clone().compact(), &compact);
+        assert_de_tokens(&Entries(clock).compact(), &compact);
+
+        let dot = Dot::new(1u32, 2u32);
+        assert_tokens(&dot.readable(), &[
+            Token::Struct { name: "Dot", len: 2 },
+            Token::Str("actor"), Token::U32(1),
+            Token::Str("counter"), Token::U32(2),
+            Token::StructEnd,
+        ]);
+        assert_tokens(&dot.compact(), &[Token::Tuple { len: 2 }, Token::U32(1), Token::U32(2), Token::TupleEnd]);
+    }
+
+    #[test]
+    fn zero_policies() {
+        let tokens = [
+            Token::Map { len: Some(2) },
+            Token::U32(1), Token::U32(2),
+            Token::U32(3), Token::U32(0),
+            Token::MapEnd,
+        ];
+        let stripped: Normalized<u32, u32> = Normalized::from_vec(vec![(1, 2)]);
+        assert_de_tokens(&stripped.readable(), &tokens);
+
+        let kept: Normalized<u32, u32, KeepZeros> = Normalized::from_vec(vec![(1, 2), (3, 0)]);
+        assert_tokens(&kept.readable(), &tokens);
+    }
+
+    #[test]
+    fn rejects_invalid() {
+        assert_de_tokens_error::<Readable<Entries>>(&[
+            Token::Map { len: Some(2) },
+            Token::U32(1), Token::U32(2),
+            Token::U32(1), Token::U32(3),
+            Token::MapEnd,
+        ], "duplicate actor in version vector");
+        assert_de_tokens_error::<Compact<Entries>>(&[
+            Token::Seq { len: Some(2) },
+            Token::Tuple { len: 2 }, Token::U32(3), Token::U32(1), Token::TupleEnd,
+            Token::Tuple { len: 2 }, Token::U32(1), Token::U32(2), Token::TupleEnd,
+            Token::SeqEnd,
+        ], "version vector entries are not strictly sorted");
+    }
+}
@@ -0,0 +1,247 @@
+//! Interop with syncthing device ids.
+//!
+//! Syncthing keys its version vectors by "short ids": the first 8 bytes
+//! of a 32-byte device id read as a big-endian `u64`. Device ids are
+//! usually exchanged in their textual form, e.g.
+//! `P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2`:
+//! base32 with a check character after every 13 characters, grouped by 7.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use VersionVec;
+
+/// Full device id, SHA-256 of device certificate
+pub type DeviceId = [u8; 32];
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons a textual device id can be rejected
+pub enum DeviceIdError {
+    /// Wrong number of significant characters
+    InvalidLength(usize),
+    /// Character outside of base32 alphabet
+    InvalidCharacter(char),
+    /// One of check characters doesn't match
+    ChecksumMismatch
+}
+
+impl fmt::Display for DeviceIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeviceIdError::InvalidLength(len) => write!(f, "device id has invalid length {}", len),
+            DeviceIdError::InvalidCharacter(c) => write!(f, "invalid character {:?} in device id", c),
+            DeviceIdError::ChecksumMismatch => f.write_str("device id check character mismatch")
+        }
+    }
+}
+
+impl error::Error for DeviceIdError {}
+
+/// Derives the short id syncthing uses as a version vector key
+pub fn short_id(device: &DeviceId) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&device[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Formats short id the way syncthing does: first 7 base32 characters,
+/// empty for zero id
+pub fn short_id_string(id: u64) -> String {
+    if id == 0 {
+        return String::new()
+    }
+    let mut s = base32_encode(&id.to_be_bytes());
+    s.truncate(7);
+    s
+}
+
+/// Formats device id in canonical grouped form with check characters
+pub fn format_device_id(device: &DeviceId) -> String {
+    let plain = base32_encode(device);
+    let mut checked = String::with_capacity(56);
+    for group in plain.as_bytes().chunks(13) {
+        for &c in group {
+            checked.push(c as char);
+        }
+        checked.push(luhn32(group) as char);
+    }
+
+    let mut result = String::with_capacity(63);
+    for (idx, c) in checked.chars().enumerate() {
+        if idx > 0 && idx % 7 == 0 {
+            result.push('-');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Parses textual device id.
+///
+/// Mirrors syncthing: dashes and spaces are ignored, case doesn't matter,
+/// commonly mistyped `0`, `1` and `8` are read as `O`, `I` and `B`, and
+/// the legacy 52 character form without check characters is accepted.
+pub fn parse_device_id(s: &str) -> Result<DeviceId, DeviceIdError> {
+    let mut plain: Vec<u8> = Vec::with_capacity(56);
+    for c in s.chars() {
+        let c = match c.to_ascii_uppercase() {
+            '-' | ' ' => continue,
+            '0' => 'O',
+            '1' => 'I',
+            '8' => 'B',
+            c => c
+        };
+        if !c.is_ascii() || !ALPHABET.contains(&(c as u8)) {
+            return Err(DeviceIdError::InvalidCharacter(c))
+        }
+        plain.push(c as u8);
+    }
+
+    match plain.len() {
+        52 => (),
+        56 => {
+            let mut stripped = Vec::with_capacity(52);
+            for group in plain.chunks(14) {
+                if luhn32(&group[..13]) != group[13] {
+                    return Err(DeviceIdError::ChecksumMismatch)
+                }
+                stripped.extend_from_slice(&group[..13]);
+            }
+            plain = stripped;
+        },
+        len => return Err(DeviceIdError::InvalidLength(len))
+    }
+
+    let mut device = [0; 32];
+    base32_decode(&plain, &mut device);
+    Ok(device)
+}
+
+/// Builds a vector keyed by short ids from `(device, counter)` pairs.
+/// If several devices share a short id, the largest counter wins.
+pub fn from_devices<S>(counters: S) -> VersionVec<u64, u64> where S: IntoIterator<Item = (DeviceId, u64)> {
+    let mut entries: Vec<(u64, u64)> = counters.into_iter().map(|(d, c)| (short_id(&d), c)).collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    entries.dedup_by_key(|e| e.0);
+    VersionVec::from_vec(entries)
+}
+
+/// Syncthing's flavour of Luhn mod 32. It's not quite the textbook
+/// algorithm (factor starts at 1 from the left), but that's what
+/// device ids use.
+fn luhn32(s: &[u8]) -> u8 {
+    let mut factor = 1;
+    let mut sum = 0;
+    for &c in s {
+        let codepoint = ALPHABET.iter().position(|&a| a == c).expect("non base32 char") as u32;
+        let addend = factor * codepoint;
+        factor = if factor == 2 { 1 } else { 2 };
+        sum += addend / 32 + addend % 32;
+    }
+    ALPHABET[((32 - sum % 32) % 32) as usize]
+}
+
+/// Unpadded RFC 4648 base32
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        result.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    result
+}
+
+/// Decodes base32 characters into `out`, ignoring trailing bits
+fn base32_decode(chars: &[u8], out: &mut [u8]) {
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    let mut idx = 0;
+    for &c in chars {
+        let value = ALPHABET.iter().position(|&a| a == c).expect("non base32 char") as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            if idx < out.len() {
+                out[idx] = (buffer >> bits) as u8;
+                idx += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::String;
+    use super::{format_device_id, from_devices, parse_device_id, short_id, short_id_string, DeviceIdError};
+
+    // Taken from syncthing test cases
+    // https://github.com/syncthing/syncthing/blob/main/lib/protocol/deviceid_test.go
+    const FORMATTED: &str = "P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2";
+
+    #[test]
+    fn parse_and_format() {
+        let cases = [
+            FORMATTED,
+            "P56IOI7MZJNU2YIQGDREYDM2MGTIMGL3BXNPQ6W5BMTBBZ4TJXZWICQ2",
+            "P56IOI7 MZJNU2Y IQGDREY DM2MGTI MGL3BXN PQ6W5BM TBBZ4TJ XZWICQ2",
+            "p56ioi7-mzjnu2y-iqgdrey-dm2mgti-mgl3bxn-pq6w5bm-tbbz4tj-xzwicq2",
+            "P56I0I7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2",
+        ];
+
+        for case in cases.iter() {
+            let device = parse_device_id(case).unwrap();
+            assert_eq!(format_device_id(&device), FORMATTED, "case {}", case);
+        }
+
+        // legacy form without check characters
+        let legacy: String = FORMATTED.chars().filter(|&c| c != '-')
+            .enumerate().filter(|&(idx, _)| idx % 14 != 13).map(|(_, c)| c)
+            .collect();
+        assert_eq!(legacy.len(), 52);
+        assert_eq!(format_device_id(&parse_device_id(&legacy).unwrap()), FORMATTED);
+    }
+
+    #[test]
+    fn short_ids() {
+        let device = parse_device_id(FORMATTED).unwrap();
+        let id = short_id(&device);
+        assert_eq!(short_id_string(id), "P56IOI7");
+        assert_eq!(id >> 56, device[0] as u64);
+        assert_eq!(short_id_string(0), "");
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse_device_id("P56IOI7"), Err(DeviceIdError::InvalidLength(7)));
+        assert_eq!(parse_device_id("P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ!"),
+                   Err(DeviceIdError::InvalidCharacter('!')));
+        assert_eq!(parse_device_id("P56IOI7-MZJNU2Z-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2"),
+                   Err(DeviceIdError::ChecksumMismatch));
+    }
+
+    #[test]
+    fn vectors() {
+        let a = parse_device_id(FORMATTED).unwrap();
+        let mut b = a;
+        b[31] ^= 1;
+        let mut c = [0; 32];
+        c[0] = 1;
+
+        let v = from_devices(vec![(a, 3), (c, 1), (b, 5)]);
+        assert_eq!(v.as_ref(), [(1 << 56, 1), (short_id(&a), 5)]);
+    }
+}
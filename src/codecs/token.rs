@@ -0,0 +1,143 @@
+//! Opaque tokens carrying a causal context to clients and back.
+//!
+//! A token is hex encoded, so it can be passed in HTTP headers and
+//! similar places where clients shouldn't interpret it. Layout: version
+//! byte, LEB128 entry count, LEB128 `(id, counter)` pairs.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use num_traits::{FromPrimitive, Num, ToPrimitive};
+
+use super::varint;
+use VersionVec;
+
+const TOKEN_VERSION: u8 = 1;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons a context token can be rejected
+pub enum TokenError {
+    /// Token is not a valid hex string or is truncated
+    Malformed,
+    /// Token was produced by an unknown encoding version
+    UnknownVersion(u8),
+    /// Id or counter doesn't fit into the target type
+    Overflow
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TokenError::Malformed => f.write_str("malformed context token"),
+            TokenError::UnknownVersion(v) => write!(f, "unknown context token version {}", v),
+            TokenError::Overflow => f.write_str("context token value out of range")
+        }
+    }
+}
+
+impl error::Error for TokenError {}
+
+/// Encodes `ctx` as a token.
+///
+/// Zero entries are dropped as they carry no causal information.
+///
+/// # Panics
+///
+/// If an id or counter can't be represented as `u64`.
+pub fn encode<I, T>(ctx: &VersionVec<I, T>) -> String
+    where I: ToPrimitive, T: ToPrimitive + Num {
+    let entries: Vec<_> = ctx.inner.iter().filter(|e| !e.1.is_zero()).collect();
+
+    let mut bytes = vec![TOKEN_VERSION];
+    varint::write(&mut bytes, entries.len() as u64);
+    for entry in entries {
+        varint::write(&mut bytes, entry.0.to_u64().expect("id doesn't fit into u64"));
+        varint::write(&mut bytes, entry.1.to_u64().expect("counter doesn't fit into u64"));
+    }
+
+    let mut token = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        token.push_str(&format!("{:02x}", byte));
+    }
+    token
+}
+
+/// Decodes context from a token produced by `encode`
+pub fn decode<I, T>(token: &str) -> Result<VersionVec<I, T>, TokenError>
+    where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+    if !token.len().is_multiple_of(2) || !token.is_ascii() {
+        return Err(TokenError::Malformed)
+    }
+
+    let bytes = (0..token.len()).step_by(2)
+        .map(|idx| u8::from_str_radix(&token[idx..idx + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| TokenError::Malformed)?;
+
+    let (version, mut input) = match bytes.split_first() {
+        Some((&version, rest)) => (version, rest),
+        None => return Err(TokenError::Malformed)
+    };
+    if version != TOKEN_VERSION {
+        return Err(TokenError::UnknownVersion(version))
+    }
+
+    let count = varint::read(&mut input).ok_or(TokenError::Malformed)?;
+    let mut entries = Vec::new();
+    let mut last = None;
+    for _ in 0..count {
+        let id = varint::read(&mut input).ok_or(TokenError::Malformed)?;
+        // ids must be strictly increasing, like those `encode` writes
+        if last.is_some_and(|last| last >= id) {
+            return Err(TokenError::Malformed)
+        }
+        last = Some(id);
+        let counter = varint::read(&mut input).ok_or(TokenError::Malformed)?;
+        entries.push((I::from_u64(id).ok_or(TokenError::Overflow)?,
+                      T::from_u64(counter).ok_or(TokenError::Overflow)?));
+    }
+
+    if !input.is_empty() {
+        return Err(TokenError::Malformed)
+    }
+
+    Ok(VersionVec { inner: entries })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode, TokenError};
+    use VersionVec;
+
+    #[test]
+    fn round_trip() {
+        let ctx: VersionVec<u64, u32> = VersionVec::from_vec(vec![(1, 3), (7, 0), (300, 1 << 20)]);
+        let token = encode(&ctx);
+
+        let decoded: VersionVec<u64, u32> = decode(&token).unwrap();
+        assert_eq!(decoded.as_ref(), [(1, 3), (300, 1 << 20)]);
+    }
+
+    #[test]
+    fn bad_tokens() {
+        fn err(token: &str) -> Option<TokenError> {
+            (decode(token) as Result<VersionVec<u8, u8>, _>).err()
+        }
+
+        let ctx: VersionVec<u64, u64> = VersionVec::from_vec(vec![(1000, 1)]);
+        assert_eq!(err(&encode(&ctx)), Some(TokenError::Overflow));
+
+        assert_eq!(err(""), Some(TokenError::Malformed));
+        assert_eq!(err("0"), Some(TokenError::Malformed));
+        assert_eq!(err("zz"), Some(TokenError::Malformed));
+        assert_eq!(err("0102"), Some(TokenError::Malformed));
+        assert_eq!(err("0200"), Some(TokenError::UnknownVersion(2)));
+        assert_eq!(err("010000"), Some(TokenError::Malformed));
+        // duplicated and unsorted ids
+        assert_eq!(err("010201010102"), Some(TokenError::Malformed));
+        assert_eq!(err("010202010101"), Some(TokenError::Malformed));
+        assert!(err("010201010201").is_none());
+    }
+}
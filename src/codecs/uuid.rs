@@ -0,0 +1,121 @@
+//! Clocks with `Uuid` actors.
+//!
+//! Compact layout, 16 bytes per actor plus a varint per counter:
+//!
+//! ```text
+//! count: varint
+//! count times:
+//!     actor:   16 bytes, RFC 4122 byte order
+//!     counter: varint
+//! ```
+//!
+//! Like the canonical encoding, zero entries are dropped and decoding
+//! rejects unsorted, duplicated or zero entries. `Uuid` orders by its
+//! bytes, so clocks are sorted the same way as their encoding and as
+//! the `u128` clocks produced by `to_u128`.
+//!
+//! Enabled by the `uuid` feature.
+
+use alloc::vec::Vec;
+
+use num_traits::{FromPrimitive, Num, ToPrimitive};
+use uuid::Uuid;
+
+use super::canonical::DecodeError;
+use super::varint;
+use VersionVec;
+
+/// Encodes `clock` in the compact layout
+///
+/// # Panics
+///
+/// If a counter can't be represented as `u64`.
+pub fn encode<T>(clock: &VersionVec<Uuid, T>) -> Vec<u8> where T: Ord + Copy + Num + ToPrimitive {
+    let entries: Vec<_> = clock.inner.iter().filter(|e| e.1 != T::zero()).collect();
+    let mut out = Vec::with_capacity(1 + entries.len() * 18);
+    varint::write(&mut out, entries.len() as u64);
+    for entry in entries {
+        out.extend_from_slice(entry.0.as_bytes());
+        varint::write(&mut out, entry.1.to_u64().expect("counter doesn't fit into u64"));
+    }
+    out
+}
+
+/// Decodes the compact layout
+pub fn decode<T>(mut bytes: &[u8]) -> Result<VersionVec<Uuid, T>, DecodeError>
+    where T: Ord + Copy + Num + FromPrimitive {
+    let count = varint::read(&mut bytes).ok_or(DecodeError::Truncated)?;
+    if count > (bytes.len() / 17) as u64 {
+        return Err(DecodeError::Truncated)
+    }
+
+    let mut inner: Vec<(Uuid, T)> = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        if bytes.len() < 16 {
+            return Err(DecodeError::Truncated)
+        }
+        let (actor, rest) = bytes.split_at(16);
+        bytes = rest;
+        let actor = Uuid::from_slice(actor).map_err(|_| DecodeError::Truncated)?;
+        let counter = varint::read(&mut bytes).ok_or(DecodeError::Truncated)?;
+
+        if counter == 0 || inner.last().is_some_and(|last| last.0 >= actor) {
+            return Err(DecodeError::NotCanonical)
+        }
+        inner.push((actor, T::from_u64(counter).ok_or(DecodeError::Overflow)?));
+    }
+    if !bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes)
+    }
+    Ok(VersionVec { inner })
+}
+
+/// Converts actors to their `u128` values, keeping the order
+pub fn to_u128<T: Copy>(clock: &VersionVec<Uuid, T>) -> VersionVec<u128, T> {
+    VersionVec { inner: clock.inner.iter().map(|e| (e.0.as_u128(), e.1)).collect() }
+}
+
+/// Converts `u128` actors back to `Uuid`s, keeping the order
+pub fn from_u128<T: Copy>(clock: &VersionVec<u128, T>) -> VersionVec<Uuid, T> {
+    VersionVec { inner: clock.inner.iter().map(|e| (Uuid::from_u128(e.0), e.1)).collect() }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::{decode, encode, from_u128, to_u128};
+    use codecs::DecodeError;
+    use {Actor, VersionVec};
+
+    #[test]
+    fn compact_round_trip() {
+        let a = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+        let b = Uuid::from_u128(0xf000_0000_0000_0000_0000_0000_0000_0001);
+        let clock = VersionVec::from_vec(vec![(b, 300u32), (a, 1), (Uuid::nil(), 0)]);
+
+        let bytes = encode(&clock);
+        assert_eq!(bytes.len(), 1 + 17 + 18);
+        assert_eq!(&bytes[1..17], a.as_bytes());
+        let decoded: VersionVec<Uuid, u32> = decode(&bytes).unwrap();
+        assert_eq!(decoded.as_ref(), [(a, 1), (b, 300)]);
+
+        let numeric = to_u128(&decoded);
+        assert_eq!(numeric.as_ref(), [(a.as_u128(), 1), (b.as_u128(), 300)]);
+        assert_eq!(from_u128(&numeric).as_ref(), decoded.as_ref());
+        assert_eq!(a.fingerprint(), a.as_bytes().fingerprint());
+
+        assert_eq!(decode::<u32>(&bytes[..20]).err(), Some(DecodeError::Truncated));
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(decode::<u32>(&trailing).err(), Some(DecodeError::TrailingBytes));
+        assert_eq!(decode::<u8>(&bytes).err(), Some(DecodeError::Overflow));
+
+        let mut unsorted = vec![2];
+        unsorted.extend_from_slice(b.as_bytes());
+        unsorted.push(1);
+        unsorted.extend_from_slice(a.as_bytes());
+        unsorted.push(1);
+        assert_eq!(decode::<u32>(&unsorted).err(), Some(DecodeError::NotCanonical));
+    }
+}
@@ -0,0 +1,61 @@
+//! LEB128 encoding of unsigned integers used by compact encodings
+
+use alloc::vec::Vec;
+
+/// Appends `value` to `out` as unsigned LEB128
+pub fn write(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Reads unsigned LEB128 from the start of `input`, advancing it past
+/// the value. Returns `None` on truncated or overlong input.
+pub fn read(input: &mut &[u8]) -> Option<u64> {
+    let mut result = 0u64;
+    let mut shift = 0;
+
+    for (idx, byte) in input.iter().enumerate() {
+        if shift >= 64 || (shift == 63 && byte & 0x7e != 0) {
+            return None
+        }
+
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            *input = &input[idx + 1..];
+            return Some(result)
+        }
+        shift += 7;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn round_trip() {
+        for &value in &[0, 1, 127, 128, 300, 1 << 32, u64::MAX] {
+            let mut buf = vec![];
+            super::write(&mut buf, value);
+            let mut input = &buf[..];
+            assert_eq!(super::read(&mut input), Some(value));
+            assert!(input.is_empty());
+        }
+    }
+
+    #[test]
+    fn truncated_and_overlong() {
+        let mut input: &[u8] = &[0x80, 0x80];
+        assert_eq!(super::read(&mut input), None);
+
+        let mut input: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+        assert_eq!(super::read(&mut input), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! Replicated counters built on version vector storage.
+//!
+//! A G-Counter is a version vector read differently: every actor counts
+//! its own increments, merge keeps the maximum per actor and the value
+//! is the sum of all entries. A PN-Counter pairs two of them to support
+//! decrements.
+
+use num_traits::{Num, Signed};
+
+use {Ordering, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Grow-only counter
+pub struct GCounter<I, T> {
+    clock: VersionVec<I, T>
+}
+
+impl<I, T> GCounter<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> GCounter<I, T> {
+        GCounter { clock: VersionVec::new() }
+    }
+
+    /// Adds one on behalf of `actor`
+    pub fn increment(&mut self, actor: I) {
+        self.clock.increment(actor);
+    }
+
+    /// Adds `amount` on behalf of `actor`
+    pub fn increment_by(&mut self, actor: I, amount: T) {
+        let current = self.clock.get(&actor).unwrap_or_else(T::zero);
+        self.clock.raise(actor, current + amount);
+    }
+
+    /// Sum of all increments
+    pub fn value(&self) -> T {
+        self.clock.inner.iter().fold(T::zero(), |sum, e| sum + e.1)
+    }
+
+    /// Increments of `actor`
+    pub fn contribution(&self, actor: I) -> T {
+        self.clock.get(&actor).unwrap_or_else(T::zero)
+    }
+
+    pub fn merge(&mut self, other: &GCounter<I, T>) {
+        self.clock.merge(&other.clock);
+    }
+
+    /// Compares which increments replicas have seen
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &GCounter<I, T>) -> Ordering {
+        self.clock.cmp(&other.clock)
+    }
+
+    /// Per-actor increments as a version vector
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+}
+
+impl<I, T> Default for GCounter<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> GCounter<I, T> {
+        GCounter::new()
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Counter supporting both increments and decrements
+pub struct PNCounter<I, T> {
+    increments: GCounter<I, T>,
+    decrements: GCounter<I, T>
+}
+
+impl<I, T> PNCounter<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> PNCounter<I, T> {
+        PNCounter { increments: GCounter::new(), decrements: GCounter::new() }
+    }
+
+    pub fn increment(&mut self, actor: I) {
+        self.increments.increment(actor);
+    }
+
+    pub fn decrement(&mut self, actor: I) {
+        self.decrements.increment(actor);
+    }
+
+    pub fn increment_by(&mut self, actor: I, amount: T) {
+        self.increments.increment_by(actor, amount);
+    }
+
+    pub fn decrement_by(&mut self, actor: I, amount: T) {
+        self.decrements.increment_by(actor, amount);
+    }
+
+    /// Sums of increments and decrements, for counter types which can't
+    /// represent negative values
+    pub fn totals(&self) -> (T, T) {
+        (self.increments.value(), self.decrements.value())
+    }
+
+    /// Current value
+    pub fn value(&self) -> T where T: Signed {
+        self.increments.value() - self.decrements.value()
+    }
+
+    pub fn merge(&mut self, other: &PNCounter<I, T>) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+    }
+
+    /// Compares which updates replicas have seen
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &PNCounter<I, T>) -> Ordering {
+        let mut result = self.increments.cmp(&other.increments);
+        match (result, self.decrements.cmp(&other.decrements)) {
+            (_, Ordering::Equal) => (),
+            (Ordering::Equal, order) => result = order,
+            (a, b) if a != b => result = Ordering::Concurrent,
+            _ => ()
+        }
+        result
+    }
+
+    pub fn increments(&self) -> &GCounter<I, T> {
+        &self.increments
+    }
+
+    pub fn decrements(&self) -> &GCounter<I, T> {
+        &self.decrements
+    }
+}
+
+impl<I, T> Default for PNCounter<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> PNCounter<I, T> {
+        PNCounter::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GCounter, PNCounter};
+    use Ordering;
+
+    #[test]
+    fn g_counter() {
+        let mut a = GCounter::new();
+        a.increment(1u32);
+        a.increment_by(1, 4u64);
+        let mut b = a.clone();
+        b.increment_by(2, 10);
+        a.increment(3);
+        assert_eq!(a.cmp(&b), Ordering::Concurrent);
+
+        a.merge(&b);
+        b.merge(&a);
+        assert_eq!((a.value(), b.value()), (16, 16));
+        assert_eq!(a.contribution(1), 5);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+    }
+
+    #[test]
+    fn pn_counter() {
+        let mut a = PNCounter::new();
+        a.increment_by(1u32, 3i64);
+        let mut b = a.clone();
+        b.decrement_by(2, 5);
+        assert_eq!(b.cmp(&a), Ordering::Greater);
+        a.increment(1);
+        assert_eq!(b.cmp(&a), Ordering::Concurrent);
+
+        a.merge(&b);
+        assert_eq!(a.value(), -1);
+        assert_eq!(a.totals(), (4, 5));
+        assert_eq!(a.cmp(&b), Ordering::Greater);
+    }
+}
@@ -0,0 +1,132 @@
+//! Human-readable metadata about actors, kept apart from clocks.
+
+use alloc::collections::btree_map::{self, BTreeMap};
+use core::fmt;
+
+use VersionVec;
+
+/// Side table of per-actor metadata (display names, endpoints, ...).
+///
+/// Clocks store nothing but ids; a directory can be attached when
+/// formatting, see `VersionVec::display_with`. Actors are displayed
+/// using `Display` of their metadata, unknown ones by their id.
+pub struct ActorDirectory<I, M> {
+    actors: BTreeMap<I, M>
+}
+
+impl<I: Ord, M> ActorDirectory<I, M> {
+    /// Creates an empty directory
+    pub fn new() -> ActorDirectory<I, M> {
+        ActorDirectory {
+            actors: BTreeMap::new()
+        }
+    }
+
+    /// Sets metadata for `id`, returns previous one
+    pub fn insert(&mut self, id: I, meta: M) -> Option<M> {
+        self.actors.insert(id, meta)
+    }
+
+    /// Returns metadata for `id`
+    pub fn get(&self, id: &I) -> Option<&M> {
+        self.actors.get(id)
+    }
+
+    /// Removes metadata for `id`
+    pub fn remove(&mut self, id: &I) -> Option<M> {
+        self.actors.remove(id)
+    }
+
+    /// Number of known actors
+    pub fn len(&self) -> usize {
+        self.actors.len()
+    }
+
+    /// Returns true if there are no known actors
+    pub fn is_empty(&self) -> bool {
+        self.actors.is_empty()
+    }
+
+    /// Known actors and their metadata, sorted by id
+    pub fn iter(&self) -> btree_map::Iter<'_, I, M> {
+        self.actors.iter()
+    }
+
+    /// Returns displayable label for `id`
+    pub fn label<'a>(&'a self, id: &'a I) -> Label<'a, I, M> {
+        Label {
+            id,
+            meta: self.actors.get(id)
+        }
+    }
+}
+
+impl<I: Ord, M> Default for ActorDirectory<I, M> {
+    fn default() -> ActorDirectory<I, M> {
+        ActorDirectory::new()
+    }
+}
+
+impl<I: Ord, M> core::iter::FromIterator<(I, M)> for ActorDirectory<I, M> {
+    fn from_iter<S: IntoIterator<Item = (I, M)>>(iter: S) -> ActorDirectory<I, M> {
+        ActorDirectory {
+            actors: iter.into_iter().collect()
+        }
+    }
+}
+
+/// Actor shown by its metadata if known, by id otherwise
+pub struct Label<'a, I: 'a, M: 'a> {
+    id: &'a I,
+    meta: Option<&'a M>
+}
+
+impl<'a, I: fmt::Display, M: fmt::Display> fmt::Display for Label<'a, I, M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.meta {
+            Some(meta) => meta.fmt(f),
+            None => self.id.fmt(f)
+        }
+    }
+}
+
+/// Version vector formatted with actor labels from a directory
+pub struct Labeled<'a, I: 'a, T: 'a, M: 'a> {
+    pub(crate) clock: &'a VersionVec<I, T>,
+    pub(crate) directory: &'a ActorDirectory<I, M>
+}
+
+impl<'a, I, T, M> fmt::Display for Labeled<'a, I, T, M> where I: Ord + fmt::Display, T: fmt::Display, M: fmt::Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("{")?;
+        for (idx, entry) in self.clock.inner.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", self.directory.label(&entry.0), entry.1)?;
+        }
+        f.write_str("}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+    use super::ActorDirectory;
+    use VersionVec;
+
+    #[test]
+    fn labels() {
+        let mut directory: ActorDirectory<u32, &str> = vec![(1, "alice"), (2, "bob")].into_iter().collect();
+        let v = VersionVec::from_vec(vec![(1u32, 3u32), (2, 1), (42, 7)]);
+
+        assert_eq!(v.display_with(&directory).to_string(), "{alice: 3, bob: 1, 42: 7}");
+        assert_eq!(v.to_string(), "{1: 3, 2: 1, 42: 7}");
+
+        directory.insert(42, "carol");
+        directory.remove(&1);
+        assert_eq!(v.display_with(&directory).to_string(), "{1: 3, bob: 1, carol: 7}");
+        assert_eq!(directory.label(&7).to_string(), "7");
+        assert_eq!(directory.len(), 2);
+    }
+}
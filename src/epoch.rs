@@ -0,0 +1,250 @@
+//! Clocks with 2-byte counters for constrained peers.
+//!
+//! A `u16` counter overflows after 65535 events, which long-running
+//! embedded peers hit quickly. `EpochClock` pairs such counters with an
+//! epoch number. Once everything recorded in the current epoch is known
+//! to be replicated everywhere, a peer calls `advance_epoch`, which
+//! resets all counters: any clock of a newer epoch supersedes every
+//! clock of an older one.
+//!
+//! Epochs wrap around too, so they are compared with serial number
+//! arithmetic: a clock is considered newer if its epoch is at most
+//! `EPOCH_WINDOW` ahead. Peers which fell further behind can't be
+//! ordered and operations involving them fail with
+//! `EpochError::OutOfWindow`.
+
+use core::error;
+use core::fmt;
+
+use events::{ChangeEvent, ChangeObserver};
+use {Ordering, VersionVec};
+
+/// Maximum epoch distance between clocks which can still be compared
+pub const EPOCH_WINDOW: u16 = 1 << 14;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Failures of `EpochClock` operations
+pub enum EpochError {
+    /// Counter would overflow, epoch has to be advanced first
+    CounterExhausted,
+    /// Epochs of the clocks are too far apart to be ordered
+    OutOfWindow,
+    /// Clock has a counter above the configured maximum
+    CounterAboveMax
+}
+
+impl fmt::Display for EpochError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EpochError::CounterExhausted => f.write_str("counter exhausted, epoch must be advanced"),
+            EpochError::OutOfWindow => f.write_str("clock epochs are too far apart"),
+            EpochError::CounterAboveMax => f.write_str("counter above the configured maximum")
+        }
+    }
+}
+
+impl error::Error for EpochError {}
+
+#[derive(Clone, Debug)]
+/// Version vector with `u16` counters scoped to an epoch
+pub struct EpochClock<I> {
+    epoch: u16,
+    clock: VersionVec<I, u16>,
+    max: u16
+}
+
+impl<I> EpochClock<I> where I: Ord + Copy {
+    /// Creates an empty clock in epoch 0
+    pub fn new() -> EpochClock<I> {
+        EpochClock { epoch: 0, clock: VersionVec::new(), max: u16::MAX }
+    }
+
+    /// Wraps `clock` recorded during `epoch`
+    pub fn from_parts(epoch: u16, clock: VersionVec<I, u16>) -> EpochClock<I> {
+        EpochClock { epoch, clock, max: u16::MAX }
+    }
+
+    /// Limits counters to `max` instead of `u16::MAX`, for protocols
+    /// reserving high counter bits. Fails if the clock already has a
+    /// counter above `max`.
+    pub fn with_max_counter(mut self, max: u16) -> Result<EpochClock<I>, EpochError> {
+        self.max = max;
+        self.check_max(&self.clock)?;
+        Ok(self)
+    }
+
+    pub fn epoch(&self) -> u16 {
+        self.epoch
+    }
+
+    /// Highest counter value allowed
+    pub fn max_counter(&self) -> u16 {
+        self.max
+    }
+
+    /// Number of bumps left for `actor` in the current epoch
+    pub fn headroom(&self, actor: I) -> u16 {
+        self.max.saturating_sub(self.clock.get(&actor).unwrap_or(0))
+    }
+
+    /// Counters of the current epoch
+    pub fn clock(&self) -> &VersionVec<I, u16> {
+        &self.clock
+    }
+
+    /// Increments counter of `id`, returning the new value.
+    ///
+    /// Fails without modifying the clock if the counter is at maximum,
+    /// `u16::MAX` unless configured with `with_max_counter`.
+    pub fn bump_for(&mut self, id: I) -> Result<u16, EpochError> {
+        if self.clock.get(&id).unwrap_or(0) >= self.max {
+            return Err(EpochError::CounterExhausted)
+        }
+        Ok(self.clock.increment(id))
+    }
+
+    /// Like `bump_for`, but reports a successful bump to `observer`
+    pub fn bump_observed<O: ChangeObserver<I, u16>>(&mut self, id: I, observer: &mut O) -> Result<u16, EpochError> {
+        let from = self.clock.get(&id);
+        let to = self.bump_for(id)?;
+        observer.on_change(match from {
+            Some(from) => ChangeEvent::Advanced { actor: id, from, to },
+            None => ChangeEvent::Added { actor: id, counter: to }
+        });
+        Ok(to)
+    }
+
+    /// Starts the next epoch with all counters reset.
+    ///
+    /// Safe only once every event of the current epoch has been seen by
+    /// all peers, as the new clock supersedes them unconditionally.
+    pub fn advance_epoch(&mut self) {
+        self.epoch = self.epoch.wrapping_add(1);
+        self.clock = VersionVec::new();
+    }
+
+    /// Compares clocks, clocks of newer epochs being greater
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &EpochClock<I>) -> Result<Ordering, EpochError> {
+        match epoch_distance(self.epoch, other.epoch)? {
+            0 => Ok(self.clock.cmp(&other.clock)),
+            d if d < 0 => Ok(Ordering::Greater),
+            _ => Ok(Ordering::Less)
+        }
+    }
+
+    /// Merges `other` in. A clock of a newer epoch replaces this one,
+    /// one of an older epoch is ignored. Fails without modifying the
+    /// clock if `other` has counters above the maximum of this one.
+    pub fn merge(&mut self, other: &EpochClock<I>) -> Result<(), EpochError> {
+        let distance = epoch_distance(self.epoch, other.epoch)?;
+        if distance >= 0 {
+            self.check_max(&other.clock)?;
+        }
+        match distance {
+            0 => self.clock.merge(&other.clock),
+            d if d > 0 => {
+                self.epoch = other.epoch;
+                self.clock = other.clock.clone();
+            },
+            _ => ()
+        }
+        Ok(())
+    }
+
+    fn check_max(&self, clock: &VersionVec<I, u16>) -> Result<(), EpochError> {
+        if clock.inner.iter().any(|e| e.1 > self.max) {
+            return Err(EpochError::CounterAboveMax)
+        }
+        Ok(())
+    }
+}
+
+impl<I> Default for EpochClock<I> where I: Ord + Copy {
+    fn default() -> EpochClock<I> {
+        EpochClock::new()
+    }
+}
+
+/// How far `to` is ahead of `from`, negative if behind
+fn epoch_distance(from: u16, to: u16) -> Result<i32, EpochError> {
+    let ahead = to.wrapping_sub(from);
+    let behind = from.wrapping_sub(to);
+    if ahead <= EPOCH_WINDOW {
+        Ok(ahead as i32)
+    } else if behind <= EPOCH_WINDOW {
+        Ok(-(behind as i32))
+    } else {
+        Err(EpochError::OutOfWindow)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EpochClock, EpochError, EPOCH_WINDOW};
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn exhaustion_and_rollover() {
+        let mut clock = EpochClock::from_parts(0, VersionVec::from_vec(vec![(1u32, u16::MAX - 1)]));
+        assert_eq!(clock.bump_for(1), Ok(u16::MAX));
+        assert_eq!(clock.bump_for(1), Err(EpochError::CounterExhausted));
+        assert_eq!(clock.clock().get(&1), Some(u16::MAX));
+
+        let old = clock.clone();
+        clock.advance_epoch();
+        assert_eq!(clock.bump_for(1), Ok(1));
+        assert_eq!(clock.cmp(&old), Ok(Ordering::Greater));
+        assert_eq!(old.cmp(&clock), Ok(Ordering::Less));
+
+        let mut lagging = old.clone();
+        lagging.merge(&clock).unwrap();
+        assert_eq!(lagging.epoch(), 1);
+        assert_eq!(lagging.cmp(&clock), Ok(Ordering::Equal));
+
+        let mut reserved = EpochClock::new().with_max_counter(1).unwrap();
+        assert_eq!(reserved.bump_for(1u32), Ok(1));
+        assert_eq!(reserved.bump_for(1), Err(EpochError::CounterExhausted));
+        reserved.advance_epoch();
+        assert_eq!(reserved.bump_for(1), Ok(1));
+
+        let mut zero = EpochClock::new().with_max_counter(0).unwrap();
+        assert_eq!(zero.bump_for(1u32), Err(EpochError::CounterExhausted));
+        assert!(zero.clock().as_ref().is_empty());
+    }
+
+    #[test]
+    fn counters_above_max() {
+        let big = EpochClock::from_parts(0, VersionVec::from_vec(vec![(1u32, 20)]));
+        assert_eq!(big.clone().with_max_counter(10).err(), Some(EpochError::CounterAboveMax));
+        assert!(big.clone().with_max_counter(20).is_ok());
+
+        let mut limited = EpochClock::from_parts(0, VersionVec::from_vec(vec![(2, 3)])).with_max_counter(10).unwrap();
+        assert_eq!(limited.merge(&big), Err(EpochError::CounterAboveMax));
+        let mut newer = big.clone();
+        newer.advance_epoch();
+        newer.merge(&big).unwrap();
+        assert_eq!(limited.merge(&newer), Ok(()));
+        assert_eq!((limited.epoch(), limited.clock().as_ref()), (1, &[][..]));
+
+        // older epochs are ignored anyway
+        assert_eq!(limited.merge(&big), Ok(()));
+    }
+
+    #[test]
+    fn epoch_window() {
+        let a: EpochClock<u32> = EpochClock::from_parts(u16::MAX, VersionVec::singleton(1));
+        let b = EpochClock::from_parts(2, VersionVec::singleton(2));
+        assert_eq!(a.cmp(&b), Ok(Ordering::Less));
+
+        let mut same = EpochClock::from_parts(2, VersionVec::singleton(3));
+        assert_eq!(same.cmp(&b), Ok(Ordering::Concurrent));
+        same.merge(&a).unwrap();
+        same.merge(&b).unwrap();
+        assert_eq!(same.cmp(&b), Ok(Ordering::Greater));
+
+        let mut far = EpochClock::from_parts(2 + EPOCH_WINDOW + 1, VersionVec::new());
+        assert_eq!(far.cmp(&b), Err(EpochError::OutOfWindow));
+        assert_eq!(far.merge(&b), Err(EpochError::OutOfWindow));
+    }
+}
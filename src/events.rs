@@ -0,0 +1,99 @@
+//! Notifications about clock movement.
+
+use alloc::collections::vec_deque::{self, VecDeque};
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// A single change of a version vector entry
+pub enum ChangeEvent<I, T> {
+    /// A new entry for `actor` was added
+    Added { actor: I, counter: T },
+    /// Existing entry for `actor` advanced
+    Advanced { actor: I, from: T, to: T }
+}
+
+impl<I: Copy, T: Copy> ChangeEvent<I, T> {
+    /// Actor whose entry changed
+    pub fn actor(&self) -> I {
+        match *self {
+            ChangeEvent::Added { actor, .. } | ChangeEvent::Advanced { actor, .. } => actor
+        }
+    }
+
+    /// Counter value after the change
+    pub fn counter(&self) -> T {
+        match *self {
+            ChangeEvent::Added { counter, .. } => counter,
+            ChangeEvent::Advanced { to, .. } => to
+        }
+    }
+}
+
+/// Receives events from `VersionVec::bump_observed`/`merge_observed`.
+///
+/// Implemented for closures `FnMut(ChangeEvent<I, T>)`.
+pub trait ChangeObserver<I, T> {
+    fn on_change(&mut self, event: ChangeEvent<I, T>);
+}
+
+impl<I, T, F> ChangeObserver<I, T> for F where F: FnMut(ChangeEvent<I, T>) {
+    fn on_change(&mut self, event: ChangeEvent<I, T>) {
+        self(event)
+    }
+}
+
+/// Called by `VersionVec::merge_with_hook` for every actor whose local
+/// and remote counters differ, missing entries counting as zero.
+///
+/// Implemented for closures `FnMut(I, T, T)` taking actor, local and
+/// remote counter.
+pub trait MergeHook<I, T> {
+    fn on_divergence(&mut self, actor: I, local: T, remote: T);
+}
+
+impl<I, T, F> MergeHook<I, T> for F where F: FnMut(I, T, T) {
+    fn on_divergence(&mut self, actor: I, local: T, remote: T) {
+        self(actor, local, remote)
+    }
+}
+
+/// Observer which queues events until they're drained, for consumers
+/// which process changes out of band
+pub struct ChangeQueue<I, T> {
+    events: VecDeque<ChangeEvent<I, T>>
+}
+
+impl<I, T> ChangeQueue<I, T> {
+    /// Creates an empty queue
+    pub fn new() -> ChangeQueue<I, T> {
+        ChangeQueue {
+            events: VecDeque::new()
+        }
+    }
+
+    /// Number of queued events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if no events are queued
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Removes and returns queued events in order they happened
+    pub fn drain(&mut self) -> vec_deque::Drain<'_, ChangeEvent<I, T>> {
+        self.events.drain(..)
+    }
+}
+
+impl<I, T> Default for ChangeQueue<I, T> {
+    fn default() -> ChangeQueue<I, T> {
+        ChangeQueue::new()
+    }
+}
+
+impl<I, T> ChangeObserver<I, T> for ChangeQueue<I, T> {
+    fn on_change(&mut self, event: ChangeEvent<I, T>) {
+        self.events.push_back(event)
+    }
+}
@@ -0,0 +1,136 @@
+//! Human readable breakdown of a comparison.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp;
+use core::fmt::{self, Write};
+
+use num_traits::Num;
+
+use {Ordering, VersionVec};
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Counters of one actor in both compared clocks
+pub struct Row<I, T> {
+    pub actor: I,
+    pub a: T,
+    pub b: T
+}
+
+impl<I, T: Ord> Row<I, T> {
+    /// `"a"` or `"b"` for the clock which is ahead, empty if equal
+    pub fn ahead(&self) -> &'static str {
+        match self.a.cmp(&self.b) {
+            cmp::Ordering::Greater => "a",
+            cmp::Ordering::Less => "b",
+            cmp::Ordering::Equal => ""
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Result of `explain_cmp`, `Display` renders it as a table
+pub struct Explanation<I, T> {
+    pub ordering: Ordering,
+    /// One row per actor known to either clock, in id order
+    pub rows: Vec<Row<I, T>>
+}
+
+/// Compares `a` with `b`, keeping per-actor details of the comparison
+pub fn explain_cmp<I, T>(a: &VersionVec<I, T>, b: &VersionVec<I, T>) -> Explanation<I, T>
+    where I: Ord + Copy, T: Ord + Copy + Num {
+    Explanation {
+        ordering: a.cmp(b),
+        rows: a.merge_join_iter(b).map(|e| {
+            let (a, b) = e.counters_or(T::zero());
+            Row { actor: e.id(), a, b }
+        }).collect()
+    }
+}
+
+impl<I: fmt::Display, T: fmt::Display + Ord> Explanation<I, T> {
+    /// Renders as JSON: `{"ordering": "<", "actors": [{"actor": "1",
+    /// "a": 1, "b": 2, "ahead": "b"}, ...]}`. Actors are strings,
+    /// counters are written as they are displayed.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push_str("{\"ordering\": ");
+        push_json_str(&mut out, self.ordering.symbol());
+        out.push_str(", \"actors\": [");
+        for (idx, row) in self.rows.iter().enumerate() {
+            if idx > 0 {
+                out.push_str(", ");
+            }
+            out.push_str("{\"actor\": ");
+            push_json_str(&mut out, &row.actor.to_string());
+            let _ = write!(out, ", \"a\": {}, \"b\": {}, \"ahead\": ", row.a, row.b);
+            push_json_str(&mut out, row.ahead());
+            out.push('}');
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+fn push_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            },
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+}
+
+/// Renders a table with a row per actor, followed by the overall result
+impl<I: fmt::Display, T: fmt::Display + Ord> fmt::Display for Explanation<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cells: Vec<[String; 3]> = self.rows.iter()
+            .map(|row| [row.actor.to_string(), row.a.to_string(), row.b.to_string()])
+            .collect();
+        let mut widths = [5, 1, 1];
+        for row in &cells {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = cmp::max(*width, cell.chars().count());
+            }
+        }
+
+        writeln!(f, "{:<w0$}  {:>w1$}  {:>w2$}  ahead", "actor", "a", "b", w0 = widths[0], w1 = widths[1], w2 = widths[2])?;
+        for (row, cell) in self.rows.iter().zip(cells.iter()) {
+            let line = format!("{:<w0$}  {:>w1$}  {:>w2$}  {}", cell[0], cell[1], cell[2], row.ahead(),
+                               w0 = widths[0], w1 = widths[1], w2 = widths[2]);
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        write!(f, "a {} b", self.ordering)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::explain_cmp;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn explains() {
+        let a: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 3), (2, 1), (10, 2)]);
+        let b = VersionVec::from_vec(vec![(1, 3), (2, 12)]);
+        let explanation = explain_cmp(&a, &b);
+        assert_eq!(explanation.ordering, Ordering::Concurrent);
+        assert_eq!(explanation.rows.len(), 3);
+
+        assert_eq!(format!("{}", explanation), "\
+actor  a   b  ahead
+1      3   3
+2      1  12  b
+10     2   0  a
+a || b");
+
+        assert_eq!(explain_cmp(&b, &b).to_json(),
+                   r#"{"ordering": "=", "actors": [{"actor": "1", "a": 3, "b": 3, "ahead": ""}, {"actor": "2", "a": 12, "b": 12, "ahead": ""}]}"#);
+    }
+}
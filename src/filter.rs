@@ -0,0 +1,101 @@
+//! Bulk dominance filters over collections of clocks.
+//!
+//! Typical use is trimming a queue of pending writes after receiving an
+//! ack vector: every write whose clock is dominated by the ack is done.
+//! Filters use `VersionVec::descends`, which bails out on the first
+//! entry proving non-dominance rather than computing full `cmp`.
+
+use alloc::vec::Vec;
+
+use num_traits::Num;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use VersionVec;
+
+/// Keeps only clocks `pivot` doesn't descend, i.e. ones which are
+/// greater than or concurrent with it
+pub fn retain_not_dominated_by<I, T>(clocks: &mut Vec<VersionVec<I, T>>, pivot: &VersionVec<I, T>)
+    where I: Ord + Copy, T: Ord + Copy + Num {
+    clocks.retain(|c| !pivot.descends(c))
+}
+
+/// Keeps only clocks `pivot` descends, i.e. ones which are less than or
+/// equal to it
+pub fn retain_dominated_by<I, T>(clocks: &mut Vec<VersionVec<I, T>>, pivot: &VersionVec<I, T>)
+    where I: Ord + Copy, T: Ord + Copy + Num {
+    clocks.retain(|c| pivot.descends(c))
+}
+
+/// Parallel version of `retain_not_dominated_by`, worth it for large
+/// collections of large clocks
+#[cfg(feature = "rayon")]
+pub fn par_retain_not_dominated_by<I, T>(clocks: &mut Vec<VersionVec<I, T>>, pivot: &VersionVec<I, T>)
+    where I: Ord + Copy + Send + Sync, T: Ord + Copy + Num + Send + Sync {
+    par_retain(clocks, |c| !pivot.descends(c))
+}
+
+/// Parallel version of `retain_dominated_by`
+#[cfg(feature = "rayon")]
+pub fn par_retain_dominated_by<I, T>(clocks: &mut Vec<VersionVec<I, T>>, pivot: &VersionVec<I, T>)
+    where I: Ord + Copy + Send + Sync, T: Ord + Copy + Num + Send + Sync {
+    par_retain(clocks, |c| pivot.descends(c))
+}
+
+#[cfg(feature = "rayon")]
+fn par_retain<I, T, F>(clocks: &mut Vec<VersionVec<I, T>>, keep: F)
+    where I: Send + Sync, T: Send + Sync, F: Fn(&VersionVec<I, T>) -> bool + Send + Sync {
+    let mask: Vec<bool> = clocks.par_iter().map(keep).collect();
+    let mut mask = mask.into_iter();
+    clocks.retain(|_| mask.next().unwrap_or(false));
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use VersionVec;
+
+    fn pending() -> Vec<VersionVec<u32, u32>> {
+        vec![
+            VersionVec::from_vec(vec![(1, 1)]),
+            VersionVec::from_vec(vec![(1, 2), (2, 1)]),
+            VersionVec::from_vec(vec![(1, 3)]),
+            VersionVec::from_vec(vec![(2, 2), (3, 0)]),
+            VersionVec::new(),
+        ]
+    }
+
+    fn counters(clocks: &[VersionVec<u32, u32>]) -> Vec<Vec<(u32, u32)>> {
+        clocks.iter().map(|c| c.as_ref().to_vec()).collect()
+    }
+
+    #[test]
+    fn not_dominated() {
+        let ack = VersionVec::from_vec(vec![(1, 2), (2, 1)]);
+        let mut clocks = pending();
+        super::retain_not_dominated_by(&mut clocks, &ack);
+        assert_eq!(counters(&clocks), [vec![(1, 3)], vec![(2, 2), (3, 0)]]);
+    }
+
+    #[test]
+    fn dominated() {
+        let ack = VersionVec::from_vec(vec![(1, 2), (2, 1)]);
+        let mut clocks = pending();
+        super::retain_dominated_by(&mut clocks, &ack);
+        assert_eq!(counters(&clocks), [vec![(1, 1)], vec![(1, 2), (2, 1)], vec![]]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let ack = VersionVec::from_vec(vec![(1, 2), (2, 1)]);
+
+        let mut clocks = pending();
+        super::par_retain_not_dominated_by(&mut clocks, &ack);
+        assert_eq!(counters(&clocks), [vec![(1, 3)], vec![(2, 2), (3, 0)]]);
+
+        let mut clocks = pending();
+        super::par_retain_dominated_by(&mut clocks, &ack);
+        assert_eq!(counters(&clocks), [vec![(1, 1)], vec![(1, 2), (2, 1)], vec![]]);
+    }
+}
@@ -0,0 +1,184 @@
+//! Small per-entry flag sets carried through merges.
+//!
+//! Markers like "actor retired" or "entry is approximate" belong to an
+//! entry and have to survive merges along with its counter.
+//! `FlaggedClock` keeps a `Flags` set per entry and combines them on
+//! merge according to a `FlagMerge` policy.
+
+use alloc::collections::BTreeMap;
+use core::cmp;
+use core::ops::BitOr;
+
+use num_traits::Num;
+
+use VersionVec;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq, Hash, Default)]
+/// Bit set of entry flags. Low bits are defined below, the rest is
+/// free for applications.
+pub struct Flags(pub u8);
+
+impl Flags {
+    pub const NONE: Flags = Flags(0);
+    /// Actor is retired and won't issue more events
+    pub const TOMBSTONED: Flags = Flags(1);
+    /// Counter is an approximation, e.g. after pruning
+    pub const APPROXIMATE: Flags = Flags(1 << 1);
+
+    pub fn contains(&self, other: Flags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Flags) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Flags) {
+        self.0 &= !other.0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Flags;
+
+    fn bitor(self, other: Flags) -> Flags {
+        Flags(self.0 | other.0)
+    }
+}
+
+/// Combines flags of an entry present on both sides of a merge. Gets
+/// counters and flags of the local and the remote entry, missing
+/// entries having zero counters and no flags.
+pub trait FlagMerge<T> {
+    fn merge_flags(&mut self, local: (T, Flags), remote: (T, Flags)) -> Flags;
+}
+
+/// Keeps every flag set on either side
+pub struct Union;
+
+impl<T> FlagMerge<T> for Union {
+    fn merge_flags(&mut self, local: (T, Flags), remote: (T, Flags)) -> Flags {
+        local.1 | remote.1
+    }
+}
+
+/// Takes flags of the side with the greater counter, uniting them if
+/// counters are equal
+pub struct FollowNewer;
+
+impl<T: Ord> FlagMerge<T> for FollowNewer {
+    fn merge_flags(&mut self, local: (T, Flags), remote: (T, Flags)) -> Flags {
+        match local.0.cmp(&remote.0) {
+            cmp::Ordering::Greater => local.1,
+            cmp::Ordering::Less => remote.1,
+            cmp::Ordering::Equal => local.1 | remote.1
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Version vector with flags per entry
+pub struct FlaggedClock<I, T> {
+    clock: VersionVec<I, T>,
+    flags: BTreeMap<I, Flags>
+}
+
+impl<I, T> FlaggedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> FlaggedClock<I, T> {
+        FlaggedClock { clock: VersionVec::new(), flags: BTreeMap::new() }
+    }
+
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    /// Flags of entry `id`, empty if there are none
+    pub fn flags(&self, id: I) -> Flags {
+        self.flags.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Replaces flags of entry `id`. Flags of an actor without an entry
+    /// create a zero entry for it.
+    pub fn set_flags(&mut self, id: I, flags: Flags) {
+        if self.clock.get(&id).is_none() {
+            self.clock.raise(id, T::zero());
+        }
+        if flags.is_empty() {
+            self.flags.remove(&id);
+        } else {
+            self.flags.insert(id, flags);
+        }
+    }
+
+    pub fn bump_for(&mut self, id: I) {
+        self.clock.bump_for(id);
+    }
+
+    /// Merges `other` in, uniting flags
+    pub fn merge(&mut self, other: &FlaggedClock<I, T>) {
+        self.merge_with(other, &mut Union)
+    }
+
+    /// Merges `other` in, combining flags of entries with `policy`
+    pub fn merge_with<P: FlagMerge<T>>(&mut self, other: &FlaggedClock<I, T>, policy: &mut P) {
+        for entry in self.clock.merge_join_iter(&other.clock) {
+            let id = entry.id();
+            let (local, remote) = entry.counters_or(T::zero());
+            let flags = policy.merge_flags((local, self.flags(id)), (remote, other.flags(id)));
+            if flags.is_empty() {
+                self.flags.remove(&id);
+            } else {
+                self.flags.insert(id, flags);
+            }
+        }
+        self.clock.merge(&other.clock);
+    }
+}
+
+impl<I, T> Default for FlaggedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> FlaggedClock<I, T> {
+        FlaggedClock::new()
+    }
+}
+
+impl<I, T> From<VersionVec<I, T>> for FlaggedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn from(clock: VersionVec<I, T>) -> FlaggedClock<I, T> {
+        FlaggedClock { clock, flags: BTreeMap::new() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FlaggedClock, Flags, FollowNewer};
+    use VersionVec;
+
+    #[test]
+    fn flags_survive_merge() {
+        let mut a = FlaggedClock::from(VersionVec::from_vec(vec![(1u32, 3u32), (2, 1)]));
+        a.set_flags(1, Flags::TOMBSTONED);
+        let mut b = FlaggedClock::from(VersionVec::from_vec(vec![(1, 4), (3, 1)]));
+        b.set_flags(3, Flags::APPROXIMATE);
+        b.set_flags(4, Flags(0x80));
+
+        let mut united = a.clone();
+        united.merge(&b);
+        assert_eq!(united.flags(1), Flags::TOMBSTONED);
+        assert_eq!(united.flags(3), Flags::APPROXIMATE);
+        assert!(united.flags(2).is_empty());
+        assert_eq!(united.clock().as_ref(), [(1, 4), (2, 1), (3, 1), (4, 0)]);
+
+        // remote entry of 1 is newer and isn't tombstoned
+        a.merge_with(&b, &mut FollowNewer);
+        assert!(!a.flags(1).contains(Flags::TOMBSTONED));
+        assert!(a.flags(3).contains(Flags::APPROXIMATE));
+        assert_eq!(a.flags(4), Flags(0x80));
+
+        let mut flags = Flags::TOMBSTONED | Flags::APPROXIMATE;
+        flags.remove(Flags::TOMBSTONED);
+        assert_eq!(flags, Flags::APPROXIMATE);
+    }
+}
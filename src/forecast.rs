@@ -0,0 +1,157 @@
+//! Forecasting counter overflow.
+//!
+//! `EpochClock` and `BoundedClock` fail once a counter is exhausted,
+//! which for a long-lived system is an outage rather than a surprise.
+//! `RotationPolicy` observes bumps (it is a `ChangeObserver`), estimates
+//! how fast every actor's counter grows and tells when the remaining
+//! headroom will run out within a configured lead time, so the epoch
+//! can be rotated while there is still time to do it safely.
+
+use alloc::collections::BTreeMap;
+use core::convert::TryFrom;
+
+use num_traits::{Bounded, Num, ToPrimitive};
+
+use epoch::EpochClock;
+use events::{ChangeEvent, ChangeObserver};
+use time::TimeSource;
+use VersionVec;
+
+impl<I, T> VersionVec<I, T> where I: Ord + Copy, T: Ord + Copy + Num + Bounded {
+    /// Number of bumps left for `actor` before its counter overflows
+    pub fn headroom(&self, actor: I) -> T {
+        T::max_value() - self.get(&actor).unwrap_or_else(T::zero)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Rate<T> {
+    since: u64,
+    from: T,
+    at: u64,
+    to: T
+}
+
+/// Recommends epoch rotation before counters run out.
+///
+/// Rates are averaged from the first observed change of an actor, times
+/// come from `S` and are in its units, as is `lead_time`.
+pub struct RotationPolicy<I, T, S> {
+    source: S,
+    lead_time: u64,
+    rates: BTreeMap<I, Rate<T>>
+}
+
+impl<I, T, S> RotationPolicy<I, T, S> where I: Ord + Copy, T: Ord + Copy + Num + ToPrimitive, S: TimeSource {
+    /// Creates a policy asking for rotation once some counter is
+    /// forecast to overflow within `lead_time`
+    pub fn new(lead_time: u64, source: S) -> RotationPolicy<I, T, S> {
+        RotationPolicy { source, lead_time, rates: BTreeMap::new() }
+    }
+
+    /// Forecast time until `actor` consumes `headroom` more counters,
+    /// `None` if its rate isn't known yet
+    pub fn time_to_exhaustion(&self, actor: I, headroom: T) -> Option<u64> {
+        let rate = self.rates.get(&actor)?;
+        let advanced = (rate.to - rate.from).to_u128()?;
+        if advanced == 0 || rate.at == rate.since {
+            return None
+        }
+        let left = headroom.to_u128()? * u128::from(rate.at - rate.since) / advanced;
+        Some(u64::try_from(left).unwrap_or(u64::MAX))
+    }
+
+    /// True if some counter of `clock` is forecast to overflow within
+    /// the lead time
+    pub fn should_rotate(&self, clock: &VersionVec<I, T>) -> bool where T: Bounded {
+        self.due(|actor| clock.headroom(actor))
+    }
+
+    /// Forgets observed rates, e.g. after counters were reset
+    pub fn reset(&mut self) {
+        self.rates.clear();
+    }
+
+    fn due<F: Fn(I) -> T>(&self, headroom: F) -> bool {
+        self.rates.keys().any(|&actor| {
+            self.time_to_exhaustion(actor, headroom(actor)).is_some_and(|left| left <= self.lead_time)
+        })
+    }
+}
+
+impl<I, S> RotationPolicy<I, u16, S> where I: Ord + Copy, S: TimeSource {
+    /// Advances epoch of `clock` if rotation is due, returns whether it
+    /// did. Rotation is only safe once every event of the current epoch
+    /// has been replicated, see `EpochClock::advance_epoch`.
+    pub fn rotate_if_due(&mut self, clock: &mut EpochClock<I>) -> bool {
+        if !self.due(|actor| clock.headroom(actor)) {
+            return false
+        }
+        clock.advance_epoch();
+        self.reset();
+        true
+    }
+}
+
+impl<I, T, S> ChangeObserver<I, T> for RotationPolicy<I, T, S> where I: Ord + Copy, T: Copy + Num, S: TimeSource {
+    fn on_change(&mut self, event: ChangeEvent<I, T>) {
+        let now = self.source.now();
+        let (from, to) = match event {
+            ChangeEvent::Added { counter, .. } => (T::zero(), counter),
+            ChangeEvent::Advanced { from, to, .. } => (from, to)
+        };
+        let rate = self.rates.entry(event.actor()).or_insert(Rate { since: now, from, at: now, to });
+        rate.at = now;
+        rate.to = to;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RotationPolicy;
+    use epoch::EpochClock;
+    use time::{TimeSource, VirtualTime};
+    use VersionVec;
+
+    #[test]
+    fn headroom() {
+        let clock = VersionVec::from_vec(vec![(1u32, 250u8)]);
+        assert_eq!(clock.headroom(1), 5);
+        assert_eq!(clock.headroom(2), 255);
+
+        let epoch = EpochClock::from_parts(0, VersionVec::from_dot(1u32, 90)).with_max_counter(100).unwrap();
+        assert_eq!(epoch.headroom(1), 10);
+    }
+
+    #[test]
+    fn forecasts_and_rotates() {
+        let time = VirtualTime::new(1000);
+        let mut policy = RotationPolicy::new(50, || time.now());
+        let mut clock = VersionVec::from_dot(1u32, u32::MAX - 100);
+        assert!(!policy.should_rotate(&clock));
+
+        for _ in 0..10 {
+            clock.bump_observed(1, &mut policy);
+            time.advance(2);
+        }
+        // 10 bumps over 18 time units, 90 left
+        assert_eq!(policy.time_to_exhaustion(1, clock.headroom(1)), Some(162));
+        assert!(!policy.should_rotate(&clock));
+        for _ in 0..70 {
+            clock.bump_observed(1, &mut policy);
+            time.advance(2);
+        }
+        assert!(policy.should_rotate(&clock));
+
+        let mut policy = RotationPolicy::new(2, || time.now());
+        let mut epoch = EpochClock::new().with_max_counter(20).unwrap();
+        for _ in 0..16 {
+            epoch.bump_observed(7u32, &mut policy).unwrap();
+            assert!(!policy.rotate_if_due(&mut epoch));
+            time.advance(1);
+        }
+        epoch.bump_observed(7, &mut policy).unwrap();
+        assert!(policy.rotate_if_due(&mut epoch));
+        assert_eq!((epoch.epoch(), epoch.headroom(7)), (1, 20));
+    }
+}
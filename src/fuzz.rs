@@ -0,0 +1,77 @@
+//! `Arbitrary` implementations for structure-aware fuzzing.
+//!
+//! Generated clocks are always valid: entries are sorted by id and ids
+//! are unique.
+//!
+//! Enabled by the `arbitrary` feature.
+
+use alloc::vec::Vec;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use num_traits::Num;
+
+use events::ChangeEvent;
+use journal::MutationRecord;
+use {Dot, VersionVec};
+
+impl<'a, I, T> Arbitrary<'a> for VersionVec<I, T>
+    where I: Arbitrary<'a> + Ord + Copy, T: Arbitrary<'a> + Ord + Copy + Num {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<VersionVec<I, T>> {
+        let mut inner: Vec<(I, T)> = u.arbitrary()?;
+        inner.sort_by_key(|e| e.0);
+        inner.dedup_by_key(|e| e.0);
+        Ok(VersionVec { inner })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <Vec<(I, T)> as Arbitrary>::size_hint(depth)
+    }
+}
+
+impl<'a, I: Arbitrary<'a>, T: Arbitrary<'a>> Arbitrary<'a> for Dot<I, T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Dot<I, T>> {
+        Ok(Dot::new(u.arbitrary()?, u.arbitrary()?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <(I, T) as Arbitrary>::size_hint(depth)
+    }
+}
+
+impl<'a, I: Arbitrary<'a>, T: Arbitrary<'a>> Arbitrary<'a> for MutationRecord<I, T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<MutationRecord<I, T>> {
+        Ok(match u.int_in_range(0..=2u8)? {
+            0 => MutationRecord::Bump(u.arbitrary()?),
+            1 => MutationRecord::Witness(u.arbitrary()?),
+            _ => MutationRecord::MergeEntry(u.arbitrary()?, u.arbitrary()?)
+        })
+    }
+}
+
+impl<'a, I: Arbitrary<'a>, T: Arbitrary<'a>> Arbitrary<'a> for ChangeEvent<I, T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<ChangeEvent<I, T>> {
+        Ok(if u.arbitrary()? {
+            ChangeEvent::Added { actor: u.arbitrary()?, counter: u.arbitrary()? }
+        } else {
+            ChangeEvent::Advanced { actor: u.arbitrary()?, from: u.arbitrary()?, to: u.arbitrary()? }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use arbitrary::Unstructured;
+    use VersionVec;
+
+    #[test]
+    fn generates_valid_clocks() {
+        let bytes: Vec<u8> = (0..=255u8).cycle().take(4096).map(|b| b.wrapping_mul(37)).collect();
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..16 {
+            let clock: VersionVec<u8, u16> = u.arbitrary().unwrap();
+            assert!(clock.as_ref().windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+}
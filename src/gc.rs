@@ -0,0 +1,208 @@
+//! Distributed garbage collection driven by stability watermarks.
+//!
+//! Tombstones and logged operations can be dropped once every peer has
+//! seen them. `GcCoordinator` keeps a matrix clock: for each peer the
+//! latest clock it has advertised, e.g. its `NodeClock` contiguous bases.
+//! Their greatest lower bound is the stable vector, and anything it
+//! covers is safe to collect.
+//!
+//! Membership is fixed on creation. A peer which hasn't reported yet
+//! holds the stable vector at zero, collection must wait for it.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use node::{DotKeyMap, NodeClock};
+use {Dot, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Matrix clock over a fixed set of peers
+pub struct GcCoordinator<I, T> {
+    rows: BTreeMap<I, VersionVec<I, T>>
+}
+
+impl<I, T> GcCoordinator<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates a coordinator for `peers`, which should include the local
+    /// node
+    pub fn new<P: IntoIterator<Item = I>>(peers: P) -> GcCoordinator<I, T> {
+        GcCoordinator { rows: peers.into_iter().map(|peer| (peer, VersionVec::new())).collect() }
+    }
+
+    /// Records that `peer` has seen `clock`. Returns false for peers
+    /// which aren't members.
+    pub fn observe(&mut self, peer: I, clock: &VersionVec<I, T>) -> bool {
+        match self.rows.get_mut(&peer) {
+            Some(row) => {
+                row.merge(clock);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Records node clock advertised by `peer`, only its gapless prefix
+    /// counts as seen
+    pub fn observe_node(&mut self, peer: I, clock: &NodeClock<I, T>) -> bool {
+        self.observe(peer, &clock.to_version_vec())
+    }
+
+    /// What `peer` is known to have seen
+    pub fn row(&self, peer: I) -> Option<&VersionVec<I, T>> {
+        self.rows.get(&peer)
+    }
+
+    /// Vector every peer has seen
+    pub fn stable(&self) -> VersionVec<I, T> {
+        let mut rows = self.rows.values();
+        match rows.next() {
+            Some(first) => rows.fold(first.clone(), |stable, row| stable.glb(row)),
+            None => VersionVec::new()
+        }
+    }
+
+    /// True if every peer has seen `dot`
+    pub fn is_stable(&self, dot: Dot<I, T>) -> bool {
+        self.rows.values().all(|row| row.get(&dot.actor).is_some_and(|counter| counter >= dot.counter))
+    }
+
+    /// Number of peers which have seen `dot`
+    pub fn seen_by(&self, dot: Dot<I, T>) -> usize {
+        self.rows.values().filter(|row| row.get(&dot.actor).is_some_and(|counter| counter >= dot.counter)).count()
+    }
+
+    /// Picks dots of tombstones or operations which can be collected
+    pub fn prune_decisions<D: IntoIterator<Item = Dot<I, T>>>(&self, dots: D) -> Vec<Dot<I, T>> {
+        let stable = self.stable();
+        dots.into_iter()
+            .filter(|dot| stable.get(&dot.actor).is_some_and(|counter| counter >= dot.counter))
+            .collect()
+    }
+
+    /// Drops stable dots from `keys`
+    pub fn prune_dot_keys<K>(&self, keys: &mut DotKeyMap<K, I, T>) {
+        keys.prune(&self.stable());
+    }
+}
+
+#[cfg(feature = "codecs")]
+mod encoding {
+    use alloc::collections::BTreeMap;
+    use alloc::vec::Vec;
+
+    use num_traits::{FromPrimitive, Num, ToPrimitive};
+
+    use codecs::canonical::{self, DecodeError};
+    use super::GcCoordinator;
+
+    const VERSION: u8 = 1;
+
+    impl<I, T> GcCoordinator<I, T> where I: Ord + Copy + ToPrimitive, T: Ord + Copy + Num + ToPrimitive {
+        /// Serializes state: version byte, `u32` row count, then for
+        /// every row `u64` peer, `u32` length and canonical clock, all
+        /// big-endian.
+        ///
+        /// # Panics
+        ///
+        /// If an id or counter can't be represented as `u64`.
+        pub fn to_bytes(&self) -> Vec<u8> {
+            let mut out = vec![VERSION];
+            out.extend_from_slice(&(self.rows.len() as u32).to_be_bytes());
+            for (peer, row) in &self.rows {
+                let clock = row.canonical_bytes();
+                out.extend_from_slice(&peer.to_u64().expect("id doesn't fit into u64").to_be_bytes());
+                out.extend_from_slice(&(clock.len() as u32).to_be_bytes());
+                out.extend_from_slice(&clock);
+            }
+            out
+        }
+    }
+
+    impl<I, T> GcCoordinator<I, T> where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+        /// Restores state serialized with `to_bytes`
+        pub fn from_bytes(bytes: &[u8]) -> Result<GcCoordinator<I, T>, DecodeError> {
+            let (&version, mut rest) = bytes.split_first().ok_or(DecodeError::Truncated)?;
+            if version != VERSION {
+                return Err(DecodeError::UnknownVersion(version))
+            }
+
+            let count = take(&mut rest, 4)?.iter().fold(0usize, |n, &b| n << 8 | b as usize);
+            let mut rows = BTreeMap::new();
+            for _ in 0..count {
+                let peer = take(&mut rest, 8)?.iter().fold(0u64, |n, &b| n << 8 | b as u64);
+                let len = take(&mut rest, 4)?.iter().fold(0usize, |n, &b| n << 8 | b as usize);
+                let clock = canonical::decode(take(&mut rest, len)?)?;
+                rows.insert(I::from_u64(peer).ok_or(DecodeError::Overflow)?, clock);
+            }
+
+            if !rest.is_empty() {
+                return Err(DecodeError::TrailingBytes)
+            }
+            Ok(GcCoordinator { rows })
+        }
+    }
+
+    fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], DecodeError> {
+        if input.len() < n {
+            return Err(DecodeError::Truncated)
+        }
+        let (head, rest) = input.split_at(n);
+        *input = rest;
+        Ok(head)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GcCoordinator;
+    use node::{DotKeyMap, NodeClock};
+    use {Dot, VersionVec};
+
+    #[test]
+    fn stable_vector() {
+        let mut gc: GcCoordinator<u32, u32> = GcCoordinator::new(vec![1, 2, 3]);
+        assert!(gc.observe(1, &VersionVec::from_vec(vec![(1, 5), (2, 3)])));
+        assert!(gc.observe(2, &VersionVec::from_vec(vec![(1, 4), (2, 3)])));
+        assert!(!gc.observe(4, &VersionVec::singleton(1)));
+        assert!(gc.stable().as_ref().is_empty());
+        assert!(!gc.is_stable(Dot::new(1, 1)));
+
+        let mut node = NodeClock::new();
+        for counter in [1, 2, 3, 5] {
+            node.add(Dot::new(1, counter));
+        }
+        node.add(Dot::new(2, 1));
+        gc.observe_node(3, &node);
+
+        assert_eq!(gc.stable().as_ref(), [(1, 3), (2, 1)]);
+        assert!(gc.is_stable(Dot::new(1, 3)) && !gc.is_stable(Dot::new(1, 4)));
+        assert_eq!(gc.seen_by(Dot::new(1, 4)), 2);
+        assert_eq!(gc.prune_decisions(vec![Dot::new(1, 4), Dot::new(2, 1), Dot::new(1, 2)]),
+                   vec![Dot::new(2, 1), Dot::new(1, 2)]);
+
+        let mut keys = DotKeyMap::new();
+        keys.insert(Dot::new(1, 3), "a");
+        keys.insert(Dot::new(1, 4), "b");
+        gc.prune_dot_keys(&mut keys);
+        assert_eq!(keys.len(), 1);
+    }
+
+    #[cfg(feature = "codecs")]
+    #[test]
+    fn round_trip() {
+        use codecs::DecodeError;
+
+        let mut gc: GcCoordinator<u32, u32> = GcCoordinator::new(vec![1, 2]);
+        gc.observe(1, &VersionVec::from_vec(vec![(1, 5), (2, 3)]));
+        let bytes = gc.to_bytes();
+
+        let restored: GcCoordinator<u32, u32> = GcCoordinator::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.row(1).unwrap().as_ref(), [(1, 5), (2, 3)]);
+        assert!(restored.row(2).unwrap().as_ref().is_empty());
+
+        assert_eq!(GcCoordinator::<u32, u32>::from_bytes(&bytes[..bytes.len() - 1]).err(), Some(DecodeError::Truncated));
+        assert_eq!(GcCoordinator::<u32, u32>::from_bytes(&[2]).err(), Some(DecodeError::UnknownVersion(2)));
+    }
+}
@@ -0,0 +1,247 @@
+//! Generating random clocks with common testing toolkits.
+//!
+//! `ClockStrategy` describes the clocks to generate: how many actors
+//! they have at most, which ids those actors are drawn from and how
+//! large counters get. Depending on enabled features it can be used as
+//!
+//! * `proptest` - the parameters of `VersionVec`'s `proptest::Arbitrary`
+//!   impl, or a strategy by itself via `ClockStrategy::proptest`,
+//! * `quickcheck` - `ClockStrategy::quickcheck` generates from a `Gen`,
+//!   `VersionVec` also implements `quickcheck::Arbitrary` with shrinking,
+//! * `rand` - a `Distribution` of version vectors.
+//!
+//! Generated clocks are always valid: entries are sorted by id and ids
+//! are unique. Ids and counters are converted with `FromPrimitive` and
+//! must fit into the target types.
+
+use alloc::vec::Vec;
+
+use num_traits::{FromPrimitive, Num};
+
+use VersionVec;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Parameters of generated clocks
+pub struct ClockStrategy {
+    max_actors: usize,
+    actor_ids: u64,
+    max_counter: u64
+}
+
+impl ClockStrategy {
+    /// Up to 8 actors with ids below 16 and counters up to 100
+    pub fn new() -> ClockStrategy {
+        ClockStrategy { max_actors: 8, actor_ids: 16, max_counter: 100 }
+    }
+
+    /// Generated clocks have at most `n` entries
+    pub fn max_actors(mut self, n: usize) -> ClockStrategy {
+        self.max_actors = n;
+        self
+    }
+
+    /// Actor ids are drawn from `0..n`, smaller domains make clocks
+    /// share more actors. `n` must be positive.
+    pub fn actor_ids(mut self, n: u64) -> ClockStrategy {
+        assert!(n > 0, "actor id domain can't be empty");
+        self.actor_ids = n;
+        self
+    }
+
+    /// Counters are drawn from `0..=max`
+    pub fn max_counter(mut self, max: u64) -> ClockStrategy {
+        self.max_counter = max;
+        self
+    }
+
+    /// Builds a clock from drawn `(id, counter)` pairs
+    fn build<I, T>(entries: Vec<(u64, u64)>) -> VersionVec<I, T>
+        where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+        let mut inner: Vec<(I, T)> = entries.into_iter()
+            .map(|(id, counter)| (I::from_u64(id).expect("actor id out of range"),
+                                  T::from_u64(counter).expect("counter out of range")))
+            .collect();
+        inner.sort_by_key(|e| e.0);
+        inner.dedup_by_key(|e| e.0);
+        VersionVec { inner }
+    }
+
+    /// Draws a clock, `below(n)` has to return a uniform value in `0..n`
+    fn draw<I, T, F>(&self, mut below: F) -> VersionVec<I, T>
+        where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive, F: FnMut(u64) -> u64 {
+        let len = below(self.max_actors as u64 + 1) as usize;
+        let entries = (0..len)
+            .map(|_| (below(self.actor_ids), below(self.max_counter.saturating_add(1))))
+            .collect();
+        ClockStrategy::build(entries)
+    }
+}
+
+impl Default for ClockStrategy {
+    fn default() -> ClockStrategy {
+        ClockStrategy::new()
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impls {
+    use alloc::vec::Vec;
+    use core::fmt;
+    use core::ops::{Range, RangeInclusive};
+
+    use num_traits::{FromPrimitive, Num};
+    use proptest::arbitrary::Arbitrary;
+    use proptest::collection::{self, VecStrategy};
+    use proptest::strategy::{Map, Strategy};
+
+    use super::ClockStrategy;
+    use VersionVec;
+
+    type Entries = VecStrategy<(Range<u64>, RangeInclusive<u64>)>;
+
+    /// Strategy generating version vectors, shrinks by dropping entries
+    /// and lowering counters
+    pub type VersionVecStrategy<I, T> = Map<Entries, fn(Vec<(u64, u64)>) -> VersionVec<I, T>>;
+
+    impl ClockStrategy {
+        pub fn proptest<I, T>(&self) -> VersionVecStrategy<I, T>
+            where I: Ord + Copy + FromPrimitive + fmt::Debug, T: Ord + Copy + Num + FromPrimitive + fmt::Debug {
+            let entry = (0..self.actor_ids, 0..=self.max_counter);
+            collection::vec(entry, 0..=self.max_actors).prop_map(ClockStrategy::build as fn(_) -> _)
+        }
+    }
+
+    impl<I, T> Arbitrary for VersionVec<I, T>
+        where I: Ord + Copy + FromPrimitive + fmt::Debug + 'static,
+              T: Ord + Copy + Num + FromPrimitive + fmt::Debug + 'static {
+        type Parameters = ClockStrategy;
+        type Strategy = VersionVecStrategy<I, T>;
+
+        fn arbitrary_with(params: ClockStrategy) -> VersionVecStrategy<I, T> {
+            params.proptest()
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub use self::proptest_impls::VersionVecStrategy;
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impls {
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+
+    use num_traits::{FromPrimitive, Num};
+    use quickcheck::{Arbitrary, Gen};
+
+    use super::ClockStrategy;
+    use VersionVec;
+
+    impl ClockStrategy {
+        pub fn quickcheck<I, T>(&self, g: &mut Gen) -> VersionVec<I, T>
+            where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+            self.draw(|n| u64::arbitrary(g) % n)
+        }
+    }
+
+    impl<I, T> Arbitrary for VersionVec<I, T>
+        where I: Arbitrary + Ord + Copy, T: Arbitrary + Ord + Copy + Num {
+        fn arbitrary(g: &mut Gen) -> VersionVec<I, T> {
+            let mut inner: Vec<(I, T)> = Arbitrary::arbitrary(g);
+            inner.sort_by_key(|e| e.0);
+            inner.dedup_by_key(|e| e.0);
+            VersionVec { inner }
+        }
+
+        fn shrink(&self) -> Box<dyn Iterator<Item = VersionVec<I, T>>> {
+            // shrunk ids may end up out of order or equal
+            Box::new(self.inner.shrink().map(|mut inner| {
+                inner.sort_by_key(|e| e.0);
+                inner.dedup_by_key(|e| e.0);
+                VersionVec { inner }
+            }))
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+mod rand_impls {
+    use num_traits::{FromPrimitive, Num};
+    use rand::distributions::Distribution;
+    use rand::Rng;
+
+    use super::ClockStrategy;
+    use VersionVec;
+
+    impl<I, T> Distribution<VersionVec<I, T>> for ClockStrategy
+        where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+        fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> VersionVec<I, T> {
+            self.draw(|n| rng.gen_range(0..n))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ClockStrategy;
+    use VersionVec;
+
+    fn valid(clock: &VersionVec<u8, u16>, strategy: &ClockStrategy) -> bool {
+        clock.as_ref().windows(2).all(|w| w[0].0 < w[1].0) &&
+            clock.as_ref().len() <= strategy.max_actors &&
+            clock.as_ref().iter().all(|e| (e.0 as u64) < strategy.actor_ids && e.1 as u64 <= strategy.max_counter)
+    }
+
+    #[test]
+    fn draws_within_parameters() {
+        let strategy = ClockStrategy::new().max_actors(4).actor_ids(6).max_counter(9);
+        let mut state = 7u64;
+        for _ in 0..64 {
+            let clock = strategy.draw(|n| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) % n
+            });
+            assert!(valid(&clock, &strategy));
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    #[test]
+    fn proptest_strategy() {
+        use proptest::strategy::{Strategy, ValueTree};
+        use proptest::test_runner::TestRunner;
+
+        let strategy = ClockStrategy::new().max_actors(3).max_counter(5);
+        let mut runner = TestRunner::deterministic();
+        for _ in 0..32 {
+            assert!(valid(&strategy.proptest().new_tree(&mut runner).unwrap().current(), &strategy));
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    #[test]
+    fn quickcheck_gen() {
+        use quickcheck::{Arbitrary, Gen};
+
+        let strategy = ClockStrategy::new().actor_ids(3);
+        let mut g = Gen::new(10);
+        for _ in 0..32 {
+            assert!(valid(&strategy.quickcheck(&mut g), &strategy));
+            let clock: VersionVec<u8, u16> = Arbitrary::arbitrary(&mut g);
+            assert!(clock.shrink().all(|c| c.as_ref().windows(2).all(|w| w[0].0 < w[1].0)));
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn rand_distribution() {
+        use rand::distributions::Distribution;
+        use rand::rngs::mock::StepRng;
+
+        let strategy = ClockStrategy::new().max_actors(5);
+        let mut rng = StepRng::new(3, 0x9e37_79b9_7f4a_7c15);
+        for _ in 0..32 {
+            assert!(valid(&strategy.sample(&mut rng), &strategy));
+        }
+    }
+}
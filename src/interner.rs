@@ -0,0 +1,178 @@
+//! Interning actor ids into small dense handles.
+//!
+//! Server-internal clocks don't need to carry full actor ids: once ids
+//! are interned, an entry can live at position `index` of a plain
+//! vector, see `storage::IndexedClock`. Released indices are reused for
+//! new actors with a bumped generation, so stale handles of a released
+//! actor never match its successor. An index whose generation reaches
+//! `u32::MAX` is retired rather than wrapped around.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use num_traits::Num;
+
+use storage::{IndexedClock, Storage};
+use VersionVec;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq, Hash, PartialOrd, Ord)]
+/// Dense handle of an interned actor
+pub struct ActorHandle {
+    index: u32,
+    generation: u32
+}
+
+impl ActorHandle {
+    pub(crate) fn new(index: u32, generation: u32) -> ActorHandle {
+        ActorHandle { index, generation }
+    }
+
+    /// Position of the actor in dense storage
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Generation of the index, starting from 1
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Two-way mapping between actor ids and handles
+pub struct ActorInterner<I> {
+    handles: BTreeMap<I, ActorHandle>,
+    /// Actor and current generation of every index
+    slots: Vec<(Option<I>, u32)>,
+    free: Vec<u32>
+}
+
+impl<I> ActorInterner<I> where I: Ord + Copy {
+    pub fn new() -> ActorInterner<I> {
+        ActorInterner { handles: BTreeMap::new(), slots: vec![], free: vec![] }
+    }
+
+    /// Returns handle of `id`, interning it if needed
+    ///
+    /// # Panics
+    ///
+    /// If more than `u32::MAX` actors are interned at once.
+    pub fn intern(&mut self, id: I) -> ActorHandle {
+        if let Some(&handle) = self.handles.get(&id) {
+            return handle
+        }
+
+        let handle = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.0 = Some(id);
+                ActorHandle::new(index, slot.1)
+            },
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("too many interned actors");
+                self.slots.push((Some(id), 1));
+                ActorHandle::new(index, 1)
+            }
+        };
+        self.handles.insert(id, handle);
+        handle
+    }
+
+    /// Handle of `id` if it is interned
+    pub fn handle(&self, id: &I) -> Option<ActorHandle> {
+        self.handles.get(id).cloned()
+    }
+
+    /// Actor of `handle`, `None` if it has been released
+    pub fn resolve(&self, handle: ActorHandle) -> Option<I> {
+        match self.slots.get(handle.index as usize) {
+            Some(&(id, generation)) if generation == handle.generation => id,
+            _ => None
+        }
+    }
+
+    /// Releases `id`, so its index can be reused. Entries other clocks
+    /// keep under the old handle are superseded by those of the next
+    /// actor at the same index.
+    pub fn release(&mut self, id: &I) -> Option<ActorHandle> {
+        let handle = self.handles.remove(id)?;
+        let slot = &mut self.slots[handle.index as usize];
+        *slot = (None, slot.1.saturating_add(1));
+        if slot.1 < u32::MAX {
+            self.free.push(handle.index);
+        }
+        Some(handle)
+    }
+
+    /// Number of interned actors
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Converts `clock` to dense storage, interning its actors
+    pub fn to_indexed<T: Ord + Copy + Num>(&mut self, clock: &VersionVec<I, T>) -> IndexedClock<T> {
+        let mut indexed = IndexedClock::new();
+        for &(id, counter) in &clock.inner {
+            indexed.set(self.intern(id), counter);
+        }
+        indexed
+    }
+
+    /// Converts dense storage back to a `VersionVec`, dropping entries
+    /// of released handles
+    pub fn resolve_clock<T: Ord + Copy + Num>(&self, clock: &IndexedClock<T>) -> VersionVec<I, T> {
+        VersionVec::from_vec(clock.iter().filter_map(|(h, counter)| self.resolve(h).map(|id| (id, counter))).collect())
+    }
+}
+
+impl<I> Default for ActorInterner<I> where I: Ord + Copy {
+    fn default() -> ActorInterner<I> {
+        ActorInterner::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ActorInterner;
+    use VersionVec;
+
+    #[test]
+    fn interns_and_reuses() {
+        let mut interner = ActorInterner::new();
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+        assert_eq!(interner.intern("a"), a);
+        assert_eq!((a.index(), b.index()), (0, 1));
+
+        assert_eq!(interner.release(&"a"), Some(a));
+        assert_eq!(interner.resolve(a), None);
+        let c = interner.intern("c");
+        assert_eq!((c.index(), c.generation()), (0, 2));
+        assert_eq!(interner.resolve(c), Some("c"));
+        assert_eq!(interner.len(), 2);
+
+        let clock = VersionVec::from_vec(vec![("b", 3u32), ("d", 1)]);
+        let indexed = interner.to_indexed(&clock);
+        assert_eq!(interner.resolve_clock(&indexed).as_ref(), clock.as_ref());
+    }
+
+    #[test]
+    fn retires_exhausted_indices() {
+        let mut interner = ActorInterner::new();
+        let a = interner.intern("a");
+        interner.slots[0].1 = u32::MAX - 2;
+        interner.release(&"a");
+        let b = interner.intern("b");
+        assert_eq!((b.index(), b.generation()), (0, u32::MAX - 1));
+
+        interner.release(&"b");
+        let c = interner.intern("c");
+        assert_eq!((c.index(), c.generation()), (1, 1));
+        assert_eq!((interner.resolve(a), interner.resolve(b)), (None, None));
+    }
+}
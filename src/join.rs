@@ -0,0 +1,114 @@
+//! Walking two clocks side by side in id order.
+
+use core::slice;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Entry present in the left clock, the right one or in both
+pub enum EitherOrBoth<A> {
+    Left(A),
+    Right(A),
+    Both(A, A)
+}
+
+impl<I: Clone, T: Copy> EitherOrBoth<(I, T)> {
+    /// Id of the entry
+    pub fn id(&self) -> I {
+        match *self {
+            EitherOrBoth::Left(ref e) | EitherOrBoth::Right(ref e) | EitherOrBoth::Both(ref e, _) => e.0.clone()
+        }
+    }
+
+    /// Counters of both sides, `zero` standing in for the missing one
+    pub fn counters_or(&self, zero: T) -> (T, T) {
+        match *self {
+            EitherOrBoth::Left(ref l) => (l.1, zero),
+            EitherOrBoth::Right(ref r) => (zero, r.1),
+            EitherOrBoth::Both(ref l, ref r) => (l.1, r.1)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Iterator returned by `VersionVec::merge_join_iter`
+pub struct MergeJoin<'a, I: 'a, T: 'a> {
+    left: slice::Iter<'a, (I, T)>,
+    right: slice::Iter<'a, (I, T)>,
+    pending_left: Option<&'a (I, T)>,
+    pending_right: Option<&'a (I, T)>
+}
+
+impl<'a, I: Ord + Clone, T: Copy> MergeJoin<'a, I, T> {
+    pub(crate) fn new(left: &'a [(I, T)], right: &'a [(I, T)]) -> MergeJoin<'a, I, T> {
+        let mut left = left.iter();
+        let mut right = right.iter();
+        MergeJoin {
+            pending_left: left.next(),
+            pending_right: right.next(),
+            left,
+            right
+        }
+    }
+}
+
+impl<'a, I: Ord + Clone, T: Copy> Iterator for MergeJoin<'a, I, T> {
+    type Item = EitherOrBoth<(I, T)>;
+
+    fn next(&mut self) -> Option<EitherOrBoth<(I, T)>> {
+        let item = match (self.pending_left, self.pending_right) {
+            (None, None) => return None,
+            (Some(l), None) => EitherOrBoth::Left(l.clone()),
+            (None, Some(r)) => EitherOrBoth::Right(r.clone()),
+            (Some(l), Some(r)) => {
+                if l.0 < r.0 {
+                    EitherOrBoth::Left(l.clone())
+                } else if r.0 < l.0 {
+                    EitherOrBoth::Right(r.clone())
+                } else {
+                    EitherOrBoth::Both(l.clone(), r.clone())
+                }
+            }
+        };
+
+        match item {
+            EitherOrBoth::Left(_) => self.pending_left = self.left.next(),
+            EitherOrBoth::Right(_) => self.pending_right = self.right.next(),
+            EitherOrBoth::Both(_, _) => {
+                self.pending_left = self.left.next();
+                self.pending_right = self.right.next();
+            }
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.left.len() + self.pending_left.is_some() as usize;
+        let right = self.right.len() + self.pending_right.is_some() as usize;
+        (core::cmp::max(left, right), Some(left + right))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use super::EitherOrBoth;
+    use VersionVec;
+
+    #[test]
+    fn joins_in_id_order() {
+        let a: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 1), (3, 2), (4, 1)]);
+        let b = VersionVec::from_vec(vec![(2, 5), (3, 1), (6, 1)]);
+
+        let joined: Vec<_> = a.merge_join_iter(&b).collect();
+        assert_eq!(joined, vec![
+            EitherOrBoth::Left((1, 1)),
+            EitherOrBoth::Right((2, 5)),
+            EitherOrBoth::Both((3, 2), (3, 1)),
+            EitherOrBoth::Left((4, 1)),
+            EitherOrBoth::Right((6, 1)),
+        ]);
+        assert_eq!(joined[1].id(), 2);
+        assert_eq!(joined[1].counters_or(0), (0, 5));
+        assert_eq!(a.merge_join_iter(&b).size_hint(), (3, Some(6)));
+        assert_eq!(VersionVec::<u32, u32>::new().merge_join_iter(&VersionVec::new()).count(), 0);
+    }
+}
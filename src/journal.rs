@@ -0,0 +1,160 @@
+//! Clock mutations as standalone records.
+//!
+//! Instead of persisting the whole vector after every update, a
+//! write-ahead log can journal each `MutationRecord` and rebuild the
+//! clock on recovery by applying them in order. See `codecs::journal`
+//! for the compact encoding.
+
+use alloc::vec::Vec;
+use core::cmp;
+
+use num_traits::Num;
+
+use {Dot, VersionVec};
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// A single change of a clock
+pub enum MutationRecord<I, T> {
+    /// Local event of an actor, as in `VersionVec::bump_for`
+    Bump(I),
+    /// Remote event was observed
+    Witness(Dot<I, T>),
+    /// Single entry carried in by a merge
+    MergeEntry(I, T)
+}
+
+impl<I, T> MutationRecord<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Replays the mutation on `clock`.
+    ///
+    /// `Witness` and `MergeEntry` only ever raise counters, so replaying
+    /// them more than once is harmless. `Bump` isn't idempotent.
+    pub fn apply(&self, clock: &mut VersionVec<I, T>) {
+        match *self {
+            MutationRecord::Bump(actor) => clock.bump_for(actor),
+            MutationRecord::Witness(dot) => clock.raise(dot.actor, dot.counter),
+            MutationRecord::MergeEntry(actor, counter) => clock.raise(actor, counter)
+        }
+    }
+}
+
+/// Replays `records` on an empty clock until `predicate` holds for the
+/// clock, returning that clock. If it never holds, returns the clock
+/// after all records.
+///
+/// E.g. `|clock| clock.get(&x.actor) >= Some(x.counter)` finds the state
+/// right after dot `x` was accepted.
+pub fn replay_until<'a, I, T, R, P>(records: R, mut predicate: P) -> VersionVec<I, T>
+    where I: 'a + Ord + Copy, T: 'a + Ord + Copy + Num,
+          R: IntoIterator<Item = &'a MutationRecord<I, T>>, P: FnMut(&VersionVec<I, T>) -> bool {
+    let mut clock = VersionVec::new();
+    if predicate(&clock) {
+        return clock
+    }
+    for record in records {
+        record.apply(&mut clock);
+        if predicate(&clock) {
+            break
+        }
+    }
+    clock
+}
+
+#[derive(Clone, Debug)]
+/// Checkpoints of a journal for seeking without replaying from the start
+pub struct JournalIndex<I, T> {
+    every: usize,
+    /// `checkpoints[n]` is the clock after `n * every` records
+    checkpoints: Vec<VersionVec<I, T>>
+}
+
+impl<I, T> JournalIndex<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Indexes `records`, keeping a checkpoint every `every` records
+    ///
+    /// # Panics
+    ///
+    /// If `every` is zero.
+    pub fn build(records: &[MutationRecord<I, T>], every: usize) -> JournalIndex<I, T> {
+        assert!(every > 0, "checkpoint interval must be positive");
+        let mut clock = VersionVec::new();
+        let mut checkpoints = vec![clock.clone()];
+        for chunk in records.chunks(every) {
+            for record in chunk {
+                record.apply(&mut clock);
+            }
+            if chunk.len() == every {
+                checkpoints.push(clock.clone());
+            }
+        }
+        JournalIndex { every, checkpoints }
+    }
+
+    /// Clock after the first `position` records of the indexed journal
+    pub fn clock_at(&self, records: &[MutationRecord<I, T>], position: usize) -> VersionVec<I, T> {
+        let position = cmp::min(position, records.len());
+        let checkpoint = cmp::min(position / self.every, self.checkpoints.len() - 1);
+        let mut clock = self.checkpoints[checkpoint].clone();
+        for record in &records[checkpoint * self.every..position] {
+            record.apply(&mut clock);
+        }
+        clock
+    }
+
+    /// Same as `replay_until`, but starts from the last checkpoint for
+    /// which `predicate` doesn't hold yet.
+    ///
+    /// Clocks only grow along a journal, so predicates like "covers dot
+    /// x" flip at most once and checkpoints can be binary searched.
+    /// Returns the position and the clock.
+    pub fn seek<P>(&self, records: &[MutationRecord<I, T>], mut predicate: P) -> (usize, VersionVec<I, T>)
+        where P: FnMut(&VersionVec<I, T>) -> bool {
+        if predicate(&self.checkpoints[0]) {
+            return (0, self.checkpoints[0].clone())
+        }
+
+        let checkpoint = self.checkpoints.partition_point(|clock| !predicate(clock)) - 1;
+        let mut position = checkpoint * self.every;
+        let mut clock = self.checkpoints[checkpoint].clone();
+        for record in &records[position..] {
+            record.apply(&mut clock);
+            position += 1;
+            if predicate(&clock) {
+                break
+            }
+        }
+        (position, clock)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::{replay_until, JournalIndex, MutationRecord};
+    use {Dot, VersionVec};
+
+    fn journal() -> Vec<MutationRecord<u32, u32>> {
+        (0..50u32).map(|n| match n % 3 {
+            0 => MutationRecord::Bump(1),
+            1 => MutationRecord::Witness(Dot::new(2, n)),
+            _ => MutationRecord::MergeEntry(3, n / 2)
+        }).collect()
+    }
+
+    #[test]
+    fn time_travel() {
+        let records = journal();
+        let covers = |clock: &VersionVec<u32, u32>| clock.get(&2) >= Some(31);
+
+        let clock = replay_until(&records, covers);
+        assert_eq!(clock.as_ref(), [(1, 11), (2, 31), (3, 14)]);
+        assert_eq!(replay_until(&records, |_| false).get(&1), Some(17));
+
+        let index = JournalIndex::build(&records, 8);
+        let (position, found) = index.seek(&records, covers);
+        assert_eq!((position, found.as_ref()), (32, clock.as_ref()));
+        assert_eq!(index.clock_at(&records, 32).as_ref(), clock.as_ref());
+        assert_eq!(index.clock_at(&records, 1000).as_ref(), replay_until(&records, |_| false).as_ref());
+        assert_eq!(index.seek(&records, |_| true).0, 0);
+        assert_eq!(index.seek(&records, |c| c.get(&1) > Some(100)).0, records.len());
+    }
+}
@@ -1,10 +1,150 @@
+//! Simple implementation of generic version vectors.
+//!
+//! Core clock types (`VersionVec`, `Dot`, `Ordering`) and comparison
+//! utilities have no optional dependencies and only need `alloc`.
+//! Larger subsystems are grouped into independent cargo features, these
+//! are enabled by default:
+//!
+//! * `std` - implementations which need the standard library
+//! * `codecs` - wire and storage encodings, see `codecs`
+//! * `protocols` - clock-passing protocol helpers, see `protocols`
+//!
+//! Opt-in features:
+//!
+//! * `rayon` - parallel versions of bulk operations
+//! * `flatbuffers` - zero-copy access to clocks in FlatBuffers messages
+//! * `borsh` - canonical Borsh encoding
+//! * `arbitrary` - `Arbitrary` implementations for fuzzing
+//! * `im` - persistent storage backend with cheap snapshots
+//! * `arrow` - conversion of clocks to and from Arrow arrays
+//! * `serde` - serde support, readable or compact depending on format
+//! * `uuid` - `Uuid` actors with a compact 16-byte encoding
+//! * `proptest`, `quickcheck`, `rand` - random clock generation, see
+//!   `generate`
+//!
+//! For embedded builds use `default-features = false` and pick the
+//! features you need.
+
+#![no_std]
 #![allow(dead_code)]
 
-extern crate num;
+#[cfg(feature = "std")]
+extern crate std;
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+#[cfg(feature = "arrow")]
+extern crate arrow_array;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
+extern crate num_traits;
+#[cfg(feature = "borsh")]
+extern crate borsh;
+#[cfg(feature = "flatbuffers")]
+extern crate flatbuffers;
+#[cfg(feature = "im")]
+extern crate im;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+#[cfg(feature = "quickcheck")]
+extern crate quickcheck;
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_test;
+#[cfg(feature = "uuid")]
+extern crate uuid;
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cmp;
+use core::convert::TryFrom;
+use core::error;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{FromIterator, Sum};
+use core::mem;
+use core::ops::{Bound, Range, RangeBounds};
+use num_traits::{Num, ToPrimitive};
 
-use std::cmp;
-use std::fmt;
-use num::Num;
+pub mod actor;
+pub mod bounded;
+pub mod bridge;
+pub mod cache;
+pub mod chaos;
+#[cfg(feature = "codecs")]
+pub mod codecs;
+pub mod crdt;
+mod digest;
+pub mod directory;
+pub mod epoch;
+pub mod events;
+pub mod explain;
+pub mod filter;
+pub mod flags;
+pub mod forecast;
+pub mod gc;
+#[cfg(any(feature = "proptest", feature = "quickcheck", feature = "rand"))]
+pub mod generate;
+pub mod interner;
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+pub mod join;
+pub mod journal;
+pub mod map;
+pub mod namespace;
+pub mod node;
+pub mod order;
+pub mod policy;
+pub mod pool;
+#[cfg(feature = "protocols")]
+pub mod protocols;
+pub mod speculative;
+pub mod storage;
+pub mod time;
+pub mod timestamped;
+pub mod tracker;
+pub mod transform;
+pub mod view;
+pub mod window;
+pub mod zeros;
+
+pub use actor::Actor;
+pub use bounded::{BoundedClock, CounterExhausted};
+pub use bridge::BridgedClock;
+pub use cache::CachedComparator;
+pub use crdt::{GCounter, PNCounter};
+pub use directory::ActorDirectory;
+pub use epoch::{EpochClock, EpochError};
+pub use events::{ChangeEvent, ChangeObserver, ChangeQueue, MergeHook};
+pub use explain::{explain_cmp, Explanation};
+pub use join::EitherOrBoth;
+pub use flags::{FlaggedClock, Flags};
+pub use forecast::RotationPolicy;
+pub use gc::GcCoordinator;
+pub use interner::{ActorHandle, ActorInterner};
+pub use journal::MutationRecord;
+pub use map::{ClockFamily, ClockMap};
+pub use namespace::NamespacedId;
+pub use node::{DotKeyMap, NodeClock};
+pub use order::TotalOrder;
+pub use policy::{Admission, MergePolicy, RejectedActor};
+pub use pool::ClockPool;
+#[cfg(feature = "protocols")]
+pub use protocols::{ClockedMessage, ReadContext};
+pub use speculative::SpeculativeClock;
+pub use time::{TimeSource, VirtualTime};
+pub use timestamped::TimestampedClock;
+pub use tracker::Tracker;
+pub use transform::TransformError;
+pub use view::MergedView;
+pub use window::{WindowOutcome, WindowedClock};
+pub use zeros::{KeepZeros, Normalized, StripZeros, ZeroPolicy};
 
 #[derive(Copy, Clone, Eq, Debug, PartialEq)]
 /// Describes relations between two version vectors
@@ -27,6 +167,254 @@ impl Ordering {
             _ => ()
         }
     }
+
+    /// Like `eat`, for relations between parts of composite clocks
+    #[inline]
+    fn absorb(&mut self, order: Ordering) {
+        match order {
+            Ordering::Less => self.eat(cmp::Ordering::Less),
+            Ordering::Greater => self.eat(cmp::Ordering::Greater),
+            Ordering::Concurrent => *self = Ordering::Concurrent,
+            Ordering::Equal => ()
+        }
+    }
+
+    /// Combines relations between parts of composite clocks, e.g. shards
+    /// or per-field clocks, into the relation of the whole. Stops at the
+    /// first `Concurrent`, an empty input is `Equal`.
+    pub fn fold<O: IntoIterator<Item = Ordering>>(orders: O) -> Ordering {
+        let mut result = Ordering::Equal;
+        for order in orders {
+            result.absorb(order);
+            if result == Ordering::Concurrent {
+                break
+            }
+        }
+        result
+    }
+
+    /// Short symbolic form: `<`, `=`, `>` or `||`
+    pub fn symbol(&self) -> &'static str {
+        match *self {
+            Ordering::Less => "<",
+            Ordering::Equal => "=",
+            Ordering::Greater => ">",
+            Ordering::Concurrent => "||"
+        }
+    }
+
+    /// Put coordinator policy for `incoming.cmp(&stored)`: incoming
+    /// value replaces the stored one if it is `Greater` or `Equal`
+    pub fn allows_overwrite(&self) -> bool {
+        matches!(*self, Ordering::Greater | Ordering::Equal)
+    }
+
+    /// Put coordinator policy for `incoming.cmp(&stored)`: concurrent
+    /// values are kept side by side as siblings. If neither this nor
+    /// `allows_overwrite` holds, incoming value is stale and rejected.
+    pub fn needs_sibling(&self) -> bool {
+        *self == Ordering::Concurrent
+    }
+
+    /// Parses a symbol produced by `symbol`
+    pub fn from_symbol(symbol: &str) -> Option<Ordering> {
+        match symbol {
+            "<" => Some(Ordering::Less),
+            "=" => Some(Ordering::Equal),
+            ">" => Some(Ordering::Greater),
+            "||" => Some(Ordering::Concurrent),
+            _ => None
+        }
+    }
+}
+
+impl FromIterator<Ordering> for Ordering {
+    fn from_iter<O: IntoIterator<Item = Ordering>>(orders: O) -> Ordering {
+        Ordering::fold(orders)
+    }
+}
+
+/// Formats as `symbol`
+impl fmt::Display for Ordering {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Wire code: 0 - less, 1 - equal, 2 - greater, 3 - concurrent
+impl From<Ordering> for u8 {
+    fn from(order: Ordering) -> u8 {
+        match order {
+            Ordering::Less => 0,
+            Ordering::Equal => 1,
+            Ordering::Greater => 2,
+            Ordering::Concurrent => 3
+        }
+    }
+}
+
+impl TryFrom<u8> for Ordering {
+    type Error = UnknownOrdering;
+
+    fn try_from(code: u8) -> Result<Ordering, UnknownOrdering> {
+        match code {
+            0 => Ok(Ordering::Less),
+            1 => Ok(Ordering::Equal),
+            2 => Ok(Ordering::Greater),
+            3 => Ok(Ordering::Concurrent),
+            _ => Err(UnknownOrdering(code))
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Byte which is not a valid `Ordering` wire code
+pub struct UnknownOrdering(pub u8);
+
+impl fmt::Display for UnknownOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown ordering code {}", self.0)
+    }
+}
+
+impl error::Error for UnknownOrdering {}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq, Hash, PartialOrd, Ord)]
+/// A single event: the `counter`-th update issued by `actor`
+pub struct Dot<I, T> {
+    pub actor: I,
+    pub counter: T
+}
+
+impl<I, T> Dot<I, T> {
+    /// Creates a dot for `counter`-th event of `actor`
+    pub fn new(actor: I, counter: T) -> Dot<I, T> {
+        Dot {
+            actor,
+            counter
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Answer of `VersionVec::approx_dominates`
+pub enum Dominance {
+    Definitely,
+    /// Only entries of pruned actors are in the way, real clocks may
+    /// well be ordered
+    Possibly,
+    No
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// How `VersionVec::fork` sets up the entry of the new actor
+pub enum ForkPolicy {
+    /// New actor starts from zero. The original replica may keep issuing
+    /// events as the old actor.
+    Fresh,
+    /// New actor continues from the old actor's counter, which must be
+    /// retired: neither replica issues events as the old actor again.
+    Inherit
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons `VersionVec::fork` refuses to fork
+pub enum ForkError {
+    /// Old and new actors are the same, the copies would issue
+    /// duplicate dots
+    SameActor,
+    /// New actor already has events in the clock, so its id has been
+    /// used before
+    ActorExists
+}
+
+impl fmt::Display for ForkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ForkError::SameActor => f.write_str("forked replica must use a new actor id"),
+            ForkError::ActorExists => f.write_str("actor id of forked replica has been used before")
+        }
+    }
+}
+
+impl error::Error for ForkError {}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Clock didn't match the expected one in `VersionVec::compare_and_bump`.
+/// Holds how the current clock compares to the expected one.
+pub struct Conflict(pub Ordering);
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "clock changed concurrently: current {} expected", self.0)
+    }
+}
+
+impl error::Error for Conflict {}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Actor with entries in both clocks given to `VersionVec::merge_disjoint`
+pub struct SharedActor<I>(pub I);
+
+impl<I: fmt::Debug> fmt::Display for SharedActor<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "actor {:?} is present in both clocks", self.0)
+    }
+}
+
+impl<I: fmt::Debug> error::Error for SharedActor<I> {}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Result of `VersionVec::apply_dot`
+pub enum ApplyOutcome<T> {
+    /// Dot was the next expected one, counter advanced to it
+    Applied,
+    /// Dot is already covered, clock untouched
+    Duplicate,
+    /// Earlier dots of the actor are missing, clock untouched. Holds
+    /// the counter expected next.
+    Gap { expected: T }
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Size of a merge result, see `VersionVec::least_upper_bound_size`
+pub struct MergeSize {
+    /// Number of entries
+    pub entries: usize,
+    /// Bytes taken by entries in memory
+    pub bytes: usize
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons `VersionVec::from_columns` rejects its input
+pub enum ColumnsError {
+    /// Id and counter columns have different lengths
+    LengthMismatch,
+    /// Id at this position isn't greater than the previous one
+    Unsorted(usize)
+}
+
+impl fmt::Display for ColumnsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ColumnsError::LengthMismatch => f.write_str("id and counter columns differ in length"),
+            ColumnsError::Unsorted(idx) => write!(f, "ids are not strictly increasing at {}", idx)
+        }
+    }
+}
+
+impl error::Error for ColumnsError {}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Decides what `VersionVec::merge_max_n` does when merge would grow
+/// a vector beyond the cap
+pub enum CapPolicy {
+    /// Don't merge anything at all
+    Refuse,
+    /// Add new actors in id order while there is room
+    DropNew,
+    /// Prefer new actors with the largest counters
+    DropSmallest
 }
 
 /// Represents version vector.
@@ -42,6 +430,31 @@ impl<I: fmt::Debug, T: fmt::Debug> fmt::Debug for VersionVec<I, T> {
     }
 }
 
+/// Formats as `{id: counter, ...}`
+impl<I: fmt::Display, T: fmt::Display> fmt::Display for VersionVec<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("{")?;
+        for (idx, entry) in self.inner.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", entry.0, entry.1)?;
+        }
+        f.write_str("}")
+    }
+}
+
+impl<I, T> VersionVec<I, T> {
+    /// Returns a `Display` adapter showing actors by their labels
+    /// in `directory`
+    pub fn display_with<'a, M>(&'a self, directory: &'a ActorDirectory<I, M>) -> directory::Labeled<'a, I, T, M> {
+        directory::Labeled {
+            clock: self,
+            directory
+        }
+    }
+}
+
 impl<I: Clone, T: Clone> Clone for VersionVec<I, T> {
     fn clone(&self) -> VersionVec<I, T> {
         VersionVec {
@@ -50,7 +463,7 @@ impl<I: Clone, T: Clone> Clone for VersionVec<I, T> {
     }
 }
 
-impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy + Clone + Num + Sized {
+impl<I, T> VersionVec<I, T> where I: Ord + Clone, T: Ord + Copy + Num {
     /// Creates a new empty version vector
     pub fn new() -> VersionVec<I, T> {
         VersionVec {
@@ -58,6 +471,18 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
         }
     }
 
+    /// Creates a vector with a single entry
+    pub fn from_dot(actor: I, counter: T) -> VersionVec<I, T> {
+        VersionVec {
+            inner: vec![(actor, counter)]
+        }
+    }
+
+    /// Creates a vector describing the first event of `actor`
+    pub fn singleton(actor: I) -> VersionVec<I, T> {
+        VersionVec::from_dot(actor, T::one())
+    }
+
     /// Constructs version vector from tuples (id, version)
     pub fn from_vec(v: Vec<(I, T)>) -> VersionVec<I, T> {
         let mut v = v;
@@ -67,6 +492,18 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
         }
     }
 
+    /// Constructs version vector from separate id and counter columns,
+    /// as stored by columnar formats. Ids must be strictly increasing.
+    pub fn from_columns(ids: &[I], counters: &[T]) -> Result<VersionVec<I, T>, ColumnsError> {
+        if ids.len() != counters.len() {
+            return Err(ColumnsError::LengthMismatch)
+        }
+        if let Some(idx) = ids.windows(2).position(|w| w[0] >= w[1]) {
+            return Err(ColumnsError::Unsorted(idx + 1))
+        }
+        Ok(VersionVec { inner: ids.iter().cloned().zip(counters.iter().cloned()).collect() })
+    }
+
     /// Creates a new copy of self, merges other into that copy and returns it
     pub fn merged(&self, other: &VersionVec<I, T>) -> VersionVec<I, T> {
         let mut result = self.clone();
@@ -74,33 +511,426 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
         result
     }
 
-    /// Returns the value of counter with id if it exists
-    pub fn get(&self, id: I) -> Option<T> {
-        for i in &self.inner {
-            if i.0 == id {
-                return Some(i.1)
-            } else if i.0 > id {
-                return None
+    /// Entry with the largest counter, the lowest actor of those tied
+    pub fn max_entry(&self) -> Option<(I, T)> {
+        self.inner.iter().filter(|e| e.1 != T::zero()).fold(None, |max: Option<&(I, T)>, e| match max {
+            Some(m) if m.1 >= e.1 => Some(m),
+            _ => Some(e)
+        }).cloned()
+    }
+
+    /// Entry with the smallest non-zero counter, the lowest actor of
+    /// those tied
+    pub fn min_entry(&self) -> Option<(I, T)> {
+        self.inner.iter().filter(|e| e.1 != T::zero()).fold(None, |min: Option<&(I, T)>, e| match min {
+            Some(m) if m.1 <= e.1 => Some(m),
+            _ => Some(e)
+        }).cloned()
+    }
+
+    /// Nearest-rank `percent` percentile of non-zero counters, `None`
+    /// for a vector without them. Values of `percent` above 100 are
+    /// treated as 100.
+    pub fn counter_percentile(&self, percent: u8) -> Option<T> {
+        let mut counters: Vec<T> = self.inner.iter().map(|e| e.1).filter(|&c| c != T::zero()).collect();
+        if counters.is_empty() {
+            return None
+        }
+        counters.sort_unstable();
+        let rank = (cmp::min(percent, 100) as usize * counters.len()).div_ceil(100);
+        Some(counters[rank.saturating_sub(1)])
+    }
+
+    /// Sums counters grouped by `bucket` of their actor, e.g. its
+    /// datacenter. Buckets without non-zero counters are left out.
+    pub fn counter_sum_by<K: Ord, F: FnMut(&I) -> K>(&self, mut bucket: F) -> BTreeMap<K, T> {
+        let mut sums = BTreeMap::new();
+        for &(ref id, counter) in self.inner.iter().filter(|e| e.1 != T::zero()) {
+            let sum = sums.entry(bucket(id)).or_insert_with(T::zero);
+            *sum = *sum + counter;
+        }
+        sums
+    }
+
+    /// Returns how many events `self` has seen that `other` hasn't, and
+    /// the other way round: sums of counter differences per direction.
+    ///
+    /// Unlike `cmp`, tells how far apart concurrent vectors are. Sums
+    /// saturate at `u64::MAX`.
+    ///
+    /// # Panics
+    ///
+    /// If a counter difference can't be represented as `u64`.
+    pub fn difference_size(&self, other: &VersionVec<I, T>) -> (u64, u64) where T: ToPrimitive {
+        let delta = |a: T, b: T| if a > b { (a - b).to_u64().expect("counter doesn't fit into u64") } else { 0 };
+        self.merge_join_iter(other).fold((0u64, 0u64), |(ahead, behind), entry| {
+            let (local, remote) = entry.counters_or(T::zero());
+            (ahead.saturating_add(delta(local, remote)), behind.saturating_add(delta(remote, local)))
+        })
+    }
+
+    /// Bumps counter of `actor` only if the clock still equals
+    /// `expected`, the building block of optimistic concurrency control:
+    /// read a value with its clock, compute, then write back conditionally.
+    pub fn compare_and_bump(&mut self, expected: &VersionVec<I, T>, actor: I) -> Result<Dot<I, T>, Conflict> {
+        match self.cmp(expected) {
+            Ordering::Equal => Ok(Dot::new(actor.clone(), self.increment(actor))),
+            order => Err(Conflict(order))
+        }
+    }
+
+    /// Like `compare_and_bump`, but also bumps if the clock has moved on
+    /// from `expected`, failing only if some of its events are missing
+    pub fn bump_if_descends(&mut self, expected: &VersionVec<I, T>, actor: I) -> Result<Dot<I, T>, Conflict> {
+        match self.cmp(expected) {
+            Ordering::Equal | Ordering::Greater => Ok(Dot::new(actor.clone(), self.increment(actor))),
+            order => Err(Conflict(order))
+        }
+    }
+
+    /// Applies `dot` if it is the next one of its actor. Op-based
+    /// replication uses the outcome to apply, drop or buffer operations.
+    pub fn apply_dot(&mut self, dot: Dot<I, T>) -> ApplyOutcome<T> {
+        let expected = self.counter_of(&dot.actor) + T::one();
+        if dot.counter < expected {
+            ApplyOutcome::Duplicate
+        } else if dot.counter > expected {
+            ApplyOutcome::Gap { expected }
+        } else {
+            self.raise(dot.actor, dot.counter);
+            ApplyOutcome::Applied
+        }
+    }
+
+    /// Splits the vector in two at `id`, like `BTreeMap::split_off`:
+    /// returns entries of actors from `id` onwards, keeping the rest
+    pub fn split_off(&mut self, id: I) -> VersionVec<I, T> {
+        let at = self.inner.partition_point(|e| e.0 < id);
+        VersionVec { inner: self.inner.split_off(at) }
+    }
+
+    /// Returns size `a.merged(b)` would have, without merging, so
+    /// capacity can be reserved or an oversized merge refused upfront
+    pub fn least_upper_bound_size(a: &VersionVec<I, T>, b: &VersionVec<I, T>) -> MergeSize {
+        let entries = a.merge_join_iter(b).count();
+        MergeSize { entries, bytes: entries * mem::size_of::<(I, T)>() }
+    }
+
+    /// Inserts zero entries so `a` and `b` end up with identical sets of
+    /// actors. Doesn't change how they compare.
+    pub fn align(a: &mut VersionVec<I, T>, b: &mut VersionVec<I, T>) {
+        let zeroed = |v: &VersionVec<I, T>| VersionVec {
+            inner: v.inner.iter().map(|e| (e.0.clone(), T::zero())).collect()
+        };
+        let (a_domain, b_domain) = (zeroed(a), zeroed(b));
+        a.merge(&b_domain);
+        b.merge(&a_domain);
+    }
+
+    /// Removes zero entries, the inverse of `align`. To have zero
+    /// entries stripped automatically see `zeros::Normalized`.
+    pub fn strip_zeros(&mut self) {
+        self.inner.retain(|e| e.1 != T::zero());
+    }
+
+    /// True if every actor with an entry here, zero or not, has one in
+    /// `other` too. Counters aren't compared.
+    pub fn domain_subset_of(&self, other: &VersionVec<I, T>) -> bool {
+        self.merge_join_iter(other).all(|e| !matches!(e, EitherOrBoth::Left(_)))
+    }
+
+    /// True if no actor has entries in both vectors
+    pub fn domain_disjoint_with(&self, other: &VersionVec<I, T>) -> bool {
+        self.merge_join_iter(other).all(|e| !matches!(e, EitherOrBoth::Both(..)))
+    }
+
+    /// True if both vectors have entries for exactly the same actors
+    pub fn domain_eq(&self, other: &VersionVec<I, T>) -> bool {
+        self.inner.len() == other.inner.len() && self.domain_subset_of(other)
+    }
+
+    /// Returns greatest lower bound of two vectors: events both of them
+    /// have seen. Counters are minimums, actors missing from either side
+    /// are dropped.
+    pub fn glb(&self, other: &VersionVec<I, T>) -> VersionVec<I, T> {
+        let mut result = Vec::with_capacity(cmp::min(self.inner.len(), other.inner.len()));
+        let mut other_idx = 0;
+
+        for entry in &self.inner {
+            while other_idx < other.inner.len() && other.inner[other_idx].0 < entry.0 {
+                other_idx += 1;
+            }
+            if other_idx == other.inner.len() {
+                break
+            }
+            if other.inner[other_idx].0 == entry.0 {
+                result.push((entry.0.clone(), cmp::min(entry.1, other.inner[other_idx].1)));
             }
         }
 
-        None
+        VersionVec { inner: result }
+    }
+
+    /// Walks entries of `self` and `other` in id order, pairing up
+    /// entries for the same id. A building block for custom merge and
+    /// comparison semantics.
+    pub fn merge_join_iter<'a>(&'a self, other: &'a VersionVec<I, T>) -> join::MergeJoin<'a, I, T> {
+        join::MergeJoin::new(&self.inner, &other.inner)
+    }
+
+    /// Compares entries of every actor known to either vector, missing
+    /// entries counting as zeros
+    pub fn cmp_each<'a>(&'a self, other: &'a VersionVec<I, T>) -> impl Iterator<Item = (I, cmp::Ordering)> + 'a {
+        self.merge_join_iter(other).map(|e| {
+            let (left, right) = e.counters_or(T::zero());
+            (e.id(), left.cmp(&right))
+        })
+    }
+
+    /// Returns a 64-bit digest of the vector.
+    ///
+    /// Zero entries don't contribute, so vectors which compare as `Equal`
+    /// share a digest. The value is stable across runs, but depends on
+    /// `Hash` implementations of the id and counter types.
+    pub fn digest(&self) -> u64 where I: Hash, T: Hash {
+        let mut hasher = digest::Fnv64::new();
+        for entry in self.inner.iter().filter(|e| e.1 != T::zero()) {
+            entry.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns digests of actor buckets, for finding where two large
+    /// vectors differ without exchanging them.
+    ///
+    /// Actors are split into `1 << bits` buckets by the top bits of their
+    /// fingerprint. Every bucket digests its non-zero entries as the
+    /// fingerprint followed by the counter as `u64`, both little-endian,
+    /// so digests match between peers on any platform and with different
+    /// counter widths. Bucket `i` covers buckets `2 * i` and `2 * i + 1`
+    /// of the next level, so peers can compare digests level by level,
+    /// descending only into buckets which differ, and finally exchange
+    /// their `bucket_entries`.
+    ///
+    /// At most 16 bits are allowed, i.e. 65536 buckets taking 512 KiB,
+    /// which is plenty for narrowing down clocks with millions of actors.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is above 16 or a counter can't be represented as `u64`.
+    pub fn hash_per_actor(&self, bits: u32) -> Vec<u64> where I: Actor, T: ToPrimitive {
+        assert!(bits <= 16, "too many digest buckets");
+        let mut hashers: Vec<_> = (0..1usize << bits).map(|_| digest::Fnv64::new()).collect();
+        for entry in self.inner.iter().filter(|e| e.1 != T::zero()) {
+            let counter = entry.1.to_u64().expect("counter doesn't fit into u64");
+            let hasher = &mut hashers[actor_bucket(&entry.0, bits)];
+            hasher.write(&entry.0.fingerprint().to_le_bytes());
+            hasher.write(&counter.to_le_bytes());
+        }
+        hashers.iter().map(|h| h.finish()).collect()
+    }
+
+    /// Entries of `bucket` out of `1 << bits`, see `hash_per_actor`
+    pub fn bucket_entries(&self, bits: u32, bucket: usize) -> impl Iterator<Item = (I, T)> + '_ where I: Actor {
+        self.inner.iter().filter(move |e| actor_bucket(&e.0, bits) == bucket).cloned()
+    }
+
+    /// Returns the value of counter with id if it exists
+    pub fn get(&self, id: &I) -> Option<T> {
+        self.index_of(id).ok().map(|idx| self.inner[idx].1)
+    }
+
+    /// Counter of `id`, zero if it's missing
+    fn counter_of(&self, id: &I) -> T {
+        self.index_of(id).map_or_else(|_| T::zero(), |idx| self.inner[idx].1)
+    }
+
+    /// Iterates over actors with counters strictly greater than `t`
+    pub fn actors_above(&self, t: T) -> impl Iterator<Item = I> + '_ {
+        self.inner.iter().filter(move |e| e.1 > t).map(|e| e.0.clone())
+    }
+
+    /// Returns entries with ids in `range`
+    pub fn entries_in<R: RangeBounds<I>>(&self, range: R) -> &[(I, T)] {
+        let start = match range.start_bound() {
+            Bound::Included(id) => self.inner.partition_point(|e| e.0 < *id),
+            Bound::Excluded(id) => self.inner.partition_point(|e| e.0 <= *id),
+            Bound::Unbounded => 0
+        };
+        let end = match range.end_bound() {
+            Bound::Included(id) => self.inner.partition_point(|e| e.0 <= *id),
+            Bound::Excluded(id) => self.inner.partition_point(|e| e.0 < *id),
+            Bound::Unbounded => self.inner.len()
+        };
+        &self.inner[start..cmp::max(start, end)]
     }
 
     /// Bump (increase) counter for specified id.
     /// If id is missing, adds a new and sets value to 1
     pub fn bump_for(&mut self, id: I) {
-        let idx = self.inner.iter().position(|value| value.0 >= id);
-        match idx {
-            None => self.inner.push((id, T::one())),
-            Some(idx) => {
-                if self.inner[idx].0 == id {
-                    self.inner[idx].1 = self.inner[idx].1 +(T::one())
-                } else {
-                    self.inner.insert(idx, (id, T::one()))
+        self.increment(id);
+    }
+
+    /// Like `bump_for`, but reports the change to `observer`
+    pub fn bump_observed<O: ChangeObserver<I, T>>(&mut self, id: I, observer: &mut O) {
+        let from = self.index_of(&id).ok().map(|idx| self.inner[idx].1);
+        let to = self.increment(id.clone());
+        observer.on_change(match from {
+            Some(from) => ChangeEvent::Advanced { actor: id, from, to },
+            None => ChangeEvent::Added { actor: id, counter: to }
+        });
+    }
+
+    /// Derives the clock of a replica cloned from a snapshot owned by
+    /// `old`. The clone must issue events as `new`.
+    ///
+    /// Two replicas issuing events under the same actor id create
+    /// duplicate dots, silently breaking every comparison. So `new` has
+    /// to be an id never used before, i.e. absent from the snapshot.
+    pub fn fork(&self, old: I, new: I, policy: ForkPolicy) -> Result<VersionVec<I, T>, ForkError> {
+        if old == new {
+            return Err(ForkError::SameActor)
+        }
+        if !self.counter_of(&new).is_zero() {
+            return Err(ForkError::ActorExists)
+        }
+
+        let mut forked = self.clone();
+        if policy == ForkPolicy::Inherit {
+            if let Ok(idx) = self.index_of(&old) {
+                forked.raise(new, self.inner[idx].1);
+            }
+        }
+        Ok(forked)
+    }
+
+    /// Advances counter of `actor` by `n` at once, returning the range of
+    /// reserved counters.
+    ///
+    /// A producer can reserve a batch under a single lock and stamp the
+    /// events from the range afterwards, as no other caller is handed the
+    /// same counters.
+    pub fn reserve_dots(&mut self, actor: I, n: T) -> Range<T> {
+        let start = self.counter_of(&actor) + T::one();
+        let end = start + n;
+        if !n.is_zero() {
+            self.raise(actor, end - T::one());
+        }
+        start..end
+    }
+
+    /// Raises counters to cover every dot of `dots` in a single pass.
+    ///
+    /// Dots are grouped by actor first, so applying a replayed log
+    /// segment costs one merge instead of a lookup per dot.
+    pub fn advance_to_cover<D: IntoIterator<Item = Dot<I, T>>>(&mut self, dots: D) {
+        let mut dots: Vec<Dot<I, T>> = dots.into_iter().collect();
+        dots.sort_unstable();
+
+        let mut covered: Vec<(I, T)> = Vec::new();
+        for dot in dots {
+            match covered.last_mut() {
+                // sorted, so later dots of the same actor are greater
+                Some(last) if last.0 == dot.actor => last.1 = dot.counter,
+                _ => covered.push((dot.actor, dot.counter))
+            }
+        }
+        self.merge(&VersionVec { inner: covered });
+    }
+
+    /// Merges client's causal context and then bumps counter for `actor`,
+    /// returning the dot of the new event.
+    ///
+    /// This is the sequence a put coordinator performs: the resulting
+    /// clock descends both the context client has read and every event
+    /// previously issued by `actor`, so the new value supersedes exactly
+    /// what the client has seen.
+    pub fn advance_from_context(&mut self, client_ctx: &VersionVec<I, T>, actor: I) -> Dot<I, T> {
+        self.merge(client_ctx);
+        let counter = self.increment(actor.clone());
+        Dot::new(actor, counter)
+    }
+
+    fn increment(&mut self, id: I) -> T {
+        match self.index_of(&id) {
+            Ok(idx) => {
+                self.inner[idx].1 = self.inner[idx].1 + T::one();
+                self.inner[idx].1
+            },
+            Err(idx) => {
+                self.inner.insert(idx, (id, T::one()));
+                T::one()
+            }
+        }
+    }
+
+    /// Raises counter of `id` to at least `counter`
+    fn raise(&mut self, id: I, counter: T) {
+        match self.index_of(&id) {
+            Ok(idx) => if self.inner[idx].1 < counter {
+                self.inner[idx].1 = counter;
+            },
+            Err(idx) => self.inner.insert(idx, (id, counter))
+        }
+    }
+
+    /// Merges `other` whose actors are known not to occur here, e.g.
+    /// clocks of different regions. Entries are copied in a single pass
+    /// without looking at counters.
+    ///
+    /// If some actor does occur in both, the result has duplicate
+    /// entries and comparisons with it are meaningless; use
+    /// `merge_disjoint` when unsure.
+    pub fn merge_disjoint_unchecked(&mut self, other: &VersionVec<I, T>) {
+        let _ = self.merge_disjoint_impl(other, false);
+    }
+
+    /// Like `merge_disjoint_unchecked`, but fails without modifying the
+    /// clock if an actor has entries in both
+    pub fn merge_disjoint(&mut self, other: &VersionVec<I, T>) -> Result<(), SharedActor<I>> {
+        self.merge_disjoint_impl(other, true)
+    }
+
+    fn merge_disjoint_impl(&mut self, other: &VersionVec<I, T>, checked: bool) -> Result<(), SharedActor<I>> {
+        match (self.inner.last(), other.inner.first()) {
+            (None, _) => {
+                self.inner.extend_from_slice(&other.inner);
+                return Ok(())
+            },
+            (_, None) => return Ok(()),
+            (Some(last), Some(first)) if last.0 < first.0 => {
+                self.inner.extend_from_slice(&other.inner);
+                return Ok(())
+            },
+            _ => ()
+        }
+
+        let mut result = Vec::with_capacity(self.inner.len() + other.inner.len());
+        let (mut left, mut right) = (&self.inner[..], &other.inner[..]);
+        while let (Some(l), Some(r)) = (left.first(), right.first()) {
+            if checked && l.0 == r.0 {
+                return Err(SharedActor(l.0.clone()))
+            }
+            if l.0 < r.0 {
+                let n = left.partition_point(|e| e.0 < r.0);
+                result.extend_from_slice(&left[..n]);
+                left = &left[n..];
+            } else {
+                let n = right.partition_point(|e| e.0 < l.0);
+                if n == 0 {
+                    // duplicated actor of an unchecked merge
+                    result.push(r.clone());
+                    right = &right[1..];
+                    continue
                 }
+                result.extend_from_slice(&right[..n]);
+                right = &right[n..];
             }
         }
+        result.extend_from_slice(left);
+        result.extend_from_slice(right);
+        self.inner = result;
+        Ok(())
     }
 
     /// Merge in-place
@@ -110,9 +940,7 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
 
         loop {
             if self_idx >= self.inner.len() {
-                for i in other.inner.iter().skip(other_idx) {
-                    self.inner.push(i.clone());
-                }
+                self.inner.extend_from_slice(&other.inner[other_idx..]);
                 break
             }
 
@@ -120,18 +948,17 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
                 break
             }
 
-            let left = self.inner[self_idx];
-            let right = other.inner[other_idx];
+            let right = &other.inner[other_idx];
 
-            if left.0 == right.0 {
-                self.inner[self_idx].1 = cmp::max(left.1, right.1);
-                self_idx += 1;
-                other_idx += 1;
-            } else {
-                if left.0 < right.0 {
-                    self_idx += 1
-                } else {
-                    self.inner.insert(self_idx, right);
+            match self.inner[self_idx].0.cmp(&right.0) {
+                cmp::Ordering::Equal => {
+                    self.inner[self_idx].1 = cmp::max(self.inner[self_idx].1, right.1);
+                    self_idx += 1;
+                    other_idx += 1;
+                },
+                cmp::Ordering::Less => self_idx += 1,
+                cmp::Ordering::Greater => {
+                    self.inner.insert(self_idx, right.clone());
                     self_idx += 1;
                     other_idx += 1;
                 }
@@ -139,7 +966,194 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
         }
     }
 
+    /// Merges entries streamed from `entries`, e.g. from a decoder,
+    /// without materializing the remote vector.
+    ///
+    /// Linear if entries come sorted by actor, as they do from any
+    /// `VersionVec`; unsorted or repeated actors are still merged
+    /// correctly, just slower.
+    pub fn merge_from_iter<E: IntoIterator<Item = (I, T)>>(&mut self, entries: E) {
+        let mut idx = 0;
+        let mut last = None;
+        for (id, counter) in entries {
+            if last.as_ref().is_some_and(|last| *last >= id) {
+                idx = 0;
+            }
+
+            idx += self.inner[idx..].partition_point(|e| e.0 < id);
+            match self.inner.get(idx) {
+                Some(e) if e.0 == id => if e.1 < counter {
+                    self.inner[idx].1 = counter;
+                },
+                _ => self.inner.insert(idx, (id.clone(), counter))
+            }
+            last = Some(id);
+        }
+    }
+
+    /// Like `merge`, but reports every added or advanced entry to
+    /// `observer`, in actor order. Zero entries of `other` don't advance
+    /// anything and aren't reported.
+    pub fn merge_observed<O: ChangeObserver<I, T>>(&mut self, other: &VersionVec<I, T>, observer: &mut O) {
+        for entry in &other.inner {
+            match self.index_of(&entry.0) {
+                Ok(idx) if self.inner[idx].1 < entry.1 => {
+                    observer.on_change(ChangeEvent::Advanced { actor: entry.0.clone(), from: self.inner[idx].1, to: entry.1 })
+                },
+                Ok(_) => (),
+                Err(_) if !entry.1.is_zero() => {
+                    observer.on_change(ChangeEvent::Added { actor: entry.0.clone(), counter: entry.1 })
+                },
+                Err(_) => ()
+            }
+        }
+        self.merge(other);
+    }
+
+    /// Like `merge`, but reports every actor where the clocks disagree to
+    /// `hook`, whichever side is behind
+    pub fn merge_with_hook<H: MergeHook<I, T>>(&mut self, other: &VersionVec<I, T>, hook: &mut H) {
+        for entry in self.merge_join_iter(other) {
+            let (local, remote) = entry.counters_or(T::zero());
+            if local != remote {
+                hook.on_divergence(entry.id(), local, remote);
+            }
+        }
+        self.merge(other);
+    }
+
+    /// Merge in-place, but never grow beyond `n` entries.
+    ///
+    /// Entries for actors already present are always merged (for every
+    /// policy but `Refuse`), new actors are added only while there is
+    /// room. Remote entries which weren't merged are returned, so caller
+    /// knows the result doesn't descend `other`. Zero entries for new
+    /// actors carry no information and are neither added nor returned.
+    pub fn merge_max_n(&mut self, other: &VersionVec<I, T>, n: usize, policy: CapPolicy) -> Vec<(I, T)> {
+        let mut new: Vec<(I, T)> = other.inner.iter()
+            .filter(|e| e.1 != T::zero() && self.index_of(&e.0).is_err())
+            .cloned()
+            .collect();
+        let room = n.saturating_sub(self.inner.len());
+
+        let dropped = if new.len() > room {
+            match policy {
+                CapPolicy::Refuse => {
+                    return other.inner.iter().filter(|e| e.1 != T::zero()).cloned().collect()
+                },
+                CapPolicy::DropSmallest => new.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0))),
+                CapPolicy::DropNew => ()
+            }
+            let mut dropped = new.split_off(room);
+            new.sort_by(|a, b| a.0.cmp(&b.0));
+            dropped.sort_by(|a, b| a.0.cmp(&b.0));
+            dropped
+        } else {
+            vec![]
+        };
+
+        let mut accepted: Vec<(I, T)> = other.inner.iter()
+            .filter(|e| self.index_of(&e.0).is_ok())
+            .cloned()
+            .collect();
+        accepted.extend(new);
+        self.merge(&VersionVec::from_vec(accepted));
+        dropped
+    }
+
+    /// Merge in-place, consulting `policy` for every actor `other`
+    /// would introduce.
+    ///
+    /// Returns dropped remote entries. If policy rejects an actor, self
+    /// is left untouched.
+    pub fn merge_with_policy<P>(&mut self, other: &VersionVec<I, T>, policy: &mut P) -> Result<Vec<(I, T)>, RejectedActor<I>>
+        where P: MergePolicy<I> {
+        let mut accepted = Vec::with_capacity(other.inner.len());
+        let mut dropped = vec![];
+
+        for entry in &other.inner {
+            if self.index_of(&entry.0).is_ok() {
+                accepted.push(entry.clone());
+                continue
+            }
+
+            match policy.admit(&entry.0) {
+                Admission::Allow => accepted.push(entry.clone()),
+                Admission::Drop => dropped.push(entry.clone()),
+                Admission::Reject => return Err(RejectedActor(entry.0.clone()))
+            }
+        }
+
+        self.merge(&VersionVec { inner: accepted });
+        Ok(dropped)
+    }
+
+    fn index_of(&self, id: &I) -> Result<usize, usize> {
+        self.inner.binary_search_by(|e| e.0.cmp(id))
+    }
+
+    /// Returns true if self has seen every event `other` has seen,
+    /// i.e. `cmp` would return `Greater` or `Equal`.
+    ///
+    /// Cheaper than `cmp` as it stops at the first entry of `other`
+    /// which isn't covered.
+    pub fn descends(&self, other: &VersionVec<I, T>) -> bool {
+        let mut idx = 0;
+        for entry in other.inner.iter().filter(|e| e.1 != T::zero()) {
+            while idx < self.inner.len() && self.inner[idx].0 < entry.0 {
+                idx += 1;
+            }
+            if idx == self.inner.len() || self.inner[idx].0 != entry.0 || self.inner[idx].1 < entry.1 {
+                return false
+            }
+        }
+        true
+    }
+
+    /// Like `descends`, but aware that entries of `pruned` actors were
+    /// dropped from `self`.
+    ///
+    /// An entry of `other` exceeding a pruned one may have been covered
+    /// before pruning, so instead of failing the check it makes the
+    /// answer `Possibly`.
+    pub fn approx_dominates(&self, other: &VersionVec<I, T>, pruned: &[I]) -> Dominance {
+        let mut result = Dominance::Definitely;
+        for entry in other.inner.iter() {
+            if self.counter_of(&entry.0) >= entry.1 {
+                continue
+            }
+            if !pruned.contains(&entry.0) {
+                return Dominance::No
+            }
+            result = Dominance::Possibly;
+        }
+        result
+    }
+
+    /// Like `cmp`, but gives up and returns `None` once the comparison
+    /// has walked more than `max_entries` entries of both vectors.
+    ///
+    /// Bounds the work done against unexpectedly large peer clocks; a
+    /// comparison which finds concurrency early stays cheap either way.
+    pub fn cmp_budget(&self, other: &VersionVec<I, T>, max_entries: usize) -> Option<Ordering> {
+        let mut walked = 0;
+        let mut result = Ordering::Equal;
+        for entry in self.merge_join_iter(other) {
+            walked += if let EitherOrBoth::Both(..) = entry { 2 } else { 1 };
+            if walked > max_entries {
+                return None
+            }
+            let (left, right) = entry.counters_or(T::zero());
+            result.eat(left.cmp(&right));
+            if result == Ordering::Concurrent {
+                break
+            }
+        }
+        Some(result)
+    }
+
     /// Compares 2 version vectors
+    #[allow(clippy::should_implement_trait)]
     pub fn cmp(&self, other: &VersionVec<I, T>) -> Ordering {
         let mut self_idx = 0;
         let mut other_idx = 0;
@@ -168,8 +1182,8 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
                 return result
             }
 
-            let left = self.inner[self_idx];
-            let right = other.inner[other_idx];
+            let left = &self.inner[self_idx];
+            let right = &other.inner[other_idx];
 
             let id_cmp = left.0.cmp(&right.0);
             let deltas = match id_cmp {
@@ -192,6 +1206,54 @@ impl<I, T> VersionVec<I, T> where I: Ord + Copy + Clone + Sized, T: Ord + Copy +
     }
 }
 
+/// Empty version vector
+impl<I, T> Default for VersionVec<I, T> where I: Ord + Clone, T: Ord + Copy + Num {
+    fn default() -> VersionVec<I, T> {
+        VersionVec::new()
+    }
+}
+
+impl<I, T> From<Dot<I, T>> for VersionVec<I, T> where I: Ord + Clone, T: Ord + Copy + Num {
+    fn from(dot: Dot<I, T>) -> VersionVec<I, T> {
+        VersionVec::from_dot(dot.actor, dot.counter)
+    }
+}
+
+impl<I, T> From<(I, T)> for VersionVec<I, T> where I: Ord + Clone, T: Ord + Copy + Num {
+    fn from(entry: (I, T)) -> VersionVec<I, T> {
+        VersionVec::from_dot(entry.0, entry.1)
+    }
+}
+
+/// Bucket of `id` for `hash_per_actor`
+fn actor_bucket<I: Actor>(id: &I, bits: u32) -> usize {
+    if bits == 0 {
+        return 0
+    }
+    (id.fingerprint() >> (64 - bits)) as usize
+}
+
+/// Sum of version vectors is their least upper bound
+impl<I, T> Sum for VersionVec<I, T> where I: Ord + Clone, T: Ord + Copy + Num {
+    fn sum<It: Iterator<Item = VersionVec<I, T>>>(iter: It) -> VersionVec<I, T> {
+        let mut result = VersionVec::new();
+        for v in iter {
+            result.merge(&v);
+        }
+        result
+    }
+}
+
+impl<'a, I: 'a, T: 'a> Sum<&'a VersionVec<I, T>> for VersionVec<I, T> where I: Ord + Clone, T: Ord + Copy + Num {
+    fn sum<It: Iterator<Item = &'a VersionVec<I, T>>>(iter: It) -> VersionVec<I, T> {
+        let mut result = VersionVec::new();
+        for v in iter {
+            result.merge(v);
+        }
+        result
+    }
+}
+
 /*
 impl<I, T> Index<RangeFull> for VersionVec<I, T> {
     type Output = [(I, T)];
@@ -205,26 +1267,338 @@ impl<I, T> Index<RangeFull> for VersionVec<I, T> {
 // FIXME: it actually should be convert::AsRef but since I'm stick to
 // an old version, Deref works much better for now
 impl<I, T> AsRef<[(I, T)]> for VersionVec<I, T> {
-    fn as_ref<'a>(&'a self) -> &'a [(I, T)] {
+    fn as_ref(&self) -> &[(I, T)] {
         &self.inner
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Ordering, VersionVec};
+    use alloc::vec::Vec;
+    use core::ops::Bound;
+    use super::{Admission, CapPolicy, ChangeEvent, ChangeQueue, Dominance, Dot, ForkError, ForkPolicy, Ordering, VersionVec};
+    use policy::{Allowlist, Quota};
 
     type VecTemplate = Vec<(usize, usize)>;
 
+    #[test]
+    fn constructors() {
+        let empty: VersionVec<u32, u32> = Default::default();
+        assert!(empty.as_ref().is_empty());
+
+        let single: VersionVec<u32, u32> = VersionVec::singleton(4);
+        assert_eq!(single.as_ref(), [(4, 1)]);
+        assert_eq!(VersionVec::from(Dot::new(4, 1)).cmp(&single), Ordering::Equal);
+        assert_eq!(VersionVec::from((4, 2)).cmp(&single), Ordering::Greater);
+        assert_eq!(VersionVec::from_dot(4, 2).as_ref(), [(4, 2)]);
+    }
+
+    #[test]
+    fn range_queries() {
+        let vv: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 5), (3, 1), (5, 9), (7, 2)]);
+        assert_eq!(vv.actors_above(2).collect::<Vec<_>>(), vec![1, 5]);
+        assert_eq!(vv.actors_above(9).count(), 0);
+
+        assert_eq!(vv.entries_in(3..7), [(3, 1), (5, 9)]);
+        assert_eq!(vv.entries_in(3..=7), [(3, 1), (5, 9), (7, 2)]);
+        assert_eq!(vv.entries_in(..2), [(1, 5)]);
+        assert_eq!(vv.entries_in(6..), [(7, 2)]);
+        assert!(vv.entries_in(8..).is_empty());
+        assert!(vv.entries_in((Bound::Excluded(5), Bound::Excluded(5))).is_empty());
+    }
+
+    #[test]
+    fn fold_orderings() {
+        use core::iter;
+
+        assert_eq!(Ordering::fold(None), Ordering::Equal);
+        assert_eq!(Ordering::fold(vec![Ordering::Equal, Ordering::Less, Ordering::Equal]), Ordering::Less);
+        assert_eq!([Ordering::Greater, Ordering::Less].iter().cloned().collect::<Ordering>(), Ordering::Concurrent);
+
+        let mut seen = 0;
+        let orders = iter::repeat(Ordering::Concurrent).inspect(|_| seen += 1);
+        assert_eq!(orders.collect::<Ordering>(), Ordering::Concurrent);
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn ordering_codes() {
+        use core::convert::TryFrom;
+        use super::UnknownOrdering;
+
+        for &order in &[Ordering::Less, Ordering::Equal, Ordering::Greater, Ordering::Concurrent] {
+            assert_eq!(Ordering::try_from(u8::from(order)), Ok(order));
+            assert_eq!(Ordering::from_symbol(&format!("{}", order)), Some(order));
+        }
+        assert_eq!(format!("{}", Ordering::Concurrent), "||");
+        assert_eq!(Ordering::try_from(4), Err(UnknownOrdering(4)));
+        assert_eq!(Ordering::from_symbol("|"), None);
+
+        let policy: Vec<_> = [Ordering::Less, Ordering::Equal, Ordering::Greater, Ordering::Concurrent].iter()
+            .map(|o| (o.allows_overwrite(), o.needs_sibling())).collect();
+        assert_eq!(policy, [(false, false), (true, false), (true, false), (false, true)]);
+    }
+
+    #[test]
+    fn approx_dominates() {
+        let pruned: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 4), (3, 2)]);
+        let other = VersionVec::from_vec(vec![(1, 3), (2, 5)]);
+
+        assert_eq!(pruned.approx_dominates(&other, &[]), Dominance::No);
+        assert_eq!(pruned.approx_dominates(&other, &[2]), Dominance::Possibly);
+        assert_eq!(pruned.approx_dominates(&VersionVec::from_vec(vec![(1, 4)]), &[2]), Dominance::Definitely);
+        assert_eq!(pruned.approx_dominates(&VersionVec::from_vec(vec![(1, 5), (2, 5)]), &[2]), Dominance::No);
+    }
+
+    #[test]
+    fn hash_per_actor() {
+        let a: VersionVec<u32, u32> = VersionVec::from_vec((0..1000).map(|id| (id, 1)).collect());
+        let mut b = a.clone();
+        b.bump_for(517);
+        b.bump_for(2000);
+        b.merge(&VersionVec::from_vec(vec![(3000, 0)]));
+
+        let mut narrowed = vec![0];
+        for bits in 1..9 {
+            let (da, db) = (a.hash_per_actor(bits), b.hash_per_actor(bits));
+            narrowed = narrowed.iter()
+                .flat_map(|&i| vec![2 * i, 2 * i + 1])
+                .filter(|&i| da[i] != db[i])
+                .collect();
+        }
+        let differing: Vec<_> = narrowed.iter().flat_map(|&i| b.bucket_entries(8, i)).collect();
+        assert!(differing.contains(&(517, 2)) && differing.contains(&(2000, 1)));
+        assert!(differing.len() < 20);
+
+        let narrow: VersionVec<u32, u8> = VersionVec::from_vec(vec![(1, 5), (2, 0), (7, 200)]);
+        let wide: VersionVec<u32, u64> = VersionVec::from_vec(vec![(1, 5), (7, 200)]);
+        assert_eq!(narrow.hash_per_actor(3), wide.hash_per_actor(3));
+        assert_eq!(a.hash_per_actor(0).len(), 1);
+        assert_eq!(a.hash_per_actor(16).len(), 1 << 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many digest buckets")]
+    fn hash_per_actor_limit() {
+        VersionVec::<u32, u32>::new().hash_per_actor(17);
+    }
+
+    #[test]
+    fn from_columns() {
+        use super::ColumnsError;
+
+        let v = VersionVec::from_columns(&[1u32, 4, 7], &[2u32, 0, 5]).unwrap();
+        assert_eq!(v.as_ref(), [(1, 2), (4, 0), (7, 5)]);
+        assert!(VersionVec::<u32, u32>::from_columns(&[], &[]).unwrap().as_ref().is_empty());
+        assert_eq!(VersionVec::from_columns(&[1u32, 4], &[2u32]).err(), Some(ColumnsError::LengthMismatch));
+        assert_eq!(VersionVec::from_columns(&[1u32, 4, 4], &[2u32, 1, 1]).err(), Some(ColumnsError::Unsorted(2)));
+    }
+
+    #[test]
+    fn cmp_budget() {
+        let a: VersionVec<u32, u32> = VersionVec::from_vec((0..100).map(|id| (id, 1)).collect());
+        let mut b = a.clone();
+        b.bump_for(99);
+        assert_eq!(a.cmp_budget(&b, 200), Some(Ordering::Less));
+        assert_eq!(a.cmp_budget(&b, 199), None);
+
+        let mut c = b.clone();
+        c.bump_for(0);
+        b.bump_for(1);
+        assert_eq!(b.cmp_budget(&c, 4), Some(Ordering::Concurrent));
+        assert_eq!(VersionVec::<u32, u32>::new().cmp_budget(&VersionVec::new(), 0), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn apply_dot() {
+        use super::ApplyOutcome;
+
+        let mut v = VersionVec::from_vec(vec![(1u32, 2u32)]);
+        assert_eq!(v.apply_dot(Dot::new(1, 3)), ApplyOutcome::Applied);
+        assert_eq!(v.apply_dot(Dot::new(1, 2)), ApplyOutcome::Duplicate);
+        assert_eq!(v.apply_dot(Dot::new(1, 5)), ApplyOutcome::Gap { expected: 4 });
+        assert_eq!(v.apply_dot(Dot::new(2, 1)), ApplyOutcome::Applied);
+        assert_eq!(v.apply_dot(Dot::new(3, 0)), ApplyOutcome::Duplicate);
+        assert_eq!(v.as_ref(), [(1, 3), (2, 1)]);
+    }
+
+    #[test]
+    fn counter_sum_by() {
+        let vv = VersionVec::from_vec(vec![(101u32, 3u32), (102, 4), (205, 0), (207, 1), (310, 0)]);
+        let sums = vv.counter_sum_by(|id| id / 100);
+        assert_eq!(sums.into_iter().collect::<Vec<_>>(), vec![(1, 7), (2, 1)]);
+        assert!(VersionVec::<u32, u32>::new().counter_sum_by(|id| *id).is_empty());
+    }
+
+    #[test]
+    fn counter_statistics() {
+        let v = VersionVec::from_vec(vec![(1u32, 5u32), (2, 0), (3, 99), (4, 1), (5, 99), (6, 1)]);
+        assert_eq!(v.max_entry(), Some((3, 99)));
+        assert_eq!(v.min_entry(), Some((4, 1)));
+        assert_eq!(v.counter_percentile(0), Some(1));
+        assert_eq!(v.counter_percentile(50), Some(5));
+        assert_eq!(v.counter_percentile(90), Some(99));
+        assert_eq!(v.counter_percentile(255), Some(99));
+
+        let zeros = VersionVec::from_vec(vec![(1u32, 0u32)]);
+        assert_eq!((zeros.max_entry(), zeros.min_entry(), zeros.counter_percentile(50)), (None, None, None));
+    }
+
+    #[test]
+    fn difference_size() {
+        let a = VersionVec::from_vec(vec![(1u32, 5u32), (2, 1), (4, 2)]);
+        let b = VersionVec::from_vec(vec![(1, 2), (2, 4), (3, 1)]);
+        assert_eq!(a.difference_size(&b), (5, 4));
+        assert_eq!(b.difference_size(&a), (4, 5));
+        assert_eq!(a.difference_size(&a.clone()), (0, 0));
+    }
+
+    #[test]
+    fn merge_disjoint() {
+        use super::SharedActor;
+
+        let mut a = VersionVec::from_vec(vec![(1u32, 2u32), (5, 1), (6, 3)]);
+        a.merge_disjoint_unchecked(&VersionVec::from_vec(vec![(2, 1), (3, 4), (9, 1)]));
+        assert_eq!(a.as_ref(), [(1, 2), (2, 1), (3, 4), (5, 1), (6, 3), (9, 1)]);
+
+        let mut b = VersionVec::from_dot(1, 1);
+        b.merge_disjoint_unchecked(&VersionVec::from_dot(4, 2));
+        assert_eq!(b.merge_disjoint(&VersionVec::new()), Ok(()));
+        assert_eq!(b.as_ref(), [(1, 1), (4, 2)]);
+
+        assert_eq!(a.merge_disjoint(&VersionVec::from_vec(vec![(4, 1), (6, 1)])), Err(SharedActor(6)));
+        assert_eq!(a.as_ref().len(), 6);
+        assert_eq!(a.merge_disjoint(&VersionVec::from_dot(0, 1)), Ok(()));
+        assert_eq!(a.as_ref()[0], (0, 1));
+    }
+
+    #[test]
+    fn compare_and_bump() {
+        use super::Conflict;
+
+        let mut v = VersionVec::from_vec(vec![(1u32, 2u32)]);
+        let read = v.clone();
+        assert_eq!(v.compare_and_bump(&read, 2), Ok(Dot::new(2, 1)));
+        assert_eq!(v.compare_and_bump(&read, 2), Err(Conflict(Ordering::Greater)));
+        assert_eq!(v.bump_if_descends(&read, 2), Ok(Dot::new(2, 2)));
+        assert_eq!(v.bump_if_descends(&VersionVec::singleton(3), 2), Err(Conflict(Ordering::Concurrent)));
+        assert_eq!(v.as_ref(), [(1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn split_off() {
+        let mut low = VersionVec::from_vec(vec![(1u32, 2u32), (4, 1), (5, 3), (9, 1)]);
+        let high = low.split_off(5);
+        assert_eq!(low.as_ref(), [(1, 2), (4, 1)]);
+        assert_eq!(high.as_ref(), [(5, 3), (9, 1)]);
+        assert!(low.clone().split_off(10).as_ref().is_empty());
+        assert_eq!(low.merged(&high).as_ref(), [(1, 2), (4, 1), (5, 3), (9, 1)]);
+    }
+
+    #[test]
+    fn domains() {
+        let a = VersionVec::from_vec(vec![(1u32, 2u32), (3, 0)]);
+        let b = VersionVec::from_vec(vec![(1, 1), (2, 5), (3, 4)]);
+        let c = VersionVec::from_vec(vec![(3, 1), (1, 7)]);
+        assert!(a.domain_subset_of(&b) && !b.domain_subset_of(&a));
+        assert!(a.domain_eq(&c) && !a.domain_eq(&b));
+        assert!(!a.domain_disjoint_with(&b));
+        assert!(a.domain_disjoint_with(&VersionVec::from_dot(2, 1)));
+        assert!(VersionVec::new().domain_subset_of(&a));
+    }
+
+    #[test]
+    fn least_upper_bound_size() {
+        use super::MergeSize;
+
+        let a = VersionVec::from_vec(vec![(1u32, 2u32), (3, 0), (5, 1)]);
+        let b = VersionVec::from_vec(vec![(2, 1), (3, 4)]);
+        let size = VersionVec::least_upper_bound_size(&a, &b);
+        assert_eq!(size, MergeSize { entries: 4, bytes: 32 });
+        assert_eq!(size.entries, a.merged(&b).as_ref().len());
+    }
+
+    #[test]
+    fn merge_from_iter() {
+        let remote = VersionVec::from_vec(vec![(0u32, 1u32), (2, 5), (3, 1), (7, 2)]);
+        let mut a = VersionVec::from_vec(vec![(2, 6), (3, 0), (5, 1)]);
+        let mut expected = a.clone();
+        expected.merge(&remote);
+        a.merge_from_iter(remote.as_ref().iter().cloned());
+        assert_eq!(a.as_ref(), expected.as_ref());
+
+        a.merge_from_iter(vec![(9, 1), (1, 4), (9, 3), (0, 0)]);
+        assert_eq!(a.as_ref(), [(0, 1), (1, 4), (2, 6), (3, 1), (5, 1), (7, 2), (9, 3)]);
+    }
+
+    #[test]
+    fn merge_with_hook() {
+        let mut local: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 3), (2, 1), (4, 2)]);
+        let remote = VersionVec::from_vec(vec![(1, 3), (2, 5), (3, 1)]);
+
+        let mut diverged = Vec::new();
+        local.merge_with_hook(&remote, &mut |actor, l, r| diverged.push((actor, l, r)));
+        assert_eq!(diverged, vec![(2, 1, 5), (3, 0, 1), (4, 2, 0)]);
+        assert_eq!(local.as_ref(), [(1, 3), (2, 5), (3, 1), (4, 2)]);
+    }
+
+    #[test]
+    fn fork() {
+        let mut snapshot: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 3), (2, 1), (3, 0)]);
+
+        let mut fresh = snapshot.fork(1, 3, ForkPolicy::Fresh).unwrap();
+        assert_eq!(fresh.as_ref(), snapshot.as_ref());
+        fresh.bump_for(3);
+        snapshot.bump_for(1);
+        assert_eq!(fresh.cmp(&snapshot), Ordering::Concurrent);
+
+        let inherited = snapshot.fork(1, 4, ForkPolicy::Inherit).unwrap();
+        assert_eq!(inherited.get(&4), Some(4));
+        assert_eq!(inherited.cmp(&snapshot), Ordering::Greater);
+
+        assert_eq!(snapshot.fork(1, 1, ForkPolicy::Fresh).err(), Some(ForkError::SameActor));
+        assert_eq!(snapshot.fork(1, 2, ForkPolicy::Fresh).err(), Some(ForkError::ActorExists));
+    }
+
+    #[test]
+    fn reserve_dots() {
+        let mut vv: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 3)]);
+        assert_eq!(vv.reserve_dots(1, 4), 4..8);
+        assert_eq!(vv.get(&1), Some(7));
+        assert_eq!(vv.reserve_dots(2, 2), 1..3);
+        assert_eq!(vv.reserve_dots(2, 0), 3..3);
+        assert_eq!(vv.as_ref(), [(1, 7), (2, 2)]);
+    }
+
+    #[test]
+    fn advance_to_cover() {
+        let mut vv: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 5), (2, 1)]);
+        vv.advance_to_cover(vec![Dot::new(3, 2), Dot::new(1, 2), Dot::new(2, 4), Dot::new(3, 7), Dot::new(2, 3)]);
+        assert_eq!(vv.as_ref(), [(1, 5), (2, 4), (3, 7)]);
+
+        vv.advance_to_cover(None);
+        assert_eq!(vv.as_ref(), [(1, 5), (2, 4), (3, 7)]);
+    }
+
+    #[test]
+    fn cmp_each() {
+        use core::cmp::Ordering::{Equal, Greater, Less};
+
+        let a: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 2), (2, 1), (4, 0)]);
+        let b = VersionVec::from_vec(vec![(1, 2), (2, 3), (3, 1)]);
+        assert_eq!(a.cmp_each(&b).collect::<Vec<_>>(), vec![(1, Equal), (2, Less), (3, Less), (4, Equal)]);
+        assert_eq!(b.cmp_each(&a).filter(|e| e.1 == Greater).count(), 2);
+    }
+
     #[test]
     fn get_counter() {
         let v = VersionVec::from_vec(vec![(1, 10), (2, 20), (3, 30)]);
 
-        assert_eq!(v.get(1), Some(10));
-        assert_eq!(v.get(5), None);
-        assert_eq!(v.get(2), Some(20));
-        assert_eq!(v.get(3), Some(30));
-        assert_eq!(v.get(6), None);
+        assert_eq!(v.get(&1), Some(10));
+        assert_eq!(v.get(&5), None);
+        assert_eq!(v.get(&2), Some(20));
+        assert_eq!(v.get(&3), Some(30));
+        assert_eq!(v.get(&6), None);
     }
 
     #[test]
@@ -241,6 +1615,151 @@ mod test {
         assert_eq!(v.as_ref(), [(0, 1), (1, 11), (2, 20), (3, 30), (10, 1)]);
     }
 
+    #[test]
+    fn sum() {
+        let clocks = vec![
+            VersionVec::from_vec(vec![(1, 3), (2, 1)]),
+            VersionVec::from_vec(vec![(1, 2), (3, 5)]),
+            VersionVec::from_vec(vec![(2, 4)]),
+        ];
+
+        let lub: VersionVec<usize, usize> = clocks.iter().sum();
+        assert_eq!(lub.as_ref(), [(1, 3), (2, 4), (3, 5)]);
+
+        let lub: VersionVec<usize, usize> = clocks.into_iter().sum();
+        assert_eq!(lub.as_ref(), [(1, 3), (2, 4), (3, 5)]);
+
+        let empty: VersionVec<usize, usize> = Vec::<VersionVec<_, _>>::new().into_iter().sum();
+        assert!(empty.as_ref().is_empty());
+    }
+
+    #[test]
+    fn observed_changes() {
+        let mut v = VersionVec::from_vec(vec![(1, 1), (5, 5)]);
+        let mut queue = ChangeQueue::new();
+
+        v.bump_observed(1, &mut queue);
+        v.bump_observed(2, &mut queue);
+        v.merge_observed(&VersionVec::from_vec(vec![(0, 1), (1, 1), (3, 0), (5, 7)]), &mut queue);
+
+        let events: Vec<_> = queue.drain().collect();
+        assert_eq!(events, [
+            ChangeEvent::Advanced { actor: 1, from: 1, to: 2 },
+            ChangeEvent::Added { actor: 2, counter: 1 },
+            ChangeEvent::Added { actor: 0, counter: 1 },
+            ChangeEvent::Advanced { actor: 5, from: 5, to: 7 },
+        ]);
+        assert!(queue.is_empty());
+        assert_eq!(v.as_ref(), [(0, 1), (1, 2), (2, 1), (3, 0), (5, 7)]);
+
+        let mut advanced = vec![];
+        v.merge_observed(&VersionVec::from_vec(vec![(2, 3)]), &mut |e: ChangeEvent<usize, usize>| advanced.push(e.actor()));
+        assert_eq!(advanced, [2]);
+    }
+
+    #[test]
+    fn align() {
+        let mut a = VersionVec::from_vec(vec![(1, 10), (3, 0)]);
+        let mut b = VersionVec::from_vec(vec![(0, 5), (3, 2)]);
+        let ordering = a.cmp(&b);
+
+        VersionVec::align(&mut a, &mut b);
+        assert_eq!(a.as_ref(), [(0, 0), (1, 10), (3, 0)]);
+        assert_eq!(b.as_ref(), [(0, 5), (1, 0), (3, 2)]);
+        assert_eq!(a.cmp(&b), ordering);
+
+        a.strip_zeros();
+        b.strip_zeros();
+        assert_eq!(a.as_ref(), [(1, 10)]);
+        assert_eq!(b.as_ref(), [(0, 5), (3, 2)]);
+    }
+
+    #[test]
+    fn glb() {
+        let v1 = VersionVec::from_vec(vec![(1, 10), (2, 20), (4, 1)]);
+        let v2 = VersionVec::from_vec(vec![(0, 5), (2, 15), (3, 1), (4, 3)]);
+
+        assert_eq!(v1.glb(&v2).as_ref(), [(2, 15), (4, 1)]);
+        assert_eq!(v2.glb(&v1).as_ref(), [(2, 15), (4, 1)]);
+        assert!(v1.glb(&VersionVec::new()).as_ref().is_empty());
+    }
+
+    #[test]
+    fn merge_max_n() {
+        let local = VersionVec::from_vec(vec![(1, 1), (5, 5)]);
+        let remote = VersionVec::from_vec(vec![(1, 3), (2, 9), (3, 0), (4, 2), (6, 1)]);
+
+        let mut v = local.clone();
+        assert_eq!(v.merge_max_n(&remote, 5, CapPolicy::Refuse), []);
+        assert_eq!(v.as_ref(), [(1, 3), (2, 9), (4, 2), (5, 5), (6, 1)]);
+
+        let mut v = local.clone();
+        assert_eq!(v.merge_max_n(&remote, 4, CapPolicy::Refuse), [(1, 3), (2, 9), (4, 2), (6, 1)]);
+        assert_eq!(v.as_ref(), local.as_ref());
+
+        let mut v = local.clone();
+        assert_eq!(v.merge_max_n(&remote, 4, CapPolicy::DropNew), [(6, 1)]);
+        assert_eq!(v.as_ref(), [(1, 3), (2, 9), (4, 2), (5, 5)]);
+
+        let mut v = local.clone();
+        assert_eq!(v.merge_max_n(&remote, 3, CapPolicy::DropSmallest), [(4, 2), (6, 1)]);
+        assert_eq!(v.as_ref(), [(1, 3), (2, 9), (5, 5)]);
+
+        let mut v = local.clone();
+        assert_eq!(v.merge_max_n(&remote, 1, CapPolicy::DropNew), [(2, 9), (4, 2), (6, 1)]);
+        assert_eq!(v.as_ref(), [(1, 3), (5, 5)]);
+    }
+
+    #[test]
+    fn merge_with_policy() {
+        let local = VersionVec::from_vec(vec![(1, 1), (5, 5)]);
+        let remote = VersionVec::from_vec(vec![(1, 3), (2, 9), (4, 2), (5, 1)]);
+
+        let mut v = local.clone();
+        let mut policy = Allowlist::new(vec![2], Admission::Drop);
+        assert_eq!(v.merge_with_policy(&remote, &mut policy), Ok(vec![(4, 2)]));
+        assert_eq!(v.as_ref(), [(1, 3), (2, 9), (5, 5)]);
+
+        let mut v = local.clone();
+        let mut policy = Allowlist::new(vec![2], Admission::Reject);
+        assert_eq!(v.merge_with_policy(&remote, &mut policy).map_err(|e| e.0), Err(4));
+        assert_eq!(v.as_ref(), local.as_ref());
+
+        let mut v = local.clone();
+        let mut policy = Quota::new(1, Admission::Drop);
+        assert_eq!(v.merge_with_policy(&remote, &mut policy), Ok(vec![(4, 2)]));
+        assert_eq!(policy.remaining(), 0);
+
+        let mut v = local.clone();
+        let mut policy = |id: &usize| if *id < 4 { Admission::Allow } else { Admission::Drop };
+        assert_eq!(v.merge_with_policy(&remote, &mut policy), Ok(vec![(4, 2)]));
+        assert_eq!(v.as_ref(), [(1, 3), (2, 9), (5, 5)]);
+    }
+
+    #[test]
+    fn digest() {
+        let v1 = VersionVec::from_vec(vec![(1, 10), (2, 20)]);
+        let v2 = VersionVec::from_vec(vec![(2, 20), (3, 0), (1, 10)]);
+        let v3 = VersionVec::from_vec(vec![(1, 10), (2, 21)]);
+
+        assert_eq!(v1.digest(), v2.digest());
+        assert!(v1.digest() != v3.digest());
+    }
+
+    #[test]
+    fn advance_from_context() {
+        let mut stored = VersionVec::from_vec(vec![(1, 3), (2, 1)]);
+        let ctx = VersionVec::from_vec(vec![(1, 2), (2, 4), (3, 1)]);
+
+        let dot = stored.advance_from_context(&ctx, 1);
+        assert_eq!(dot, Dot::new(1, 4));
+        assert_eq!(stored.as_ref(), [(1, 4), (2, 4), (3, 1)]);
+        assert_eq!(stored.cmp(&ctx), Ordering::Greater);
+
+        let dot = stored.advance_from_context(&VersionVec::new(), 5);
+        assert_eq!(dot, Dot::new(5, 1));
+    }
+
     #[test]
     fn comparisons() {
         // Taken from synching test cases, except concurrent and nil cases
@@ -275,9 +1794,30 @@ mod test {
 
             let res = v1.cmp(&v2);
             assert!(res == case.0, "expected: {:?}, got {:?}, left {:?}, right {:?}", case.0, res, v1, v2);
+
+            let descends = res == Equal || res == Greater;
+            assert!(v1.descends(&v2) == descends, "descends: expected {:?}, left {:?}, right {:?}", descends, v1, v2);
         }
     }
 
+    #[test]
+    fn string_actors() {
+        use alloc::string::{String, ToString};
+
+        let mut a: VersionVec<String, u64> = VersionVec::new();
+        a.bump_for("node-b".to_string());
+        a.bump_for("node-a".to_string());
+        a.bump_for("node-b".to_string());
+        assert_eq!(a.get(&"node-b".to_string()), Some(2));
+
+        let mut b = VersionVec::from_dot("node-c".to_string(), 1);
+        assert_eq!(a.cmp(&b), Ordering::Concurrent);
+        b.merge(&a);
+        assert_eq!(b.cmp(&a), Ordering::Greater);
+        assert!(b.descends(&a));
+        assert_eq!(b.as_ref().iter().map(|e| e.0.as_str()).collect::<Vec<_>>(), ["node-a", "node-b", "node-c"]);
+    }
+
     #[test]
     fn merge() {
         let test_cases: Vec<(VecTemplate, VecTemplate, VecTemplate)> = vec![
@@ -0,0 +1,171 @@
+//! Clocks of many keys, as kept by a replica of a key-value store.
+
+use alloc::collections::btree_map::{self, BTreeMap};
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+use num_traits::Num;
+
+use digest::Fnv64;
+use {Dominance, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Map from keys to their clocks
+pub struct ClockMap<K, I, T> {
+    clocks: BTreeMap<K, VersionVec<I, T>>
+}
+
+impl<K, I, T> ClockMap<K, I, T> where K: Ord, I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> ClockMap<K, I, T> {
+        ClockMap { clocks: BTreeMap::new() }
+    }
+
+    /// Clock of `key`
+    pub fn get(&self, key: &K) -> Option<&VersionVec<I, T>> {
+        self.clocks.get(key)
+    }
+
+    /// Replaces clock of `key`, returning the previous one
+    pub fn insert(&mut self, key: K, clock: VersionVec<I, T>) -> Option<VersionVec<I, T>> {
+        self.clocks.insert(key, clock)
+    }
+
+    /// Merges `clock` into the clock of `key`
+    pub fn merge(&mut self, key: K, clock: &VersionVec<I, T>) {
+        self.clocks.entry(key).or_default().merge(clock);
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<VersionVec<I, T>> {
+        self.clocks.remove(key)
+    }
+
+    /// True if clock of `key` descends `clock`, i.e. a write carrying
+    /// `clock` has nothing new for this replica. Missing keys only
+    /// descend empty clocks.
+    pub fn descends(&self, key: &K, clock: &VersionVec<I, T>) -> bool {
+        match self.clocks.get(key) {
+            Some(local) => local.descends(clock),
+            None => VersionVec::new().descends(clock)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.clocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clocks.is_empty()
+    }
+
+    /// Keys with their clocks in key order
+    pub fn iter(&self) -> btree_map::Iter<'_, K, VersionVec<I, T>> {
+        self.clocks.iter()
+    }
+}
+
+impl<K, I, T> Default for ClockMap<K, I, T> where K: Ord, I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> ClockMap<K, I, T> {
+        ClockMap::new()
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Bounded-memory alternative to `ClockMap`: keys are hashed into a
+/// fixed number of buckets sharing a clock.
+///
+/// A bucket clock is the least upper bound of clocks of all keys hashed
+/// to it, so it over-approximates every one of them. Answers are
+/// reported accordingly: a bucket failing to descend a clock proves the
+/// key doesn't either, while a descending bucket only means the key
+/// possibly does.
+pub struct ClockFamily<I, T> {
+    buckets: Vec<VersionVec<I, T>>
+}
+
+impl<I, T> ClockFamily<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates a family of `buckets` clocks
+    ///
+    /// # Panics
+    ///
+    /// If `buckets` is zero.
+    pub fn new(buckets: usize) -> ClockFamily<I, T> {
+        assert!(buckets > 0, "clock family needs at least one bucket");
+        ClockFamily { buckets: (0..buckets).map(|_| VersionVec::new()).collect() }
+    }
+
+    /// Number of buckets
+    pub fn buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Bucket `key` is hashed to, stable across runs
+    pub fn bucket_of<K: Hash + ?Sized>(&self, key: &K) -> usize {
+        let mut hasher = Fnv64::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.buckets.len() as u64) as usize
+    }
+
+    /// Upper bound of the clock of `key`
+    pub fn clock_for<K: Hash + ?Sized>(&self, key: &K) -> &VersionVec<I, T> {
+        &self.buckets[self.bucket_of(key)]
+    }
+
+    /// Records event of `actor` on `key`
+    pub fn bump<K: Hash + ?Sized>(&mut self, key: &K, actor: I) {
+        let bucket = self.bucket_of(key);
+        self.buckets[bucket].bump_for(actor);
+    }
+
+    /// Merges `clock` into the clock of `key`
+    pub fn merge<K: Hash + ?Sized>(&mut self, key: &K, clock: &VersionVec<I, T>) {
+        let bucket = self.bucket_of(key);
+        self.buckets[bucket].merge(clock);
+    }
+
+    /// Checks if the clock of `key` descends `clock`. Never answers
+    /// `Definitely` unless `clock` is empty, as other keys may have
+    /// contributed to the bucket.
+    pub fn descends<K: Hash + ?Sized>(&self, key: &K, clock: &VersionVec<I, T>) -> Dominance {
+        if clock.inner.iter().all(|e| e.1.is_zero()) {
+            Dominance::Definitely
+        } else if self.clock_for(key).descends(clock) {
+            Dominance::Possibly
+        } else {
+            Dominance::No
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ClockFamily, ClockMap};
+    use {Dominance, VersionVec};
+
+    #[test]
+    fn clock_map() {
+        let mut map: ClockMap<&str, u32, u32> = ClockMap::new();
+        map.merge("a", &VersionVec::from_vec(vec![(1, 2)]));
+        map.merge("a", &VersionVec::from_vec(vec![(2, 1)]));
+        assert_eq!(map.get(&"a").unwrap().as_ref(), [(1, 2), (2, 1)]);
+        assert!(map.descends(&"a", &VersionVec::singleton(2)));
+        assert!(!map.descends(&"b", &VersionVec::singleton(2)));
+        assert!(map.descends(&"b", &VersionVec::new()));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn family_approximates() {
+        let mut family: ClockFamily<u32, u32> = ClockFamily::new(1);
+        family.bump("a", 1);
+        family.bump("b", 2);
+
+        // single bucket, "a" is charged with events of "b"
+        assert_eq!(family.descends("a", &VersionVec::singleton(2)), Dominance::Possibly);
+        assert_eq!(family.descends("a", &VersionVec::from_dot(1, 2)), Dominance::No);
+        assert_eq!(family.descends("a", &VersionVec::new()), Dominance::Definitely);
+
+        let family: ClockFamily<u32, u32> = ClockFamily::new(16);
+        assert!(family.bucket_of("a") < 16);
+        assert_eq!(family.bucket_of("a"), family.bucket_of("a"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Composite actor ids for multi-tenant clocks.
+//!
+//! When one physical node writes on behalf of many tenants, actors are
+//! `(tenant, node)` pairs. `NamespacedId` orders by namespace first, so
+//! entries of a namespace are contiguous in a `VersionVec` and can be
+//! sliced out without scanning the whole clock.
+
+use alloc::collections::BTreeMap;
+use core::fmt;
+
+use num_traits::Num;
+
+use VersionVec;
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// Actor `node` acting within namespace `namespace`
+pub struct NamespacedId<N, A> {
+    pub namespace: N,
+    pub node: A
+}
+
+impl<N, A> NamespacedId<N, A> {
+    pub fn new(namespace: N, node: A) -> NamespacedId<N, A> {
+        NamespacedId { namespace, node }
+    }
+}
+
+/// Formats as `namespace/node`
+impl<N: fmt::Display, A: fmt::Display> fmt::Display for NamespacedId<N, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.namespace, self.node)
+    }
+}
+
+impl<N, A, T> VersionVec<NamespacedId<N, A>, T> where N: Ord + Copy, A: Ord + Copy, T: Ord + Copy + Num {
+    /// Entries of `namespace`, keyed by node
+    pub fn filter_namespace(&self, namespace: N) -> VersionVec<A, T> {
+        let start = self.inner.partition_point(|e| e.0.namespace < namespace);
+        let end = start + self.inner[start..].partition_point(|e| e.0.namespace == namespace);
+        VersionVec { inner: self.inner[start..end].iter().map(|e| (e.0.node, e.1)).collect() }
+    }
+
+    /// Splits the clock into a clock per namespace, keyed by node
+    pub fn split_by_namespace(&self) -> BTreeMap<N, VersionVec<A, T>> {
+        let mut result: BTreeMap<N, VersionVec<A, T>> = BTreeMap::new();
+        for entry in &self.inner {
+            // entries come sorted, so pushing keeps every clock sorted
+            result.entry(entry.0.namespace).or_default().inner.push((entry.0.node, entry.1));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::NamespacedId;
+    use VersionVec;
+
+    #[test]
+    fn namespaces() {
+        let id = NamespacedId::new;
+        let clock = VersionVec::from_vec(vec![(id(2u8, 1u32), 4u32), (id(1, 7), 1), (id(1, 3), 2), (id(3, 1), 0)]);
+
+        assert_eq!(clock.filter_namespace(1).as_ref(), [(3, 2), (7, 1)]);
+        assert!(clock.filter_namespace(4).as_ref().is_empty());
+
+        let split = clock.split_by_namespace();
+        assert_eq!(split.keys().cloned().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(split[&2].as_ref(), [(1, 4)]);
+        assert_eq!(format!("{}", id(2, 1)), "2/1");
+    }
+}
@@ -0,0 +1,244 @@
+//! Node-wide clocks for server-wide causality.
+//!
+//! Instead of a version vector per key, every node keeps a single
+//! `NodeClock` describing which dots from each peer it has stored,
+//! together with a `DotKeyMap` telling which key each not yet stable dot
+//! belongs to. Anti-entropy between two nodes compares their node clocks
+//! and ships exactly the keys behind dots the other side lacks.
+//!
+//! Dots may arrive out of order, so besides the contiguous prefix per
+//! peer (its base) a node clock tracks the individual dots above it.
+
+use alloc::collections::btree_map::{self, BTreeMap};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::cmp;
+
+use num_traits::Num;
+
+use {Dot, VersionVec};
+
+#[derive(Clone, Eq, Debug, PartialEq)]
+struct Entry<T> {
+    /// Every dot up to and including base is present
+    base: T,
+    /// Dots above `base + 1`
+    extra: BTreeSet<T>
+}
+
+impl<T> Entry<T> where T: Ord + Copy + Num {
+    fn new() -> Entry<T> {
+        Entry { base: T::zero(), extra: BTreeSet::new() }
+    }
+
+    /// Moves extras which continue the base into it
+    fn compact(&mut self) {
+        while self.extra.remove(&(self.base + T::one())) {
+            self.base = self.base + T::one();
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Dots seen from each peer, with gaps
+pub struct NodeClock<I, T> {
+    entries: BTreeMap<I, Entry<T>>
+}
+
+impl<I, T> NodeClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> NodeClock<I, T> {
+        NodeClock { entries: BTreeMap::new() }
+    }
+
+    /// Records `dot` as seen
+    pub fn add(&mut self, dot: Dot<I, T>) {
+        let entry = self.entries.entry(dot.actor).or_insert_with(Entry::new);
+        if dot.counter > entry.base {
+            entry.extra.insert(dot.counter);
+            entry.compact();
+        }
+    }
+
+    /// True if `dot` has been seen
+    pub fn contains(&self, dot: Dot<I, T>) -> bool {
+        self.entries.get(&dot.actor)
+            .is_some_and(|e| dot.counter <= e.base || e.extra.contains(&dot.counter))
+    }
+
+    /// Mints and records the next dot of `actor`, the node's own id
+    pub fn next_dot(&mut self, actor: I) -> Dot<I, T> {
+        let dot = Dot::new(actor, self.max(actor) + T::one());
+        self.add(dot);
+        dot
+    }
+
+    /// Counter up to which all dots of `actor` are present
+    pub fn base(&self, actor: I) -> T {
+        self.entries.get(&actor).map_or_else(T::zero, |e| e.base)
+    }
+
+    /// Highest seen counter of `actor`
+    pub fn max(&self, actor: I) -> T {
+        self.entries.get(&actor).map_or_else(T::zero, |e| e.extra.iter().next_back().cloned().unwrap_or(e.base))
+    }
+
+    /// Counters of `actor` below its maximum which haven't been seen
+    pub fn missing(&self, actor: I) -> Vec<T> {
+        let entry = match self.entries.get(&actor) {
+            Some(entry) => entry,
+            None => return vec![]
+        };
+
+        let mut result = vec![];
+        let mut counter = entry.base + T::one();
+        for &present in &entry.extra {
+            while counter < present {
+                result.push(counter);
+                counter = counter + T::one();
+            }
+            counter = present + T::one();
+        }
+        result
+    }
+
+    /// Adds every dot seen by `other`
+    pub fn join(&mut self, other: &NodeClock<I, T>) {
+        for (&actor, theirs) in &other.entries {
+            let entry = self.entries.entry(actor).or_insert_with(Entry::new);
+            let base = cmp::max(entry.base, theirs.base);
+            entry.base = base;
+            entry.extra.extend(theirs.extra.iter().cloned());
+            entry.extra.retain(|&counter| counter > base);
+            entry.compact();
+        }
+    }
+
+    /// Version vector of contiguous bases, dots it covers are all present
+    pub fn to_version_vec(&self) -> VersionVec<I, T> {
+        VersionVec { inner: self.entries.iter().map(|(&id, e)| (id, e.base)).collect() }
+    }
+}
+
+impl<I, T> Default for NodeClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> NodeClock<I, T> {
+        NodeClock::new()
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Which key each dot stored on a node belongs to
+pub struct DotKeyMap<K, I, T> {
+    keys: BTreeMap<Dot<I, T>, K>
+}
+
+impl<K, I, T> DotKeyMap<K, I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> DotKeyMap<K, I, T> {
+        DotKeyMap { keys: BTreeMap::new() }
+    }
+
+    /// Records that write `dot` was made to `key`
+    pub fn insert(&mut self, dot: Dot<I, T>, key: K) {
+        self.keys.insert(dot, key);
+    }
+
+    pub fn get(&self, dot: &Dot<I, T>) -> Option<&K> {
+        self.keys.get(dot)
+    }
+
+    /// Keys written by dots `peer` hasn't seen, which are what has to be
+    /// sent to it
+    pub fn missing_for<'a>(&'a self, peer: &'a NodeClock<I, T>) -> impl Iterator<Item = (Dot<I, T>, &'a K)> + 'a {
+        self.keys.iter().filter(move |&(dot, _)| !peer.contains(*dot)).map(|(dot, key)| (*dot, key))
+    }
+
+    /// Forgets dots covered by `stable`, i.e. seen by every peer
+    pub fn prune(&mut self, stable: &VersionVec<I, T>) {
+        self.keys.retain(|dot, _| stable.get(&dot.actor).is_none_or(|counter| counter < dot.counter));
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, Dot<I, T>, K> {
+        self.keys.iter()
+    }
+}
+
+impl<K, I, T> Default for DotKeyMap<K, I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> DotKeyMap<K, I, T> {
+        DotKeyMap::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::{DotKeyMap, Entry, NodeClock};
+    use {Dot, VersionVec};
+
+    #[test]
+    fn tracks_gaps() {
+        let mut clock: NodeClock<u32, u32> = NodeClock::new();
+        clock.add(Dot::new(1, 1));
+        clock.add(Dot::new(1, 4));
+        clock.add(Dot::new(1, 6));
+        assert_eq!(clock.base(1), 1);
+        assert_eq!(clock.max(1), 6);
+        assert_eq!(clock.missing(1), vec![2, 3, 5]);
+        assert!(clock.contains(Dot::new(1, 4)) && !clock.contains(Dot::new(1, 3)));
+
+        clock.add(Dot::new(1, 2));
+        clock.add(Dot::new(1, 3));
+        assert_eq!(clock.base(1), 4);
+        assert_eq!(clock.missing(1), vec![5]);
+
+        assert_eq!(clock.next_dot(2), Dot::new(2, 1));
+        assert_eq!(clock.next_dot(1), Dot::new(1, 7));
+        assert_eq!(clock.to_version_vec().as_ref(), [(1, 4), (2, 1)]);
+    }
+
+    #[test]
+    fn anti_entropy() {
+        let mut a: NodeClock<u32, u32> = NodeClock::new();
+        let mut b = NodeClock::new();
+        let mut keys = DotKeyMap::new();
+        for key in ["x", "y", "z"] {
+            keys.insert(a.next_dot(1), key);
+        }
+        b.add(Dot::new(1, 2));
+
+        let missing: Vec<_> = keys.missing_for(&b).map(|(_, key)| *key).collect();
+        assert_eq!(missing, vec!["x", "z"]);
+
+        b.join(&a);
+        assert_eq!(keys.missing_for(&b).count(), 0);
+        assert_eq!(b.base(1), 3);
+
+        keys.prune(&VersionVec::from_vec(vec![(1, 2)]));
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys.get(&Dot::new(1, 3)), Some(&"z"));
+    }
+
+    #[test]
+    fn join_large_bases() {
+        let base = u64::MAX / 2;
+        let mut far: NodeClock<u32, u64> = NodeClock::new();
+        far.entries.insert(1, Entry { base, extra: vec![base + 2, base + 4].into_iter().collect() });
+        let mut near = NodeClock::new();
+        near.add(Dot::new(1, 5));
+        near.add(Dot::new(1, base + 1));
+        near.add(Dot::new(1, base + 3));
+
+        near.join(&far);
+        assert_eq!(near.base(1), base + 4);
+        assert!(near.missing(1).is_empty());
+        far.join(&near);
+        assert_eq!(far.to_version_vec().as_ref(), [(1, base + 4)]);
+    }
+}
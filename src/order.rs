@@ -0,0 +1,78 @@
+//! Deterministic total order over clocks.
+
+use core::cmp;
+
+use num_traits::Num;
+
+use VersionVec;
+
+#[derive(Clone, Debug, Default)]
+/// Orders clocks totally, so they can be kept in `BTreeSet`s or sorted
+/// into a stable listing.
+///
+/// Entries of both clocks are walked in id order, missing ones counting
+/// as zeros, and the first differing counter decides. This extends the
+/// causal order: a clock which is `Less` than another is also less here,
+/// clocks comparing as `Equal` are equal, and only concurrent clocks are
+/// ordered arbitrarily (but deterministically).
+pub struct TotalOrder<V>(pub V);
+
+impl<I, T> Ord for TotalOrder<VersionVec<I, T>> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn cmp(&self, other: &TotalOrder<VersionVec<I, T>>) -> cmp::Ordering {
+        self.0.cmp_each(&other.0)
+            .map(|e| e.1)
+            .find(|&order| order != cmp::Ordering::Equal)
+            .unwrap_or(cmp::Ordering::Equal)
+    }
+}
+
+impl<I, T> PartialOrd for TotalOrder<VersionVec<I, T>> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn partial_cmp(&self, other: &TotalOrder<VersionVec<I, T>>) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<I, T> PartialEq for TotalOrder<VersionVec<I, T>> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn eq(&self, other: &TotalOrder<VersionVec<I, T>>) -> bool {
+        self.cmp(other) == cmp::Ordering::Equal
+    }
+}
+
+impl<I, T> Eq for TotalOrder<VersionVec<I, T>> where I: Ord + Copy, T: Ord + Copy + Num {}
+
+#[cfg(test)]
+mod test {
+    use alloc::collections::BTreeSet;
+    use alloc::vec::Vec;
+    use core::cmp;
+
+    use super::TotalOrder;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn extends_causal_order() {
+        let clocks: Vec<VersionVec<u32, u32>> = vec![
+            VersionVec::from_vec(vec![(1, 1), (2, 2)]),
+            VersionVec::from_vec(vec![(1, 2)]),
+            VersionVec::from_vec(vec![(1, 1)]),
+            VersionVec::from_vec(vec![(1, 1), (2, 2), (3, 0)]),
+            VersionVec::new(),
+        ];
+
+        for a in &clocks {
+            for b in &clocks {
+                let total = TotalOrder(a.clone()).cmp(&TotalOrder(b.clone()));
+                match a.cmp(b) {
+                    Ordering::Less => assert_eq!(total, cmp::Ordering::Less),
+                    Ordering::Greater => assert_eq!(total, cmp::Ordering::Greater),
+                    Ordering::Equal => assert_eq!(total, cmp::Ordering::Equal),
+                    Ordering::Concurrent => assert!(total != cmp::Ordering::Equal)
+                }
+            }
+        }
+
+        let set: BTreeSet<_> = clocks.into_iter().map(TotalOrder).collect();
+        let listing: Vec<Vec<_>> = set.iter().map(|c| c.0.as_ref().iter().filter(|e| e.1 > 0).cloned().collect()).collect();
+        assert_eq!(listing, vec![vec![], vec![(1, 1)], vec![(1, 1), (2, 2)], vec![(1, 2)]]);
+    }
+}
@@ -0,0 +1,111 @@
+//! Policies deciding which actors a merge may introduce.
+
+use alloc::collections::BTreeSet;
+use core::error;
+use core::fmt;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// What to do with a remote entry for an actor unknown locally
+pub enum Admission {
+    /// Merge the entry
+    Allow,
+    /// Silently skip the entry
+    Drop,
+    /// Abort the whole merge
+    Reject
+}
+
+/// Consulted by `VersionVec::merge_with_policy` once per remote actor
+/// missing from the local vector.
+///
+/// Implemented for closures `FnMut(&I) -> Admission`.
+pub trait MergePolicy<I> {
+    fn admit(&mut self, id: &I) -> Admission;
+}
+
+impl<I, F> MergePolicy<I> for F where F: FnMut(&I) -> Admission {
+    fn admit(&mut self, id: &I) -> Admission {
+        self(id)
+    }
+}
+
+/// Admits only known actors, others are handled according to `unknown`
+pub struct Allowlist<I> {
+    allowed: BTreeSet<I>,
+    unknown: Admission
+}
+
+impl<I: Ord> Allowlist<I> {
+    /// Creates allowlist which applies `unknown` to actors not in `allowed`
+    pub fn new<S: IntoIterator<Item = I>>(allowed: S, unknown: Admission) -> Allowlist<I> {
+        Allowlist {
+            allowed: allowed.into_iter().collect(),
+            unknown
+        }
+    }
+
+    /// Adds `id` to allowed actors
+    pub fn allow(&mut self, id: I) {
+        self.allowed.insert(id);
+    }
+
+    /// Removes `id` from allowed actors
+    pub fn revoke(&mut self, id: &I) {
+        self.allowed.remove(id);
+    }
+}
+
+impl<I: Ord> MergePolicy<I> for Allowlist<I> {
+    fn admit(&mut self, id: &I) -> Admission {
+        if self.allowed.contains(id) {
+            Admission::Allow
+        } else {
+            self.unknown
+        }
+    }
+}
+
+/// Admits at most a fixed number of new actors over its lifetime (e.g.
+/// per client session), others are handled according to `exceeded`
+pub struct Quota {
+    remaining: usize,
+    exceeded: Admission
+}
+
+impl Quota {
+    /// Creates quota of `n` new actors
+    pub fn new(n: usize, exceeded: Admission) -> Quota {
+        Quota {
+            remaining: n,
+            exceeded
+        }
+    }
+
+    /// Number of actors which can still be admitted
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<I> MergePolicy<I> for Quota {
+    fn admit(&mut self, _id: &I) -> Admission {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            Admission::Allow
+        } else {
+            self.exceeded
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Merge was aborted because policy rejected an actor
+pub struct RejectedActor<I>(pub I);
+
+impl<I: fmt::Debug> fmt::Display for RejectedActor<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "actor {:?} rejected by merge policy", self.0)
+    }
+}
+
+impl<I: fmt::Debug> error::Error for RejectedActor<I> {}
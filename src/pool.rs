@@ -0,0 +1,78 @@
+//! Recycling clock allocations.
+//!
+//! Request handlers which build and discard clocks for every message
+//! spend a fair share of their time in the allocator. `ClockPool`
+//! keeps buffers of recycled clocks and hands them out again.
+
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use VersionVec;
+
+#[derive(Clone, Debug)]
+/// Pool of at most `capacity` spare clock buffers
+pub struct ClockPool<I, T> {
+    free: Vec<Vec<(I, T)>>,
+    capacity: usize
+}
+
+impl<I, T> ClockPool<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new(capacity: usize) -> ClockPool<I, T> {
+        ClockPool { free: Vec::with_capacity(capacity), capacity }
+    }
+
+    /// Returns an empty clock, reusing a recycled buffer if there is one
+    pub fn take(&mut self) -> VersionVec<I, T> {
+        VersionVec { inner: self.free.pop().unwrap_or_default() }
+    }
+
+    /// Returns a copy of `clock` in a pooled buffer
+    pub fn take_copy(&mut self, clock: &VersionVec<I, T>) -> VersionVec<I, T> {
+        let mut copy = self.take();
+        copy.inner.extend_from_slice(&clock.inner);
+        copy
+    }
+
+    /// Gives buffer of `clock` back to the pool. It is simply dropped if
+    /// the pool is full.
+    pub fn recycle(&mut self, clock: VersionVec<I, T>) {
+        if self.free.len() < self.capacity {
+            let mut inner = clock.inner;
+            inner.clear();
+            self.free.push(inner);
+        }
+    }
+
+    /// Number of spare buffers
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ClockPool;
+    use VersionVec;
+
+    #[test]
+    fn reuses_buffers() {
+        let mut pool = ClockPool::new(1);
+        let clock = VersionVec::from_vec(vec![(1u32, 2u32), (2, 1), (3, 5)]);
+        let copy = pool.take_copy(&clock);
+        assert_eq!(copy.as_ref(), clock.as_ref());
+
+        pool.recycle(copy);
+        pool.recycle(VersionVec::singleton(1));
+        assert_eq!(pool.len(), 1);
+
+        let reused = pool.take();
+        assert!(reused.as_ref().is_empty());
+        assert!(reused.inner.capacity() >= 3);
+        assert!(pool.is_empty());
+    }
+}
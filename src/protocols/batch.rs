@@ -0,0 +1,113 @@
+//! Batched merging for ingestion pipelines.
+
+use alloc::collections::BTreeMap;
+
+use num_traits::Num;
+
+use VersionVec;
+
+/// Collects remote clocks from peers `P` and merges them into the local
+/// clock in batches, so ingestion doesn't lock the local clock for every
+/// message.
+///
+/// Clocks from the same peer are coalesced while they wait. Once pending
+/// entries reach the high-water mark, `hook` is called with their number
+/// and `should_flush` turns true, until the next `flush_into`.
+pub struct MergeBatcher<P, I, T, H = fn(usize)> {
+    pending: BTreeMap<P, VersionVec<I, T>>,
+    entries: usize,
+    high_water: usize,
+    hook: H
+}
+
+impl<P, I, T> MergeBatcher<P, I, T> where P: Ord, I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates a batcher without high-water hook
+    pub fn new(high_water: usize) -> MergeBatcher<P, I, T> {
+        MergeBatcher::with_hook(high_water, |_| ())
+    }
+}
+
+impl<P, I, T, H> MergeBatcher<P, I, T, H> where P: Ord, I: Ord + Copy, T: Ord + Copy + Num, H: FnMut(usize) {
+    /// Creates a batcher calling `hook` when pending entries reach
+    /// `high_water`
+    pub fn with_hook(high_water: usize, hook: H) -> MergeBatcher<P, I, T, H> {
+        MergeBatcher { pending: BTreeMap::new(), entries: 0, high_water, hook }
+    }
+
+    /// Queues `clock` received from `peer`
+    pub fn push(&mut self, peer: P, clock: &VersionVec<I, T>) {
+        let below = self.entries < self.high_water;
+        let pending = self.pending.entry(peer).or_default();
+        self.entries -= pending.inner.len();
+        pending.merge(clock);
+        self.entries += pending.inner.len();
+        if below && self.entries >= self.high_water {
+            (self.hook)(self.entries);
+        }
+    }
+
+    /// True once pending entries reached the high-water mark
+    pub fn should_flush(&self) -> bool {
+        self.entries >= self.high_water
+    }
+
+    /// Merges everything pending into `clock`, returns number of peers
+    /// whose clocks were merged
+    pub fn flush_into(&mut self, clock: &mut VersionVec<I, T>) -> usize {
+        let peers = self.pending.len();
+        for pending in self.pending.values() {
+            clock.merge(pending);
+        }
+        self.pending.clear();
+        self.entries = 0;
+        peers
+    }
+
+    /// Number of peers with pending clocks
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Total number of pending entries
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::MergeBatcher;
+    use VersionVec;
+
+    #[test]
+    fn batches() {
+        let mut signals = Vec::new();
+        let mut local = VersionVec::from_vec(vec![(9u32, 1u32)]);
+        {
+            let mut batcher = MergeBatcher::with_hook(3, |n| signals.push(n));
+            batcher.push("a", &VersionVec::from_vec(vec![(1, 1), (2, 1)]));
+            batcher.push("a", &VersionVec::from_vec(vec![(1, 2), (2, 1)]));
+            assert_eq!((batcher.len(), batcher.entries()), (1, 2));
+            assert!(!batcher.should_flush());
+
+            batcher.push("b", &VersionVec::from_vec(vec![(3, 4)]));
+            batcher.push("b", &VersionVec::from_vec(vec![(4, 1)]));
+            assert!(batcher.should_flush());
+            assert_eq!(batcher.flush_into(&mut local), 2);
+            assert!(batcher.is_empty());
+            assert_eq!(batcher.entries(), 0);
+        }
+        assert_eq!(signals, [3]);
+        assert_eq!(local.as_ref(), [(1, 2), (2, 1), (3, 4), (4, 1), (9, 1)]);
+
+        let mut plain: MergeBatcher<u8, u32, u32> = MergeBatcher::new(1);
+        plain.push(1, &VersionVec::singleton(1));
+        assert!(plain.should_flush());
+    }
+}
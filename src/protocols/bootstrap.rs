@@ -0,0 +1,43 @@
+//! Joining a cluster with an existing causal history.
+
+use num_traits::Num;
+
+use {Dot, ForkError, VersionVec};
+
+/// Clock of a joined node and its first event
+pub type Bootstrapped<I, T> = (VersionVec<I, T>, Dot<I, T>);
+
+/// Sets up the clock of a node joining as `new_actor`.
+///
+/// The node adopts `seed` (e.g. the clock of a snapshot it was
+/// bootstrapped from) and records its first event, returning the clock
+/// and the dot of that event. `new_actor` must not have issued events
+/// before, otherwise the node would reissue existing dots.
+pub fn bootstrap_from<I, T>(seed: &VersionVec<I, T>, new_actor: I) -> Result<Bootstrapped<I, T>, ForkError>
+    where I: Ord + Copy, T: Ord + Copy + Num {
+    if seed.get(&new_actor).is_some_and(|counter| !counter.is_zero()) {
+        return Err(ForkError::ActorExists)
+    }
+
+    let mut clock = seed.clone();
+    let counter = clock.increment(new_actor);
+    Ok((clock, Dot::new(new_actor, counter)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::bootstrap_from;
+    use {Dot, ForkError, Ordering, VersionVec};
+
+    #[test]
+    fn joins_with_history() {
+        let seed: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 4), (2, 2), (3, 0)]);
+
+        let (clock, dot) = bootstrap_from(&seed, 3).unwrap();
+        assert_eq!(dot, Dot::new(3, 1));
+        assert_eq!(clock.cmp(&seed), Ordering::Greater);
+        assert_eq!(clock.as_ref(), [(1, 4), (2, 2), (3, 1)]);
+
+        assert_eq!(bootstrap_from(&seed, 2).err(), Some(ForkError::ActorExists));
+    }
+}
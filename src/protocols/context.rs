@@ -0,0 +1,87 @@
+//! Client half of causal context passing.
+//!
+//! On read a client receives all sibling values together with a single
+//! context covering them, and echoes that context back on its next write
+//! (see `VersionVec::advance_from_context` for the coordinator half).
+
+use core::borrow::Borrow;
+
+use num_traits::Num;
+#[cfg(feature = "codecs")]
+use num_traits::{FromPrimitive, ToPrimitive};
+
+#[cfg(feature = "codecs")]
+use alloc::string::String;
+#[cfg(feature = "codecs")]
+use codecs::token::{self, TokenError};
+use VersionVec;
+
+/// Builds contexts clients should echo back on writes
+pub struct ReadContext;
+
+impl ReadContext {
+    /// Computes the context for a read which returned `siblings`.
+    ///
+    /// That's their least upper bound: a write made with it supersedes
+    /// every returned sibling and nothing else.
+    pub fn from_siblings<I, T, V, S>(siblings: S) -> VersionVec<I, T>
+        where I: Ord + Clone, T: Ord + Copy + Num, V: Borrow<VersionVec<I, T>>, S: IntoIterator<Item = V> {
+        let mut result = VersionVec::new();
+        for sibling in siblings {
+            result.merge(sibling.borrow());
+        }
+        result
+    }
+
+    /// Encodes context as an opaque token, see `codecs::token`
+    ///
+    /// # Panics
+    ///
+    /// If an id or counter can't be represented as `u64`.
+    #[cfg(feature = "codecs")]
+    pub fn encode_token<I, T>(ctx: &VersionVec<I, T>) -> String
+        where I: ToPrimitive, T: ToPrimitive + Num {
+        token::encode(ctx)
+    }
+
+    /// Decodes context from a token produced by `encode_token`
+    #[cfg(feature = "codecs")]
+    pub fn decode_token<I, T>(token: &str) -> Result<VersionVec<I, T>, TokenError>
+        where I: Ord + Copy + FromPrimitive, T: Ord + Copy + Num + FromPrimitive {
+        token::decode(token)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use super::ReadContext;
+    use VersionVec;
+
+    #[test]
+    fn from_siblings() {
+        let siblings = vec![
+            VersionVec::from_vec(vec![(1, 3), (2, 1)]),
+            VersionVec::from_vec(vec![(1, 2), (3, 5)]),
+        ];
+
+        let ctx: VersionVec<u32, u32> = ReadContext::from_siblings(&siblings);
+        assert_eq!(ctx.as_ref(), [(1, 3), (2, 1), (3, 5)]);
+
+        let empty: VersionVec<u32, u32> = ReadContext::from_siblings(Vec::<VersionVec<u32, u32>>::new());
+        assert!(empty.as_ref().is_empty());
+    }
+
+    #[cfg(feature = "codecs")]
+    #[test]
+    fn token_round_trip() {
+        let ctx: VersionVec<u64, u32> = ReadContext::from_siblings(vec![
+            VersionVec::from_vec(vec![(1, 3), (2, 1)]),
+            VersionVec::from_vec(vec![(1, 2), (3, 5)]),
+        ]);
+
+        let token = ReadContext::encode_token(&ctx);
+        let decoded: VersionVec<u64, u32> = ReadContext::decode_token(&token).unwrap();
+        assert_eq!(decoded.as_ref(), ctx.as_ref());
+    }
+}
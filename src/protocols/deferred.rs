@@ -0,0 +1,90 @@
+//! Deferred merging of remote clocks received while the local clock
+//! can't be touched, e.g. during state transfer.
+
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use VersionVec;
+
+#[derive(Clone, Debug)]
+/// Buffer of remote clocks waiting to be merged.
+///
+/// Clocks are kept as received until their total number of entries
+/// exceeds `max_entries`, then they are coalesced into a single clock.
+/// Memory stays within `max_entries` or the size of the union of all
+/// buffered clocks, whichever is bigger.
+pub struct DeferredMerge<I, T> {
+    pending: Vec<VersionVec<I, T>>,
+    entries: usize,
+    max_entries: usize
+}
+
+impl<I, T> DeferredMerge<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new(max_entries: usize) -> DeferredMerge<I, T> {
+        DeferredMerge { pending: vec![], entries: 0, max_entries }
+    }
+
+    /// Buffers `clock`, coalescing if the bound is exceeded
+    pub fn push(&mut self, clock: VersionVec<I, T>) {
+        self.entries += clock.as_ref().len();
+        self.pending.push(clock);
+        if self.entries > self.max_entries {
+            self.coalesce();
+        }
+    }
+
+    /// Merges all buffered clocks into one
+    pub fn coalesce(&mut self) {
+        if self.pending.len() > 1 {
+            let merged: VersionVec<I, T> = self.pending.drain(..).sum();
+            self.entries = merged.as_ref().len();
+            self.pending.push(merged);
+        }
+    }
+
+    /// Merges all buffered clocks into `clock` and empties the buffer
+    pub fn flush_into(&mut self, clock: &mut VersionVec<I, T>) {
+        for pending in self.pending.drain(..) {
+            clock.merge(&pending);
+        }
+        self.entries = 0;
+    }
+
+    /// Number of buffered clocks
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Total number of entries in buffered clocks
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DeferredMerge;
+    use VersionVec;
+
+    #[test]
+    fn coalesces_under_bound() {
+        let mut deferred = DeferredMerge::new(4);
+        deferred.push(VersionVec::from_vec(vec![(1u32, 2u32), (2, 1)]));
+        deferred.push(VersionVec::from_vec(vec![(1, 3)]));
+        assert_eq!((deferred.len(), deferred.entries()), (2, 3));
+
+        deferred.push(VersionVec::from_vec(vec![(1, 1), (3, 4)]));
+        assert_eq!((deferred.len(), deferred.entries()), (1, 3));
+
+        let mut local = VersionVec::from_vec(vec![(2, 5)]);
+        deferred.flush_into(&mut local);
+        assert!(deferred.is_empty());
+        assert_eq!(deferred.entries(), 0);
+        assert_eq!(local.as_ref(), [(1, 3), (2, 5), (3, 4)]);
+    }
+}
@@ -0,0 +1,92 @@
+//! Hinted handoff: remembering writes a replica missed while it was down.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use {Dot, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Dots destined for replicas `R` which couldn't be delivered yet
+pub struct HandoffLedger<R, I, T> {
+    pending: BTreeMap<R, BTreeSet<Dot<I, T>>>
+}
+
+impl<R, I, T> HandoffLedger<R, I, T> where R: Ord, I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> HandoffLedger<R, I, T> {
+        HandoffLedger { pending: BTreeMap::new() }
+    }
+
+    /// Records that `replica` should eventually receive `dot`
+    pub fn record(&mut self, replica: R, dot: Dot<I, T>) {
+        self.pending.entry(replica).or_default().insert(dot);
+    }
+
+    /// Returns dots `replica` still lacks given its advertised clock.
+    ///
+    /// Dots covered by `their_clock` are dropped from the ledger, so the
+    /// ledger shrinks as the replica catches up.
+    pub fn pending_for(&mut self, replica: &R, their_clock: &VersionVec<I, T>) -> Vec<Dot<I, T>> {
+        let dots = match self.pending.get_mut(replica) {
+            Some(dots) => dots,
+            None => return vec![]
+        };
+
+        dots.retain(|dot| their_clock.get(&dot.actor).is_none_or(|seen| seen < dot.counter));
+        let missing: Vec<_> = dots.iter().cloned().collect();
+        if missing.is_empty() {
+            self.pending.remove(replica);
+        }
+        missing
+    }
+
+    /// Drops everything recorded for `replica`, e.g. once it's
+    /// decommissioned
+    pub fn forget(&mut self, replica: &R) {
+        self.pending.remove(replica);
+    }
+
+    /// Total number of undelivered dots
+    pub fn len(&self) -> usize {
+        self.pending.values().map(|dots| dots.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl<R, I, T> Default for HandoffLedger<R, I, T> where R: Ord, I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> HandoffLedger<R, I, T> {
+        HandoffLedger::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HandoffLedger;
+    use {Dot, VersionVec};
+
+    #[test]
+    fn prunes_as_replica_catches_up() {
+        let mut ledger = HandoffLedger::new();
+        ledger.record("b", Dot::new(1u32, 1u32));
+        ledger.record("b", Dot::new(1, 2));
+        ledger.record("b", Dot::new(2, 1));
+        ledger.record("c", Dot::new(1, 2));
+        assert_eq!(ledger.len(), 4);
+
+        let clock = VersionVec::from_vec(vec![(1, 1)]);
+        assert_eq!(ledger.pending_for(&"b", &clock), vec![Dot::new(1, 2), Dot::new(2, 1)]);
+        assert_eq!(ledger.len(), 3);
+
+        let clock = VersionVec::from_vec(vec![(1, 2), (2, 1)]);
+        assert!(ledger.pending_for(&"b", &clock).is_empty());
+        assert!(ledger.pending_for(&"a", &clock).is_empty());
+        assert_eq!(ledger.len(), 1);
+
+        ledger.forget(&"c");
+        assert!(ledger.is_empty());
+    }
+}
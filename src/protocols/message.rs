@@ -0,0 +1,122 @@
+//! Messages stamped with vector clocks.
+//!
+//! Implements the textbook send/receive rules: sender ticks its own
+//! entry *before* sending and attaches the resulting clock, receiver
+//! merges message clock *then* ticks its own entry.
+//!
+//! A clock covering a message doesn't mean the message itself was
+//! delivered, its send event could have been learned from a later
+//! message. Receivers additionally keep a `NodeClock` of delivered send
+//! events, which is what duplicates are detected by.
+
+use num_traits::Num;
+
+use node::NodeClock;
+use {Dot, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Payload together with causal metadata of its send event
+pub struct ClockedMessage<I, T, M> {
+    pub sender: I,
+    /// Send event
+    pub dot: Dot<I, T>,
+    /// Sender's clock right after send event, includes `dot`
+    pub clock: VersionVec<I, T>,
+    pub payload: M
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Result of `ClockedMessage::accept`
+pub enum AcceptOutcome<I, T> {
+    /// Message was delivered before, local clock was left untouched
+    Duplicate,
+    /// Message was merged in and receive event `receive` recorded.
+    /// `in_causal_order` is false if an earlier message of the same
+    /// sender hasn't been delivered or other events message depends on
+    /// haven't been seen locally before.
+    Accepted { receive: Dot<I, T>, in_causal_order: bool }
+}
+
+impl<I, T, M> ClockedMessage<I, T, M> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Records send event of `actor` in `clock` and wraps `payload`
+    /// into a message carrying it
+    pub fn tag(payload: M, clock: &mut VersionVec<I, T>, actor: I) -> ClockedMessage<I, T, M> {
+        let counter = clock.increment(actor);
+        ClockedMessage {
+            sender: actor,
+            dot: Dot::new(actor, counter),
+            clock: clock.clone(),
+            payload
+        }
+    }
+
+    /// Delivers `msg` to `receiver` owning `clock`: merges message clock,
+    /// records receive event and adds the send event to `delivered`
+    pub fn accept(msg: &ClockedMessage<I, T, M>, clock: &mut VersionVec<I, T>,
+                  delivered: &mut NodeClock<I, T>, receiver: I) -> AcceptOutcome<I, T> {
+        if delivered.contains(msg.dot) {
+            return AcceptOutcome::Duplicate
+        }
+
+        let in_causal_order = delivered.base(msg.dot.actor) + T::one() == msg.dot.counter &&
+            msg.clock.as_ref().iter()
+                .filter(|e| e.0 != msg.dot.actor)
+                .all(|e| clock.get(&e.0).unwrap_or_else(T::zero) >= e.1);
+
+        delivered.add(msg.dot);
+        let receive = clock.advance_from_context(&msg.clock, receiver);
+        AcceptOutcome::Accepted { receive, in_causal_order }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AcceptOutcome, ClockedMessage};
+    use {Dot, NodeClock, Ordering, VersionVec};
+
+    #[test]
+    fn send_and_receive() {
+        let mut a = VersionVec::new();
+        let mut b = VersionVec::new();
+        let mut delivered = NodeClock::new();
+
+        let m1 = ClockedMessage::tag("hello", &mut a, 1u32);
+        assert_eq!(m1.dot, Dot::new(1, 1u32));
+        assert_eq!(m1.clock.as_ref(), a.as_ref());
+
+        assert_eq!(ClockedMessage::accept(&m1, &mut b, &mut delivered, 2),
+                   AcceptOutcome::Accepted { receive: Dot::new(2, 1), in_causal_order: true });
+        assert_eq!(b.as_ref(), [(1, 1), (2, 1)]);
+        assert_eq!(b.cmp(&m1.clock), Ordering::Greater);
+
+        assert_eq!(ClockedMessage::accept(&m1, &mut b, &mut delivered, 2), AcceptOutcome::Duplicate);
+        assert_eq!(b.as_ref(), [(1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn out_of_order() {
+        let mut a = VersionVec::new();
+        let mut b = VersionVec::new();
+        let mut c = VersionVec::new();
+        let mut delivered = NodeClock::new();
+
+        let m1 = ClockedMessage::tag(1, &mut a, 1u32);
+        ClockedMessage::accept(&m1, &mut b, &mut NodeClock::new(), 2);
+        let m2 = ClockedMessage::tag(2, &mut b, 2);
+
+        // c gets b's reply before a's original message
+        assert_eq!(ClockedMessage::accept(&m2, &mut c, &mut delivered, 3),
+                   AcceptOutcome::Accepted { receive: Dot::new(3, 1), in_causal_order: false });
+
+        // a's next message still waits for m1
+        let m3 = ClockedMessage::tag(3, &mut a, 1);
+        assert_eq!(ClockedMessage::accept(&m3, &mut c, &mut delivered, 3),
+                   AcceptOutcome::Accepted { receive: Dot::new(3, 2), in_causal_order: false });
+
+        // m1 is covered by c's clock but has never been delivered
+        assert_eq!(ClockedMessage::accept(&m1, &mut c, &mut delivered, 3),
+                   AcceptOutcome::Accepted { receive: Dot::new(3, 3), in_causal_order: true });
+        assert_eq!(ClockedMessage::accept(&m1, &mut c, &mut delivered, 3), AcceptOutcome::Duplicate);
+        assert_eq!(ClockedMessage::accept(&m3, &mut c, &mut delivered, 3), AcceptOutcome::Duplicate);
+    }
+}
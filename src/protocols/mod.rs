@@ -0,0 +1,23 @@
+//! Helpers implementing common clock-passing protocols.
+//!
+//! Enabled by the `protocols` feature.
+
+pub mod batch;
+pub mod bootstrap;
+pub mod context;
+pub mod deferred;
+pub mod handoff;
+pub mod message;
+pub mod quorum;
+pub mod rsm;
+pub mod sync;
+
+pub use self::batch::MergeBatcher;
+pub use self::bootstrap::bootstrap_from;
+pub use self::context::ReadContext;
+pub use self::deferred::DeferredMerge;
+pub use self::handoff::HandoffLedger;
+pub use self::message::{AcceptOutcome, ClockedMessage};
+pub use self::quorum::{quorum_latest, QuorumResult};
+pub use self::rsm::Rsm;
+pub use self::sync::SyncSession;
@@ -0,0 +1,97 @@
+//! Resolving Dynamo-style quorum reads.
+
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use {Ordering, VersionVec};
+
+#[derive(Clone, Debug, PartialEq)]
+/// Outcome of `quorum_latest`
+pub enum QuorumResult<'a, V: 'a> {
+    /// Fewer responses than required
+    Insufficient,
+    /// A single causally latest value, carried by at least `required`
+    /// responses
+    Latest(&'a V),
+    /// A single causally latest value exists, but too few responses carry
+    /// it. Responses with `stale` indices are behind and need repair
+    /// before the value can be considered read at quorum.
+    NeedsRepair { latest: &'a V, stale: Vec<usize> },
+    /// Responses contain concurrent values, one per distinct clock
+    Siblings(Vec<&'a V>)
+}
+
+/// Determines which value a quorum read returns.
+///
+/// A response is causally latest if no other response's clock is greater.
+/// When all latest responses share a clock, the value is uniquely
+/// identified, and whether enough replicas agree on it is what decides
+/// between `Latest` and `NeedsRepair`.
+pub fn quorum_latest<I, T, V>(responses: &[(VersionVec<I, T>, V)], required: usize) -> QuorumResult<'_, V>
+    where I: Ord + Copy, T: Ord + Copy + Num {
+    if responses.len() < required || responses.is_empty() {
+        return QuorumResult::Insufficient
+    }
+
+    // representatives of distinct maximal clocks
+    let mut latest: Vec<usize> = vec![];
+    for (idx, response) in responses.iter().enumerate() {
+        let dominated = responses.iter().any(|other| other.0.cmp(&response.0) == Ordering::Greater);
+        let duplicate = latest.iter().any(|&l| responses[l].0.cmp(&response.0) == Ordering::Equal);
+        if !dominated && !duplicate {
+            latest.push(idx);
+        }
+    }
+
+    if latest.len() > 1 {
+        return QuorumResult::Siblings(latest.into_iter().map(|idx| &responses[idx].1).collect())
+    }
+
+    let clock = &responses[latest[0]].0;
+    let stale: Vec<usize> = responses.iter().enumerate()
+        .filter(|&(_, r)| r.0.cmp(clock) != Ordering::Equal)
+        .map(|(idx, _)| idx)
+        .collect();
+    let value = &responses[latest[0]].1;
+
+    if responses.len() - stale.len() >= required {
+        QuorumResult::Latest(value)
+    } else {
+        QuorumResult::NeedsRepair { latest: value, stale }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{quorum_latest, QuorumResult};
+    use VersionVec;
+
+    fn vv(entries: &[(u32, u32)]) -> VersionVec<u32, u32> {
+        VersionVec::from_vec(entries.to_vec())
+    }
+
+    #[test]
+    fn latest() {
+        let responses = vec![
+            (vv(&[(1, 2)]), "new"),
+            (vv(&[(1, 2), (2, 0)]), "new"),
+            (vv(&[(1, 1)]), "old"),
+        ];
+        assert_eq!(quorum_latest(&responses, 2), QuorumResult::Latest(&"new"));
+        assert_eq!(quorum_latest(&responses, 3), QuorumResult::NeedsRepair { latest: &"new", stale: vec![2] });
+        assert_eq!(quorum_latest(&responses, 4), QuorumResult::Insufficient);
+        assert_eq!(quorum_latest::<u32, u32, ()>(&[], 0), QuorumResult::Insufficient);
+    }
+
+    #[test]
+    fn siblings() {
+        let responses = vec![
+            (vv(&[(1, 1)]), "old"),
+            (vv(&[(1, 2)]), "a"),
+            (vv(&[(1, 1), (2, 1)]), "b"),
+            (vv(&[(1, 2)]), "a"),
+        ];
+        assert_eq!(quorum_latest(&responses, 2), QuorumResult::Siblings(vec![&"a", &"b"]));
+    }
+}
@@ -0,0 +1,141 @@
+//! Replicated state machine bookkeeping.
+//!
+//! `Rsm` stamps every command submitted locally with a dot, accepts
+//! commands received from other replicas in per-origin sequence, and
+//! tracks which clocks peers have acknowledged (a matrix clock, see
+//! `GcCoordinator`). Once a command is stable, i.e. acknowledged by all
+//! or a quorum of peers, it can be compacted into a snapshot and
+//! dropped from the log.
+//!
+//! Only the order of commands from the same origin is enforced, via
+//! `VersionVec::apply_dot`. Dependencies between origins aren't tracked,
+//! so a command may be applied before commands of other replicas it
+//! causally depends on.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use gc::GcCoordinator;
+use {ApplyOutcome, Dot, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Command log of one replica with acknowledgment tracking
+pub struct Rsm<I, T, C> {
+    local: I,
+    clock: VersionVec<I, T>,
+    acks: GcCoordinator<I, T>,
+    log: BTreeMap<Dot<I, T>, C>
+}
+
+impl<I, T, C> Rsm<I, T, C> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates replica `local` of a group of `peers`, which should
+    /// include `local` itself
+    pub fn new<P: IntoIterator<Item = I>>(local: I, peers: P) -> Rsm<I, T, C> {
+        Rsm { local, clock: VersionVec::new(), acks: GcCoordinator::new(peers), log: BTreeMap::new() }
+    }
+
+    /// Commands applied locally, own and received
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    /// Stamps locally submitted `command` with the next local dot
+    pub fn submit(&mut self, command: C) -> Dot<I, T> {
+        let dot = Dot::new(self.local, self.clock.increment(self.local));
+        self.log.insert(dot, command);
+        self.acks.observe(self.local, &self.clock);
+        dot
+    }
+
+    /// Applies `command` stamped with `dot` by another replica. It is
+    /// accepted only if it's the next command of its origin, see
+    /// `VersionVec::apply_dot`.
+    pub fn receive(&mut self, dot: Dot<I, T>, command: C) -> ApplyOutcome<T> {
+        let outcome = self.clock.apply_dot(dot);
+        if outcome == ApplyOutcome::Applied {
+            self.log.insert(dot, command);
+            self.acks.observe(self.local, &self.clock);
+        }
+        outcome
+    }
+
+    /// Records that `peer` has applied every command covered by `clock`.
+    /// Returns false for peers which aren't members.
+    pub fn acknowledge(&mut self, peer: I, clock: &VersionVec<I, T>) -> bool {
+        self.acks.observe(peer, clock)
+    }
+
+    /// True if every peer has acknowledged `dot`
+    pub fn is_stable(&self, dot: Dot<I, T>) -> bool {
+        self.acks.is_stable(dot)
+    }
+
+    /// True if at least `quorum` peers have acknowledged `dot`
+    pub fn is_quorum_stable(&self, dot: Dot<I, T>, quorum: usize) -> bool {
+        self.acks.seen_by(dot) >= quorum
+    }
+
+    /// Vector of commands acknowledged by every peer
+    pub fn frontier(&self) -> VersionVec<I, T> {
+        self.acks.stable()
+    }
+
+    /// Logged commands which aren't stable yet
+    pub fn pending(&self) -> impl Iterator<Item = (Dot<I, T>, &C)> + '_ {
+        let frontier = self.frontier();
+        self.log.iter()
+            .filter(move |e| !covers(&frontier, e.0))
+            .map(|(dot, command)| (*dot, command))
+    }
+
+    /// Removes and returns commands every peer has acknowledged
+    pub fn take_stable(&mut self) -> Vec<(Dot<I, T>, C)> {
+        let frontier = self.frontier();
+        let stable: Vec<_> = self.log.keys()
+            .filter(|dot| covers(&frontier, dot))
+            .cloned()
+            .collect();
+        stable.into_iter().filter_map(|dot| self.log.remove(&dot).map(|command| (dot, command))).collect()
+    }
+}
+
+fn covers<I, T>(frontier: &VersionVec<I, T>, dot: &Dot<I, T>) -> bool where I: Ord + Copy, T: Ord + Copy + Num {
+    frontier.get(&dot.actor).is_some_and(|counter| counter >= dot.counter)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::Rsm;
+    use {ApplyOutcome, Dot};
+
+    #[test]
+    fn stability() {
+        let mut a = Rsm::new(1u32, vec![1, 2, 3]);
+        let mut b = Rsm::new(2u32, vec![1, 2, 3]);
+        let x = a.submit("x");
+        let y = a.submit("y");
+        assert_eq!((x, y), (Dot::new(1, 1u32), Dot::new(1, 2)));
+
+        assert_eq!(b.receive(y, "y"), ApplyOutcome::Gap { expected: 1 });
+        assert_eq!(b.receive(x, "x"), ApplyOutcome::Applied);
+        assert_eq!(b.receive(x, "x"), ApplyOutcome::Duplicate);
+        let z = b.submit("z");
+        assert_eq!(z, Dot::new(2, 1));
+
+        a.acknowledge(2, b.clock());
+        assert!(a.is_quorum_stable(x, 2) && !a.is_quorum_stable(y, 2));
+        assert!(!a.is_stable(x));
+        assert!(a.take_stable().is_empty());
+
+        a.acknowledge(3, &b.clock().clone());
+        assert!(a.is_stable(x));
+        assert_eq!(a.frontier().as_ref(), [(1, 1)]);
+        assert_eq!(a.pending().map(|e| e.0).collect::<Vec<_>>(), vec![y]);
+        assert_eq!(a.take_stable(), vec![(x, "x")]);
+        assert_eq!(a.pending().map(|e| e.0).collect::<Vec<_>>(), vec![y]);
+    }
+}
@@ -0,0 +1,101 @@
+//! Sans-IO session bringing two replicas to the same clock.
+//!
+//! The session only decides which clock to send next, encoding and
+//! transport are up to the caller. One side calls `start` and sends
+//! the result, from then on both sides feed every clock they receive to
+//! `receive` and send back whatever it returns. Messages strictly
+//! alternate, and a side is done once `is_synced` is true and it has
+//! sent the last reply.
+
+use num_traits::Num;
+
+use {Ordering, VersionVec};
+
+#[derive(Clone, Debug)]
+/// One side of a clock synchronization exchange
+pub struct SyncSession<I, T> {
+    clock: VersionVec<I, T>,
+    sent: Option<VersionVec<I, T>>,
+    synced: bool
+}
+
+impl<I, T> SyncSession<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new(clock: VersionVec<I, T>) -> SyncSession<I, T> {
+        SyncSession { clock, sent: None, synced: false }
+    }
+
+    /// Opening message, sent by the side initiating the exchange
+    pub fn start(&mut self) -> VersionVec<I, T> {
+        self.send()
+    }
+
+    /// Merges clock received from the peer, returns the reply to send
+    /// if the peer doesn't know yet that both sides agree
+    pub fn receive(&mut self, peer: &VersionVec<I, T>) -> Option<VersionVec<I, T>> {
+        self.clock.merge(peer);
+        self.synced = peer.cmp(&self.clock) == Ordering::Equal;
+
+        let told = self.sent.as_ref().is_some_and(|sent| sent.cmp(&self.clock) == Ordering::Equal);
+        if self.synced && told {
+            None
+        } else {
+            Some(self.send())
+        }
+    }
+
+    /// True once the last received clock matched the local one
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    pub fn into_version_vec(self) -> VersionVec<I, T> {
+        self.clock
+    }
+
+    fn send(&mut self) -> VersionVec<I, T> {
+        self.sent = Some(self.clock.clone());
+        self.clock.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SyncSession;
+    use VersionVec;
+
+    fn run(a: VersionVec<u32, u32>, b: VersionVec<u32, u32>) -> (usize, VersionVec<u32, u32>, VersionVec<u32, u32>) {
+        let mut sides = [SyncSession::new(a), SyncSession::new(b)];
+        let mut message = Some(sides[0].start());
+        let mut messages = 0;
+        let mut turn = 1;
+        while let Some(clock) = message {
+            messages += 1;
+            message = sides[turn].receive(&clock);
+            turn = 1 - turn;
+        }
+        assert!(sides[0].is_synced() && sides[1].is_synced());
+        let [a, b] = sides;
+        (messages, a.into_version_vec(), b.into_version_vec())
+    }
+
+    #[test]
+    fn converges() {
+        let a = VersionVec::from_vec(vec![(1, 3), (2, 1)]);
+        let b = VersionVec::from_vec(vec![(2, 2), (3, 1)]);
+        let (messages, a, b) = run(a, b);
+        assert_eq!(messages, 3);
+        assert_eq!(a.as_ref(), [(1, 3), (2, 2), (3, 1)]);
+        assert_eq!(a.as_ref(), b.as_ref());
+
+        let (messages, _, b) = run(VersionVec::from_dot(1, 2), VersionVec::from_dot(1, 1));
+        assert_eq!(messages, 2);
+        assert_eq!(b.as_ref(), [(1, 2)]);
+
+        let (messages, _, _) = run(VersionVec::from_dot(1, 2), VersionVec::from_dot(1, 2));
+        assert_eq!(messages, 2);
+    }
+}
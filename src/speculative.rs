@@ -0,0 +1,157 @@
+//! Dots for speculative events.
+//!
+//! Transactional layers stamp operations before knowing whether they
+//! commit. Bumping a plain clock for them would advance it permanently,
+//! even if the transaction aborts. `SpeculativeClock` hands out
+//! tentative dots instead and only advances the committed clock once
+//! they commit, in counter order: a committed dot stays invisible until
+//! every lower dot of its actor has committed as well.
+//!
+//! Tentative dots are reference counted, several speculative operations
+//! may share one. Once the last reference is aborted the dot is
+//! returned and will be handed out again, so committed counters never
+//! have holes.
+
+use alloc::collections::BTreeMap;
+
+use num_traits::Num;
+
+use {Dot, VersionVec};
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+enum Slot {
+    /// Held by that many speculative operations
+    Held(usize),
+    /// Committed, waiting for lower dots
+    Committed
+}
+
+#[derive(Clone, Debug)]
+/// Committed clock together with outstanding tentative dots
+pub struct SpeculativeClock<I, T> {
+    committed: VersionVec<I, T>,
+    slots: BTreeMap<Dot<I, T>, Slot>
+}
+
+impl<I, T> SpeculativeClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> SpeculativeClock<I, T> {
+        SpeculativeClock::from_committed(VersionVec::new())
+    }
+
+    pub fn from_committed(committed: VersionVec<I, T>) -> SpeculativeClock<I, T> {
+        SpeculativeClock { committed, slots: BTreeMap::new() }
+    }
+
+    /// Clock of committed events, safe to expose to other replicas
+    pub fn committed(&self) -> &VersionVec<I, T> {
+        &self.committed
+    }
+
+    /// Hands out the lowest dot of `actor` which is neither committed
+    /// nor tentative, holding one reference to it
+    pub fn reserve(&mut self, actor: I) -> Dot<I, T> {
+        let mut counter = self.committed.get(&actor).unwrap_or_else(T::zero) + T::one();
+        while self.slots.contains_key(&Dot::new(actor, counter)) {
+            counter = counter + T::one();
+        }
+        let dot = Dot::new(actor, counter);
+        self.slots.insert(dot, Slot::Held(1));
+        dot
+    }
+
+    /// Adds a reference to tentative `dot`, false if it isn't tentative
+    pub fn retain(&mut self, dot: Dot<I, T>) -> bool {
+        match self.slots.get_mut(&dot) {
+            Some(Slot::Held(refs)) => {
+                *refs += 1;
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Drops a reference to tentative `dot`. Returns true if that was
+    /// the last one and the dot has been returned.
+    pub fn abort(&mut self, dot: Dot<I, T>) -> bool {
+        match self.slots.get_mut(&dot) {
+            Some(Slot::Held(refs)) if *refs > 1 => {
+                *refs -= 1;
+                false
+            },
+            Some(Slot::Held(_)) => {
+                self.slots.remove(&dot);
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Commits tentative `dot` regardless of remaining references,
+    /// false if it isn't tentative. The committed clock advances over
+    /// every contiguously committed dot.
+    pub fn commit(&mut self, dot: Dot<I, T>) -> bool {
+        match self.slots.get_mut(&dot) {
+            Some(slot @ Slot::Held(_)) => *slot = Slot::Committed,
+            _ => return false
+        }
+
+        let mut next = Dot::new(dot.actor, self.committed.get(&dot.actor).unwrap_or_else(T::zero) + T::one());
+        while self.slots.get(&next) == Some(&Slot::Committed) {
+            self.slots.remove(&next);
+            self.committed.raise(next.actor, next.counter);
+            next.counter = next.counter + T::one();
+        }
+        true
+    }
+
+    /// True if `dot` has been reserved and neither aborted nor made
+    /// visible by commit
+    pub fn is_tentative(&self, dot: Dot<I, T>) -> bool {
+        self.slots.contains_key(&dot)
+    }
+
+    /// Number of outstanding dots, tentative or committed out of order
+    pub fn pending(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+impl<I, T> Default for SpeculativeClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> SpeculativeClock<I, T> {
+        SpeculativeClock::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SpeculativeClock;
+    use Dot;
+
+    #[test]
+    fn commit_and_abort() {
+        let mut clock: SpeculativeClock<u32, u32> = SpeculativeClock::new();
+        let a = clock.reserve(1);
+        let b = clock.reserve(1);
+        let c = clock.reserve(1);
+        assert_eq!((a, b, c), (Dot::new(1, 1), Dot::new(1, 2), Dot::new(1, 3)));
+
+        assert!(clock.commit(b));
+        assert!(clock.committed().as_ref().is_empty());
+
+        assert!(clock.retain(a));
+        assert!(!clock.abort(a));
+        assert!(clock.abort(a));
+        assert!(!clock.is_tentative(a) && !clock.abort(a));
+
+        // the returned dot is handed out again
+        assert_eq!(clock.reserve(1), a);
+        assert!(clock.commit(a));
+        assert_eq!(clock.committed().as_ref(), [(1, 2)]);
+        assert!(!clock.commit(a));
+
+        assert!(clock.commit(c));
+        assert_eq!(clock.committed().as_ref(), [(1, 3)]);
+        assert_eq!(clock.pending(), 0);
+        assert_eq!(clock.reserve(1), Dot::new(1, 4));
+    }
+}
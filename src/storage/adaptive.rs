@@ -0,0 +1,239 @@
+//! Storage switching representation as the actor set grows.
+
+use alloc::collections::btree_map::{self, BTreeMap};
+use core::fmt;
+use core::slice;
+
+use num_traits::Num;
+
+use super::Storage;
+use VersionVec;
+
+/// Maximum number of entries kept inline
+pub const INLINE_CAPACITY: usize = 4;
+
+/// Number of entries beyond which B-tree is used
+pub const TREE_THRESHOLD: usize = 128;
+
+#[derive(Clone)]
+enum Repr<I, T> {
+    Inline { len: usize, entries: [Option<(I, T)>; INLINE_CAPACITY] },
+    Sorted(VersionVec<I, T>),
+    Tree(BTreeMap<I, T>)
+}
+
+/// Clock which picks its representation by size: entries are stored
+/// inline (no allocation) while there are at most `INLINE_CAPACITY` of
+/// them, then in a sorted vector, and in a B-tree once there are more
+/// than `TREE_THRESHOLD`.
+///
+/// Switching happens transparently on growth. Clocks never switch back,
+/// as actor sets rarely shrink.
+#[derive(Clone)]
+pub struct AdaptiveClock<I, T> {
+    repr: Repr<I, T>
+}
+
+/// Representation currently used by an `AdaptiveClock`
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+pub enum SizeClass {
+    Inline,
+    Sorted,
+    Tree
+}
+
+impl<I, T> AdaptiveClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates a new empty clock
+    pub fn new() -> AdaptiveClock<I, T> {
+        AdaptiveClock {
+            repr: Repr::Inline { len: 0, entries: [None; INLINE_CAPACITY] }
+        }
+    }
+
+    /// Representation currently in use
+    pub fn size_class(&self) -> SizeClass {
+        match self.repr {
+            Repr::Inline { .. } => SizeClass::Inline,
+            Repr::Sorted(_) => SizeClass::Sorted,
+            Repr::Tree(_) => SizeClass::Tree
+        }
+    }
+
+    /// Same as `VersionVec::bump_for`
+    pub fn bump_for(&mut self, id: I) {
+        self.bump(id);
+    }
+
+    fn upgrade(&mut self) {
+        let next = match self.repr {
+            Repr::Inline { len, ref entries } if len == INLINE_CAPACITY => {
+                Repr::Sorted(VersionVec { inner: entries.iter().flatten().cloned().collect() })
+            },
+            Repr::Sorted(ref v) if v.inner.len() >= TREE_THRESHOLD => {
+                Repr::Tree(v.inner.iter().cloned().collect())
+            },
+            _ => return
+        };
+        self.repr = next;
+    }
+}
+
+impl<I, T> Default for AdaptiveClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> AdaptiveClock<I, T> {
+        AdaptiveClock::new()
+    }
+}
+
+impl<I, T> From<VersionVec<I, T>> for AdaptiveClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn from(v: VersionVec<I, T>) -> AdaptiveClock<I, T> {
+        let mut result = AdaptiveClock::new();
+        for (id, counter) in v.inner {
+            result.set(id, counter);
+        }
+        result
+    }
+}
+
+impl<I: fmt::Debug, T: fmt::Debug> fmt::Debug for AdaptiveClock<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.repr {
+            Repr::Inline { len, ref entries } => f.debug_list().entries(entries[..len].iter().flatten()).finish(),
+            Repr::Sorted(ref v) => f.debug_list().entries(v.inner.iter()).finish(),
+            Repr::Tree(ref m) => f.debug_list().entries(m.iter()).finish()
+        }
+    }
+}
+
+/// Sorted entries of an `AdaptiveClock`
+pub enum Iter<'a, I: 'a, T: 'a> {
+    Slice(slice::Iter<'a, (I, T)>),
+    Inline(slice::Iter<'a, Option<(I, T)>>),
+    Tree(btree_map::Iter<'a, I, T>)
+}
+
+impl<'a, I: Copy, T: Copy> Iterator for Iter<'a, I, T> {
+    type Item = (I, T);
+
+    fn next(&mut self) -> Option<(I, T)> {
+        match *self {
+            Iter::Slice(ref mut it) => it.next().cloned(),
+            Iter::Inline(ref mut it) => it.next().and_then(|e| *e),
+            Iter::Tree(ref mut it) => it.next().map(|(id, counter)| (*id, *counter))
+        }
+    }
+}
+
+impl<I, T> Storage<I, T> for AdaptiveClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    type Iter<'a> = Iter<'a, I, T> where I: 'a, T: 'a;
+
+    fn get(&self, id: &I) -> Option<T> {
+        match self.repr {
+            Repr::Inline { len, ref entries } => {
+                entries[..len].iter().flatten().find(|e| e.0 == *id).map(|e| e.1)
+            },
+            Repr::Sorted(ref v) => Storage::get(v, id),
+            Repr::Tree(ref m) => m.get(id).cloned()
+        }
+    }
+
+    fn set(&mut self, id: I, counter: T) {
+        if self.get(&id).is_none() {
+            self.upgrade();
+        }
+
+        match self.repr {
+            Repr::Inline { ref mut len, ref mut entries } => {
+                let idx = entries[..*len].iter().flatten().position(|e| e.0 >= id).unwrap_or(*len);
+                match entries[idx] {
+                    Some(ref mut e) if e.0 == id => e.1 = counter,
+                    _ => {
+                        // there is room as otherwise clock would be upgraded
+                        entries[idx..=*len].rotate_right(1);
+                        entries[idx] = Some((id, counter));
+                        *len += 1;
+                    }
+                }
+            },
+            Repr::Sorted(ref mut v) => v.set(id, counter),
+            Repr::Tree(ref mut m) => {
+                m.insert(id, counter);
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self.repr {
+            Repr::Inline { len, .. } => len,
+            Repr::Sorted(ref v) => v.inner.len(),
+            Repr::Tree(ref m) => m.len()
+        }
+    }
+
+    fn iter(&self) -> Iter<'_, I, T> {
+        match self.repr {
+            Repr::Inline { len, ref entries } => Iter::Inline(entries[..len].iter()),
+            Repr::Sorted(ref v) => Iter::Slice(v.inner.iter()),
+            Repr::Tree(ref m) => Iter::Tree(m.iter())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use super::{AdaptiveClock, SizeClass, INLINE_CAPACITY, TREE_THRESHOLD};
+    use storage::Storage;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn switches_on_growth() {
+        let mut clock: AdaptiveClock<u32, u32> = AdaptiveClock::new();
+        let mut reference = VersionVec::new();
+
+        for id in (0..TREE_THRESHOLD as u32 + 10).rev() {
+            clock.bump_for(id);
+            clock.bump_for(id / 2);
+            reference.bump_for(id);
+            reference.bump_for(id / 2);
+
+            let expected = match clock.len() {
+                n if n <= INLINE_CAPACITY => SizeClass::Inline,
+                n if n <= TREE_THRESHOLD => SizeClass::Sorted,
+                _ => SizeClass::Tree
+            };
+            assert_eq!(clock.size_class(), expected);
+            assert_eq!(clock.iter().collect::<Vec<_>>(), reference.as_ref());
+        }
+    }
+
+    #[test]
+    fn inline_ordering() {
+        let mut clock = AdaptiveClock::new();
+        for &id in [3u8, 1, 4, 2].iter() {
+            clock.bump_for(id);
+        }
+        clock.bump_for(4);
+        assert_eq!(clock.size_class(), SizeClass::Inline);
+        assert_eq!(clock.iter().collect::<Vec<_>>(), [(1, 1u8), (2, 1), (3, 1), (4, 2)]);
+        assert_eq!(clock.get(&4), Some(2));
+        assert_eq!(clock.get(&5), None);
+    }
+
+    #[test]
+    fn interoperates() {
+        let a = AdaptiveClock::from(VersionVec::from_vec(vec![(1u32, 2u32), (2, 1)]));
+        let b = VersionVec::from_vec(vec![(1, 1), (3, 1)]);
+
+        assert_eq!(a.cmp_with(&b), Ordering::Concurrent);
+        assert_eq!(a.cmp_with(&a.clone()), Ordering::Equal);
+
+        let mut merged = a.clone();
+        merged.merge_from(&b);
+        assert_eq!(merged.cmp_with(&a), Ordering::Greater);
+        assert_eq!(merged.to_version_vec().as_ref(), [(1, 2), (2, 1), (3, 1)]);
+
+        let mut v = b.clone();
+        v.merge_from(&a);
+        assert_eq!(v.as_ref(), [(1, 2), (2, 1), (3, 1)]);
+    }
+}
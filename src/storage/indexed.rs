@@ -0,0 +1,151 @@
+//! Dense storage for clocks over interned actors.
+
+use alloc::vec::Vec;
+use core::iter::Enumerate;
+use core::slice;
+
+use num_traits::Num;
+
+use super::Storage;
+use interner::ActorHandle;
+use Ordering;
+
+/// Clock over `ActorHandle`s from an `ActorInterner`, keeping counters
+/// in a vector indexed by handle.
+///
+/// `get` and `bump` are O(1), `merge` and `cmp` walk both vectors in
+/// lockstep. Every index remembers the generation of its entry, so a
+/// handle of a released actor doesn't see the entry of its successor.
+/// On merge, an entry of a newer generation replaces an older one.
+#[derive(Clone, Debug)]
+pub struct IndexedClock<T> {
+    counters: Vec<T>,
+    /// Generation of the entry at each index, 0 if there is none
+    generations: Vec<u32>
+}
+
+impl<T> IndexedClock<T> where T: Ord + Copy + Num {
+    pub fn new() -> IndexedClock<T> {
+        IndexedClock { counters: vec![], generations: vec![] }
+    }
+
+    /// Increments counter of `handle`, returns the new value
+    pub fn bump_for(&mut self, handle: ActorHandle) -> T {
+        self.bump(handle)
+    }
+
+    /// Merges `other` in
+    pub fn merge(&mut self, other: &IndexedClock<T>) {
+        if self.counters.len() < other.counters.len() {
+            self.counters.resize(other.counters.len(), T::zero());
+            self.generations.resize(other.counters.len(), 0);
+        }
+        for idx in 0..other.counters.len() {
+            let (generation, counter) = (other.generations[idx], other.counters[idx]);
+            if generation > self.generations[idx] {
+                self.generations[idx] = generation;
+                self.counters[idx] = counter;
+            } else if generation == self.generations[idx] && counter > self.counters[idx] {
+                self.counters[idx] = counter;
+            }
+        }
+    }
+
+    /// Compares with `other`, same as `VersionVec::cmp`
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &IndexedClock<T>) -> Ordering {
+        self.cmp_with(other)
+    }
+}
+
+impl<T> Default for IndexedClock<T> where T: Ord + Copy + Num {
+    fn default() -> IndexedClock<T> {
+        IndexedClock::new()
+    }
+}
+
+/// Iterator over entries of `IndexedClock`
+pub struct Iter<'a, T: 'a> {
+    entries: Enumerate<slice::Iter<'a, u32>>,
+    counters: &'a [T]
+}
+
+impl<'a, T: Copy> Iterator for Iter<'a, T> {
+    type Item = (ActorHandle, T);
+
+    fn next(&mut self) -> Option<(ActorHandle, T)> {
+        for (idx, &generation) in &mut self.entries {
+            if generation != 0 {
+                return Some((ActorHandle::new(idx as u32, generation), self.counters[idx]))
+            }
+        }
+        None
+    }
+}
+
+impl<T> Storage<ActorHandle, T> for IndexedClock<T> where T: Ord + Copy + Num {
+    type Iter<'a> = Iter<'a, T> where T: 'a;
+
+    fn get(&self, handle: &ActorHandle) -> Option<T> {
+        let idx = handle.index() as usize;
+        match self.generations.get(idx) {
+            Some(&generation) if generation == handle.generation() => Some(self.counters[idx]),
+            _ => None
+        }
+    }
+
+    fn set(&mut self, handle: ActorHandle, counter: T) {
+        let idx = handle.index() as usize;
+        if idx >= self.counters.len() {
+            self.counters.resize(idx + 1, T::zero());
+            self.generations.resize(idx + 1, 0);
+        }
+        self.counters[idx] = counter;
+        self.generations[idx] = handle.generation();
+    }
+
+    fn len(&self) -> usize {
+        self.generations.iter().filter(|&&g| g != 0).count()
+    }
+
+    fn iter(&self) -> Iter<'_, T> {
+        Iter { entries: self.generations.iter().enumerate(), counters: &self.counters }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::IndexedClock;
+    use interner::ActorInterner;
+    use storage::Storage;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn dense_clock() {
+        let mut interner = ActorInterner::new();
+        let (a, b) = (interner.intern(10u64), interner.intern(20));
+
+        let mut x = IndexedClock::new();
+        assert_eq!(x.bump_for(a), 1);
+        let mut y = x.clone();
+        y.bump_for(b);
+        assert_eq!(x.cmp(&y), Ordering::Less);
+        x.bump_for(a);
+        assert_eq!(x.cmp(&y), Ordering::Concurrent);
+        assert_eq!(x.cmp_with(&VersionVec::from_vec(vec![(a, 2u32)])), Ordering::Equal);
+
+        x.merge(&y);
+        assert_eq!(interner.resolve_clock(&x).as_ref(), [(10, 2), (20, 1)]);
+
+        // index of 10 goes to 30, entries of the newer generation win
+        interner.release(&10);
+        let c = interner.intern(30);
+        assert_eq!(c.index(), a.index());
+        let mut z = IndexedClock::new();
+        z.set(c, 5u32);
+        x.merge(&z);
+        assert_eq!(x.get(&a), None);
+        assert_eq!(interner.resolve_clock(&x).as_ref(), [(20, 1), (30, 5)]);
+        assert_eq!(x.len(), 2);
+    }
+}
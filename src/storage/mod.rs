@@ -0,0 +1,144 @@
+//! Alternative storage backends for clocks.
+//!
+//! `VersionVec` keeps entries in a sorted vector, which is hard to beat
+//! for typical small actor sets. Backends here trade that simplicity for
+//! better behaviour in specific workloads. All of them implement
+//! `Storage`, which provides `VersionVec` semantics (missing entries are
+//! zeros) on top of a handful of primitive operations, so clocks stored
+//! in different backends can be compared and merged with each other.
+
+use core::cmp;
+use core::iter::Peekable;
+
+use num_traits::Num;
+
+use {Ordering, VersionVec};
+
+pub mod adaptive;
+pub mod indexed;
+pub mod ordered;
+#[cfg(feature = "im")]
+pub mod persistent;
+pub mod unsorted;
+
+pub use self::adaptive::AdaptiveClock;
+pub use self::indexed::IndexedClock;
+pub use self::ordered::{ActorOrder, NaturalOrder, OrderedClock};
+#[cfg(feature = "im")]
+pub use self::persistent::PersistentClock;
+pub use self::unsorted::UnsortedClock;
+
+/// Primitive operations a clock backend has to provide
+pub trait Storage<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Iterator over entries sorted by id
+    type Iter<'a>: Iterator<Item = (I, T)> where Self: 'a;
+
+    /// Returns counter for `id` if there is an entry for it
+    fn get(&self, id: &I) -> Option<T>;
+
+    /// Inserts or overwrites entry for `id`
+    fn set(&mut self, id: I, counter: T);
+
+    /// Number of stored entries, including zero ones
+    fn len(&self) -> usize;
+
+    /// Returns true if there are no entries
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Entries sorted by id
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Increments counter for `id`, returns the new value
+    fn bump(&mut self, id: I) -> T {
+        let counter = self.get(&id).unwrap_or_else(T::zero) + T::one();
+        self.set(id, counter);
+        counter
+    }
+
+    /// Merges `other` in, keeping maximum of counters per actor
+    fn merge_from<S: Storage<I, T>>(&mut self, other: &S) {
+        for (id, counter) in other.iter() {
+            match self.get(&id) {
+                Some(current) if current >= counter => (),
+                _ => self.set(id, counter)
+            }
+        }
+    }
+
+    /// Compares with another clock, same as `VersionVec::cmp`
+    fn cmp_with<S: Storage<I, T>>(&self, other: &S) -> Ordering {
+        cmp_sorted(self.iter(), other.iter())
+    }
+
+    /// Copies entries into a `VersionVec`
+    fn to_version_vec(&self) -> VersionVec<I, T> {
+        VersionVec { inner: self.iter().collect() }
+    }
+}
+
+impl<I, T> Storage<I, T> for VersionVec<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    type Iter<'a> = core::iter::Copied<core::slice::Iter<'a, (I, T)>> where I: 'a, T: 'a;
+
+    fn get(&self, id: &I) -> Option<T> {
+        self.index_of(id).ok().map(|idx| self.inner[idx].1)
+    }
+
+    fn set(&mut self, id: I, counter: T) {
+        match self.index_of(&id) {
+            Ok(idx) => self.inner[idx].1 = counter,
+            Err(idx) => self.inner.insert(idx, (id, counter))
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.inner.iter().copied()
+    }
+
+    fn merge_from<S: Storage<I, T>>(&mut self, other: &S) {
+        self.merge(&other.to_version_vec())
+    }
+}
+
+/// Compares two sorted entry sequences with `VersionVec::cmp` semantics
+pub(crate) fn cmp_sorted<I, T, A, B>(left: A, right: B) -> Ordering
+    where I: Ord, T: Ord + Num, A: Iterator<Item = (I, T)>, B: Iterator<Item = (I, T)> {
+    let mut left: Peekable<A> = left.peekable();
+    let mut right: Peekable<B> = right.peekable();
+    let mut result = Ordering::Equal;
+
+    loop {
+        let order = match (left.peek(), right.peek()) {
+            (None, None) => return result,
+            (Some(_), None) => cmp::Ordering::Less,
+            (None, Some(_)) => cmp::Ordering::Greater,
+            (Some(l), Some(r)) => l.0.cmp(&r.0)
+        };
+
+        let elementary = match order {
+            cmp::Ordering::Less => {
+                let (_, counter) = left.next().unwrap();
+                counter.cmp(&T::zero())
+            },
+            cmp::Ordering::Greater => {
+                let (_, counter) = right.next().unwrap();
+                T::zero().cmp(&counter)
+            },
+            cmp::Ordering::Equal => {
+                let (_, l) = left.next().unwrap();
+                let (_, r) = right.next().unwrap();
+                l.cmp(&r)
+            }
+        };
+
+        result.eat(elementary);
+        if result == Ordering::Concurrent {
+            return result
+        }
+    }
+}
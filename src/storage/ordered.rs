@@ -0,0 +1,188 @@
+//! Storage sorted by a custom actor order.
+
+use alloc::vec::Vec;
+use core::cmp;
+use core::marker::PhantomData;
+use core::slice;
+
+use num_traits::Num;
+
+use Ordering;
+
+/// Order of actor ids. Ids comparing `Equal` are the same actor.
+pub trait ActorOrder<I> {
+    fn cmp(a: &I, b: &I) -> cmp::Ordering;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+/// Order given by `Ord`
+pub struct NaturalOrder;
+
+impl<I: Ord> ActorOrder<I> for NaturalOrder {
+    fn cmp(a: &I, b: &I) -> cmp::Ordering {
+        a.cmp(b)
+    }
+}
+
+/// Clock whose entries are kept sorted by `O` instead of `Ord`.
+///
+/// For id types with a domain order different from `Ord`, such as
+/// case-insensitive names. Since other backends are sorted by `Ord`, it
+/// doesn't implement `Storage` and only works with clocks of its own
+/// kind. Ids are only required to be `Clone`.
+#[derive(Clone, Debug)]
+pub struct OrderedClock<I, T, O> {
+    entries: Vec<(I, T)>,
+    order: PhantomData<O>
+}
+
+impl<I, T, O> OrderedClock<I, T, O> where I: Clone, T: Ord + Copy + Num, O: ActorOrder<I> {
+    pub fn new() -> OrderedClock<I, T, O> {
+        OrderedClock { entries: vec![], order: PhantomData }
+    }
+
+    /// Constructs a clock from tuples (id, version). Of entries for the
+    /// same actor the largest counter is kept.
+    pub fn from_vec(v: Vec<(I, T)>) -> OrderedClock<I, T, O> {
+        let mut clock = OrderedClock::new();
+        for (id, counter) in v {
+            clock.raise(id, counter);
+        }
+        clock
+    }
+
+    fn position(&self, id: &I) -> Result<usize, usize> {
+        self.entries.binary_search_by(|e| O::cmp(&e.0, id))
+    }
+
+    /// Returns the value of counter with id if it exists
+    pub fn get(&self, id: &I) -> Option<T> {
+        self.position(id).ok().map(|idx| self.entries[idx].1)
+    }
+
+    /// Increments counter of `id`, returns the new value
+    pub fn bump_for(&mut self, id: I) -> T {
+        match self.position(&id) {
+            Ok(idx) => {
+                self.entries[idx].1 = self.entries[idx].1 + T::one();
+                self.entries[idx].1
+            },
+            Err(idx) => {
+                self.entries.insert(idx, (id, T::one()));
+                T::one()
+            }
+        }
+    }
+
+    fn raise(&mut self, id: I, counter: T) {
+        match self.position(&id) {
+            Ok(idx) => self.entries[idx].1 = cmp::max(self.entries[idx].1, counter),
+            Err(idx) => self.entries.insert(idx, (id, counter))
+        }
+    }
+
+    /// Merge in-place
+    pub fn merge(&mut self, other: &OrderedClock<I, T, O>) {
+        let mut merged = Vec::with_capacity(cmp::max(self.entries.len(), other.entries.len()));
+        let mut left = self.entries.drain(..).peekable();
+        let mut right = other.entries.iter().peekable();
+        loop {
+            let order = match (left.peek(), right.peek()) {
+                (None, None) => break,
+                (Some(_), None) => cmp::Ordering::Less,
+                (None, Some(_)) => cmp::Ordering::Greater,
+                (Some(l), Some(r)) => O::cmp(&l.0, &r.0)
+            };
+            match order {
+                cmp::Ordering::Less => merged.extend(left.next()),
+                cmp::Ordering::Greater => merged.extend(right.next().cloned()),
+                cmp::Ordering::Equal => {
+                    let (id, l) = left.next().unwrap();
+                    let r = right.next().unwrap().1;
+                    merged.push((id, cmp::max(l, r)));
+                }
+            }
+        }
+        drop(left);
+        self.entries = merged;
+    }
+
+    /// Compares 2 clocks, same as `VersionVec::cmp`
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &OrderedClock<I, T, O>) -> Ordering {
+        let (mut left, mut right) = (self.entries.iter().peekable(), other.entries.iter().peekable());
+        let mut result = Ordering::Equal;
+        loop {
+            let order = match (left.peek(), right.peek()) {
+                (None, None) => return result,
+                (Some(_), None) => cmp::Ordering::Less,
+                (None, Some(_)) => cmp::Ordering::Greater,
+                (Some(l), Some(r)) => O::cmp(&l.0, &r.0)
+            };
+            let elementary = match order {
+                cmp::Ordering::Less => left.next().unwrap().1.cmp(&T::zero()),
+                cmp::Ordering::Greater => T::zero().cmp(&right.next().unwrap().1),
+                cmp::Ordering::Equal => left.next().unwrap().1.cmp(&right.next().unwrap().1)
+            };
+            result.eat(elementary);
+            if result == Ordering::Concurrent {
+                return result
+            }
+        }
+    }
+
+    /// Entries in actor order
+    pub fn iter(&self) -> slice::Iter<'_, (I, T)> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<I, T, O> Default for OrderedClock<I, T, O> where I: Clone, T: Ord + Copy + Num, O: ActorOrder<I> {
+    fn default() -> OrderedClock<I, T, O> {
+        OrderedClock::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use core::cmp;
+
+    use super::{ActorOrder, NaturalOrder, OrderedClock};
+    use Ordering;
+
+    struct CaseInsensitive;
+
+    impl ActorOrder<String> for CaseInsensitive {
+        fn cmp(a: &String, b: &String) -> cmp::Ordering {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        }
+    }
+
+    #[test]
+    fn custom_order() {
+        let s = |s: &str| s.to_string();
+        let mut a: OrderedClock<String, u32, CaseInsensitive> = OrderedClock::from_vec(vec![(s("b"), 1), (s("Alice"), 2)]);
+        let b = OrderedClock::from_vec(vec![(s("ALICE"), 3), (s("Bob"), 1)]);
+        assert_eq!(a.get(&s("alice")), Some(2));
+        assert_eq!(a.bump_for(s("B")), 2);
+        assert_eq!(a.cmp(&b), Ordering::Concurrent);
+
+        a.merge(&b);
+        let entries: Vec<_> = a.iter().map(|e| (e.0.as_str(), e.1)).collect();
+        assert_eq!(entries, [("Alice", 3), ("b", 2), ("Bob", 1)]);
+        assert_eq!(a.cmp(&b), Ordering::Greater);
+
+        let natural: OrderedClock<u32, u32, NaturalOrder> = OrderedClock::from_vec(vec![(2, 1), (1, 1), (2, 3)]);
+        assert_eq!(natural.iter().cloned().collect::<Vec<_>>(), [(1, 1), (2, 3)]);
+    }
+}
@@ -0,0 +1,109 @@
+//! Storage with cheap snapshots.
+//!
+//! Enabled by the `im` feature.
+
+use core::fmt;
+
+use im::ordmap::{self, OrdMap};
+use num_traits::Num;
+
+use super::Storage;
+use VersionVec;
+
+/// Clock backed by a persistent ordered map.
+///
+/// Clones share structure with the original, so taking a snapshot is
+/// O(1) and the first update after it costs O(log n) instead of copying
+/// the whole vector. Suits applications keeping many historical clocks
+/// around, e.g. for undo.
+#[derive(Clone)]
+pub struct PersistentClock<I: Ord + Clone, T: Clone> {
+    entries: OrdMap<I, T>
+}
+
+impl<I, T> PersistentClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates a new empty clock
+    pub fn new() -> PersistentClock<I, T> {
+        PersistentClock { entries: OrdMap::new() }
+    }
+
+    /// Increments counter of `id`, returns the new value
+    pub fn bump_for(&mut self, id: I) -> T {
+        self.bump(id)
+    }
+
+    /// Returns a copy sharing structure with `self`
+    pub fn snapshot(&self) -> PersistentClock<I, T> {
+        self.clone()
+    }
+}
+
+impl<I, T> Default for PersistentClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> PersistentClock<I, T> {
+        PersistentClock::new()
+    }
+}
+
+impl<I, T> From<VersionVec<I, T>> for PersistentClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn from(v: VersionVec<I, T>) -> PersistentClock<I, T> {
+        PersistentClock { entries: v.inner.into_iter().collect() }
+    }
+}
+
+impl<I: Ord + Clone + fmt::Debug, T: Clone + fmt::Debug> fmt::Debug for PersistentClock<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.entries.iter()).finish()
+    }
+}
+
+/// Sorted entries of a `PersistentClock`
+pub struct Iter<'a, I: 'a, T: 'a> {
+    inner: ordmap::Iter<'a, I, T>
+}
+
+impl<'a, I: Ord + Copy, T: Copy> Iterator for Iter<'a, I, T> {
+    type Item = (I, T);
+
+    fn next(&mut self) -> Option<(I, T)> {
+        self.inner.next().map(|(id, counter)| (*id, *counter))
+    }
+}
+
+impl<I, T> Storage<I, T> for PersistentClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    type Iter<'a> = Iter<'a, I, T> where I: 'a, T: 'a;
+
+    fn get(&self, id: &I) -> Option<T> {
+        self.entries.get(id).cloned()
+    }
+
+    fn set(&mut self, id: I, counter: T) {
+        self.entries.insert(id, counter);
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn iter(&self) -> Iter<'_, I, T> {
+        Iter { inner: self.entries.iter() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PersistentClock;
+    use storage::Storage;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn snapshots() {
+        let mut clock = PersistentClock::from(VersionVec::from_vec(vec![(1u32, 2u32), (3, 1)]));
+        let snapshot = clock.snapshot();
+        clock.bump_for(2);
+        clock.bump_for(1);
+
+        assert_eq!(snapshot.to_version_vec().as_ref(), [(1, 2), (3, 1)]);
+        assert_eq!(clock.to_version_vec().as_ref(), [(1, 3), (2, 1), (3, 1)]);
+        assert_eq!(clock.cmp_with(&snapshot), Ordering::Greater);
+    }
+}
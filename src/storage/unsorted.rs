@@ -0,0 +1,187 @@
+//! Storage for write-dominated workloads.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::{self, Vec};
+use core::fmt;
+use core::slice;
+
+use num_traits::Num;
+
+use super::Storage;
+use VersionVec;
+
+/// Clock keeping entries in insertion order.
+///
+/// New actors are appended and removed ones swapped out, so neither
+/// shifts other entries. Entries are found through an id to position
+/// index, which makes lookups, bumps, inserts and removals O(log n)
+/// regardless of order; a sorted `VersionVec` pays O(n) shifting for
+/// every new actor instead.
+///
+/// Entries get sorted lazily: `ensure_sorted` sorts in place, O(n log n),
+/// and should be called on the read path, before comparisons and
+/// serialization. `Storage::iter` on an unsorted clock has to sort a
+/// copy instead.
+#[derive(Clone)]
+pub struct UnsortedClock<I, T> {
+    entries: Vec<(I, T)>,
+    index: BTreeMap<I, usize>,
+    sorted: bool
+}
+
+impl<I, T> UnsortedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates a new empty clock
+    pub fn new() -> UnsortedClock<I, T> {
+        UnsortedClock { entries: vec![], index: BTreeMap::new(), sorted: true }
+    }
+
+    /// Increments counter of `id`, returns the new value
+    pub fn bump_for(&mut self, id: I) -> T {
+        self.bump(id)
+    }
+
+    /// Removes entry of `id` by swapping the last entry into its place
+    pub fn swap_remove_actor(&mut self, id: I) -> Option<T> {
+        let idx = self.index.remove(&id)?;
+        let removed = self.entries.swap_remove(idx);
+        if idx < self.entries.len() {
+            self.index.insert(self.entries[idx].0, idx);
+            self.sorted = false;
+        }
+        Some(removed.1)
+    }
+
+    /// True if entries are currently in id order
+    pub fn is_sorted(&self) -> bool {
+        self.sorted
+    }
+
+    /// Sorts entries by id, cheap if they already are
+    pub fn ensure_sorted(&mut self) {
+        if !self.sorted {
+            self.entries.sort_unstable_by_key(|e| e.0);
+            self.index = index_of(&self.entries);
+            self.sorted = true;
+        }
+    }
+
+    /// Sorts entries and converts into `VersionVec` without copying
+    pub fn into_version_vec(mut self) -> VersionVec<I, T> {
+        self.ensure_sorted();
+        VersionVec { inner: self.entries }
+    }
+
+    fn position(&self, id: I) -> Option<usize> {
+        self.index.get(&id).cloned()
+    }
+}
+
+fn index_of<I: Ord + Copy, T>(entries: &[(I, T)]) -> BTreeMap<I, usize> {
+    entries.iter().enumerate().map(|(idx, e)| (e.0, idx)).collect()
+}
+
+impl<I, T> Default for UnsortedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> UnsortedClock<I, T> {
+        UnsortedClock::new()
+    }
+}
+
+impl<I, T> From<VersionVec<I, T>> for UnsortedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn from(v: VersionVec<I, T>) -> UnsortedClock<I, T> {
+        UnsortedClock { index: index_of(&v.inner), entries: v.inner, sorted: true }
+    }
+}
+
+impl<I: fmt::Debug, T: fmt::Debug> fmt::Debug for UnsortedClock<I, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.entries.iter()).finish()
+    }
+}
+
+/// Sorted entries of an `UnsortedClock`
+pub enum Iter<'a, I: 'a, T: 'a> {
+    Slice(slice::Iter<'a, (I, T)>),
+    Sorted(vec::IntoIter<(I, T)>)
+}
+
+impl<'a, I: Copy, T: Copy> Iterator for Iter<'a, I, T> {
+    type Item = (I, T);
+
+    fn next(&mut self) -> Option<(I, T)> {
+        match *self {
+            Iter::Slice(ref mut it) => it.next().cloned(),
+            Iter::Sorted(ref mut it) => it.next()
+        }
+    }
+}
+
+impl<I, T> Storage<I, T> for UnsortedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    type Iter<'a> = Iter<'a, I, T> where I: 'a, T: 'a;
+
+    fn get(&self, id: &I) -> Option<T> {
+        self.position(*id).map(|idx| self.entries[idx].1)
+    }
+
+    fn set(&mut self, id: I, counter: T) {
+        match self.position(id) {
+            Some(idx) => self.entries[idx].1 = counter,
+            None => {
+                if self.entries.last().is_some_and(|last| last.0 > id) {
+                    self.sorted = false;
+                }
+                self.index.insert(id, self.entries.len());
+                self.entries.push((id, counter));
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn iter(&self) -> Iter<'_, I, T> {
+        if self.sorted {
+            Iter::Slice(self.entries.iter())
+        } else {
+            let mut entries = self.entries.clone();
+            entries.sort_unstable_by_key(|e| e.0);
+            Iter::Sorted(entries.into_iter())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UnsortedClock;
+    use storage::Storage;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn lazy_sorting() {
+        let mut clock = UnsortedClock::new();
+        clock.bump_for(3u32);
+        clock.bump_for(1);
+        clock.bump_for(2);
+        clock.bump_for(1);
+        assert!(!clock.is_sorted());
+        assert_eq!(clock.get(&1), Some(2u32));
+
+        let vv = VersionVec::from_vec(vec![(1, 2), (2, 1), (3, 1)]);
+        assert_eq!(clock.cmp_with(&vv), Ordering::Equal);
+
+        assert_eq!(clock.swap_remove_actor(3), Some(1));
+        assert_eq!(clock.swap_remove_actor(3), None);
+        assert_eq!((clock.get(&1), clock.get(&2)), (Some(2), Some(1)));
+        clock.ensure_sorted();
+        assert!(clock.is_sorted());
+        assert_eq!(clock.clone().into_version_vec().as_ref(), [(1, 2), (2, 1)]);
+
+        let mut sorted = UnsortedClock::from(vv);
+        assert_eq!(sorted.swap_remove_actor(3), Some(1));
+        assert!(sorted.is_sorted());
+        sorted.bump_for(4);
+        assert!(sorted.is_sorted());
+        sorted.bump_for(1);
+        assert_eq!(sorted.into_version_vec().as_ref(), [(1, 3), (2, 1), (4, 1)]);
+    }
+}
@@ -0,0 +1,60 @@
+//! Sources of current time for timestamped features.
+//!
+//! Code which needs "now" takes a `TimeSource` rather than reading the
+//! system clock, so simulations and tests can drive time themselves
+//! with `VirtualTime`. `SystemTimeSource`, available with the `std`
+//! feature, reads wall-clock time.
+
+use core::cell::Cell;
+
+/// Provides current time as `u64`, milliseconds unless documented
+/// otherwise by the implementation
+pub trait TimeSource {
+    fn now(&self) -> u64;
+}
+
+impl<F: Fn() -> u64> TimeSource for F {
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+/// Time which moves only when told to
+pub struct VirtualTime {
+    now: Cell<u64>
+}
+
+impl VirtualTime {
+    pub fn new(start: u64) -> VirtualTime {
+        VirtualTime { now: Cell::new(start) }
+    }
+
+    pub fn set(&self, now: u64) {
+        self.now.set(now);
+    }
+
+    pub fn advance(&self, by: u64) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl TimeSource for VirtualTime {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Default)]
+/// Milliseconds since the Unix epoch, from `std::time::SystemTime`
+pub struct SystemTimeSource;
+
+#[cfg(feature = "std")]
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> u64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        // clocks set before 1970 are treated as the epoch itself
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+    }
+}
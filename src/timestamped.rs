@@ -0,0 +1,133 @@
+//! Clocks remembering when each entry last advanced.
+//!
+//! Timestamps are plain `u64` values, typically milliseconds since an
+//! epoch chosen by the caller, who supplies the current time to every
+//! mutating call, either directly or through a `TimeSource`. They are
+//! local bookkeeping only and take no part in comparisons.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use num_traits::Num;
+
+use time::TimeSource;
+use VersionVec;
+
+#[derive(Clone, Debug)]
+/// Version vector with per-entry last-update timestamps
+pub struct TimestampedClock<I, T> {
+    clock: VersionVec<I, T>,
+    updated: BTreeMap<I, u64>
+}
+
+impl<I, T> TimestampedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new() -> TimestampedClock<I, T> {
+        TimestampedClock { clock: VersionVec::new(), updated: BTreeMap::new() }
+    }
+
+    /// Wraps `clock`, treating all of its entries as updated at `now`
+    pub fn from_clock(clock: VersionVec<I, T>, now: u64) -> TimestampedClock<I, T> {
+        let updated = clock.inner.iter().map(|e| (e.0, now)).collect();
+        TimestampedClock { clock, updated }
+    }
+
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    /// Time entry of `id` last advanced
+    pub fn last_update(&self, id: I) -> Option<u64> {
+        self.updated.get(&id).cloned()
+    }
+
+    /// Bumps counter of `id` at `now`
+    pub fn bump_for(&mut self, id: I, now: u64) {
+        self.clock.bump_for(id);
+        self.updated.insert(id, now);
+    }
+
+    /// Bumps counter of `id` at current time of `time`
+    pub fn bump_using<S: TimeSource>(&mut self, id: I, time: &S) {
+        self.bump_for(id, time.now())
+    }
+
+    /// Merges `other` in, stamping every entry it advances with `now`
+    pub fn merge(&mut self, other: &VersionVec<I, T>, now: u64) {
+        for entry in other.inner.iter() {
+            if self.clock.get(&entry.0).is_none_or(|counter| counter < entry.1) {
+                self.updated.insert(entry.0, now);
+            }
+        }
+        self.clock.merge(other);
+    }
+
+    /// Like `merge`, stamping with current time of `time`
+    pub fn merge_using<S: TimeSource>(&mut self, other: &VersionVec<I, T>, time: &S) {
+        self.merge(other, time.now())
+    }
+
+    /// Like `staleness`, as of current time of `time`
+    pub fn staleness_using<S: TimeSource>(&self, time: &S) -> Option<u64> {
+        self.staleness(time.now())
+    }
+
+    /// Time since the least recently advanced entry was updated, `None`
+    /// for an empty clock
+    pub fn staleness(&self, now: u64) -> Option<u64> {
+        self.updated.values().min().map(|&oldest| now.saturating_sub(oldest))
+    }
+
+    /// Actors which haven't advanced since `threshold`
+    pub fn stale_actors(&self, threshold: u64) -> Vec<I> {
+        self.updated.iter()
+            .filter(|&(_, &updated)| updated < threshold)
+            .map(|(&id, _)| id)
+            .collect()
+    }
+}
+
+impl<I, T> Default for TimestampedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn default() -> TimestampedClock<I, T> {
+        TimestampedClock::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TimestampedClock;
+    use time::VirtualTime;
+    use VersionVec;
+
+    #[test]
+    fn staleness() {
+        let mut clock = TimestampedClock::from_clock(VersionVec::from_vec(vec![(1u32, 1u32), (2, 1)]), 100);
+        assert_eq!(clock.staleness(150), Some(50));
+
+        clock.bump_for(1, 200);
+        clock.merge(&VersionVec::from_vec(vec![(2, 1), (3, 4)]), 300);
+        assert_eq!(clock.last_update(1), Some(200));
+        assert_eq!(clock.last_update(2), Some(100));
+        assert_eq!(clock.last_update(3), Some(300));
+        assert_eq!(clock.clock().get(&3), Some(4));
+
+        assert_eq!(clock.staleness(400), Some(300));
+        assert_eq!(clock.stale_actors(250), vec![1, 2]);
+        assert!(clock.stale_actors(100).is_empty());
+        assert_eq!(TimestampedClock::<u32, u32>::new().staleness(400), None);
+    }
+
+    #[test]
+    fn virtual_time() {
+        let time = VirtualTime::new(10);
+        let mut clock = TimestampedClock::new();
+        clock.bump_using(1u32, &time);
+        time.advance(5);
+        clock.merge_using(&VersionVec::from_vec(vec![(1, 1u32), (2, 1)]), &time);
+        assert_eq!(clock.last_update(1), Some(10));
+        assert_eq!(clock.last_update(2), Some(15));
+
+        time.set(40);
+        assert_eq!(clock.staleness_using(&time), Some(30));
+        assert_eq!(clock.staleness_using(&|| 12), Some(2));
+    }
+}
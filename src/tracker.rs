@@ -0,0 +1,190 @@
+//! Incremental comparison against a slowly changing reference clock.
+
+use alloc::vec::Vec;
+use core::cmp;
+
+use num_traits::Num;
+
+use {Dot, Ordering, VersionVec};
+
+struct Tracked<I, T> {
+    clock: VersionVec<I, T>,
+    /// Number of actors where reference is ahead of tracked clock
+    ahead: usize,
+    /// Number of actors where reference is behind tracked clock
+    behind: usize
+}
+
+impl<I, T> Tracked<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    fn update(&mut self, actor: I, old: T, new: T) {
+        let counter = self.clock.get(&actor).unwrap_or_else(T::zero);
+
+        match old.cmp(&counter) {
+            cmp::Ordering::Greater => self.ahead -= 1,
+            cmp::Ordering::Less => self.behind -= 1,
+            cmp::Ordering::Equal => ()
+        }
+        match new.cmp(&counter) {
+            cmp::Ordering::Greater => self.ahead += 1,
+            cmp::Ordering::Less => self.behind += 1,
+            cmp::Ordering::Equal => ()
+        }
+    }
+
+    fn ordering(&self) -> Ordering {
+        match (self.ahead > 0, self.behind > 0) {
+            (false, false) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (true, true) => Ordering::Concurrent
+        }
+    }
+}
+
+/// Keeps results of comparing a reference clock against a set of tracked
+/// clocks up to date as the reference advances.
+///
+/// A bump costs O(tracked clocks × log n) instead of a full `cmp` per
+/// tracked clock, plus shifting the reference's entries if it gains a
+/// new actor. `on_merge` pays that for every entry of the merged clock.
+pub struct Tracker<I, T> {
+    reference: VersionVec<I, T>,
+    tracked: Vec<Option<Tracked<I, T>>>
+}
+
+impl<I, T> Tracker<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Creates a tracker for `reference` with no tracked clocks
+    pub fn new(reference: VersionVec<I, T>) -> Tracker<I, T> {
+        Tracker {
+            reference,
+            tracked: vec![]
+        }
+    }
+
+    /// Current state of the reference clock
+    pub fn reference(&self) -> &VersionVec<I, T> {
+        &self.reference
+    }
+
+    /// Starts tracking `clock`, returns a handle to query its ordering
+    pub fn track(&mut self, clock: VersionVec<I, T>) -> usize {
+        let mut tracked = Tracked {
+            clock,
+            ahead: 0,
+            behind: 0
+        };
+
+        for (left, right) in union(&self.reference, &tracked.clock) {
+            match left.cmp(&right) {
+                cmp::Ordering::Greater => tracked.ahead += 1,
+                cmp::Ordering::Less => tracked.behind += 1,
+                cmp::Ordering::Equal => ()
+            }
+        }
+
+        match self.tracked.iter().position(|t| t.is_none()) {
+            Some(idx) => {
+                self.tracked[idx] = Some(tracked);
+                idx
+            },
+            None => {
+                self.tracked.push(Some(tracked));
+                self.tracked.len() - 1
+            }
+        }
+    }
+
+    /// Stops tracking clock with `handle` and returns it. Handle might
+    /// be reused by later `track` calls.
+    pub fn untrack(&mut self, handle: usize) -> Option<VersionVec<I, T>> {
+        self.tracked.get_mut(handle).and_then(|t| t.take()).map(|t| t.clock)
+    }
+
+    /// Returns how reference compares to clock with `handle`,
+    /// i.e. `reference.cmp(tracked)`
+    pub fn ordering(&self, handle: usize) -> Option<Ordering> {
+        self.tracked.get(handle).and_then(|t| t.as_ref()).map(|t| t.ordering())
+    }
+
+    /// Records that reference has advanced to `dot`. Dots which are
+    /// already covered by reference are ignored.
+    pub fn on_bump(&mut self, dot: Dot<I, T>) {
+        let old = self.reference.get(&dot.actor).unwrap_or_else(T::zero);
+        if dot.counter <= old {
+            return
+        }
+
+        self.reference.raise(dot.actor, dot.counter);
+        for tracked in self.tracked.iter_mut().flatten() {
+            tracked.update(dot.actor, old, dot.counter);
+        }
+    }
+
+    /// Merges `other` into reference
+    pub fn on_merge(&mut self, other: &VersionVec<I, T>) {
+        for &(actor, counter) in other.as_ref() {
+            self.on_bump(Dot::new(actor, counter));
+        }
+    }
+}
+
+/// Pairs of (left, right) counters over the union of actors
+fn union<I, T>(left: &VersionVec<I, T>, right: &VersionVec<I, T>) -> Vec<(T, T)>
+    where I: Ord + Copy, T: Ord + Copy + Num {
+    left.merge_join_iter(right).map(|e| e.counters_or(T::zero())).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use super::Tracker;
+    use {Dot, Ordering, VersionVec};
+
+    #[test]
+    fn follows_reference() {
+        let mut tracker = Tracker::new(VersionVec::from_vec(vec![(1u32, 2u32), (2, 1)]));
+        let a = tracker.track(VersionVec::from_vec(vec![(1, 2), (2, 1)]));
+        let b = tracker.track(VersionVec::from_vec(vec![(1, 3), (2, 0)]));
+        let c = tracker.track(VersionVec::from_vec(vec![(3, 1)]));
+
+        assert_eq!(tracker.ordering(a), Some(Ordering::Equal));
+        assert_eq!(tracker.ordering(b), Some(Ordering::Concurrent));
+        assert_eq!(tracker.ordering(c), Some(Ordering::Concurrent));
+
+        tracker.on_bump(Dot::new(1, 3));
+        assert_eq!(tracker.ordering(a), Some(Ordering::Greater));
+        assert_eq!(tracker.ordering(b), Some(Ordering::Greater));
+
+        tracker.on_bump(Dot::new(1, 1));
+        assert_eq!(tracker.reference().get(&1), Some(3));
+
+        tracker.on_merge(&VersionVec::from_vec(vec![(3, 1)]));
+        assert_eq!(tracker.ordering(c), Some(Ordering::Greater));
+
+        for handle in &[a, b, c] {
+            let clock = tracker.untrack(*handle).unwrap();
+            assert_eq!(tracker.reference().cmp(&clock), Ordering::Greater);
+        }
+        assert_eq!(tracker.ordering(a), None);
+        assert_eq!(tracker.track(VersionVec::new()), a);
+    }
+
+    #[test]
+    fn matches_full_cmp() {
+        let mut tracker = Tracker::new(VersionVec::new());
+        let clocks = [
+            VersionVec::from_vec(vec![(1u32, 1u32), (3, 2)]),
+            VersionVec::from_vec(vec![(2, 2), (3, 0)]),
+            VersionVec::from_vec(vec![(1, 2), (2, 1), (3, 1)]),
+        ];
+        let handles: Vec<_> = clocks.iter().map(|c| tracker.track(c.clone())).collect();
+
+        let dots = [(2, 1), (1, 1), (3, 1), (2, 2), (1, 2), (3, 2), (1, 3)];
+        for &(actor, counter) in dots.iter() {
+            tracker.on_bump(Dot::new(actor, counter));
+            for (clock, handle) in clocks.iter().zip(&handles) {
+                assert_eq!(tracker.ordering(*handle), Some(tracker.reference().cmp(clock)));
+            }
+        }
+    }
+}
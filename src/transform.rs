@@ -0,0 +1,108 @@
+//! Bulk counter transforms for migrations.
+//!
+//! Clocks imported from systems whose counters started at a different
+//! base, or counted in different units (e.g. millisecond timestamps
+//! used as pseudo-counters), can be rewritten with `shift_counters`,
+//! `scale_counters` or an arbitrary `remap_counters`.
+//!
+//! A transform keeps comparisons intact only if it is strictly
+//! increasing and keeps positive counters positive, zero entries are
+//! left alone as they stand for missing ones. Each call validates that on
+//! the counters of the clock it rewrites and fails without modifying
+//! it otherwise; the same transform has to be applied to every clock
+//! which is going to be compared.
+
+use alloc::vec::Vec;
+use core::error;
+use core::fmt;
+
+use num_traits::{CheckedAdd, CheckedMul, Num};
+
+use VersionVec;
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Reasons a counter transform was refused
+pub enum TransformError<T> {
+    /// Counter has no representable image
+    Overflow(T),
+    /// Transform would reorder or zero out these two counters, the
+    /// second one being the larger
+    NotMonotonic(T, T)
+}
+
+impl<T: fmt::Debug> fmt::Display for TransformError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TransformError::Overflow(ref c) => write!(f, "counter {:?} overflows when transformed", c),
+            TransformError::NotMonotonic(ref a, ref b) => write!(f, "transform doesn't preserve order of counters {:?} and {:?}", a, b)
+        }
+    }
+}
+
+impl<T: fmt::Debug> error::Error for TransformError<T> {}
+
+impl<I, T> VersionVec<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    /// Adds `delta` to every non-zero counter
+    pub fn shift_counters(&mut self, delta: T) -> Result<(), TransformError<T>> where T: CheckedAdd {
+        self.remap_counters(|c| c.checked_add(&delta))
+    }
+
+    /// Multiplies every counter by `factor`, which must be positive
+    pub fn scale_counters(&mut self, factor: T) -> Result<(), TransformError<T>> where T: CheckedMul {
+        self.remap_counters(|c| c.checked_mul(&factor))
+    }
+
+    /// Replaces every non-zero counter `c` with `f(c)`, `None` meaning
+    /// it can't be represented
+    pub fn remap_counters<F>(&mut self, mut f: F) -> Result<(), TransformError<T>> where F: FnMut(T) -> Option<T> {
+        let mut mapped: Vec<(T, T)> = Vec::with_capacity(self.inner.len());
+        for &(_, counter) in &self.inner {
+            if counter != T::zero() {
+                mapped.push((counter, f(counter).ok_or(TransformError::Overflow(counter))?));
+            }
+        }
+
+        let mut sorted = mapped.clone();
+        sorted.sort();
+        sorted.dedup_by_key(|e| e.0);
+        let mut last = (T::zero(), T::zero());
+        for &entry in &sorted {
+            if entry.1 <= last.1 {
+                return Err(TransformError::NotMonotonic(last.0, entry.0))
+            }
+            last = entry;
+        }
+
+        let mut mapped = mapped.into_iter();
+        for entry in self.inner.iter_mut().filter(|e| e.1 != T::zero()) {
+            entry.1 = mapped.next().expect("mapped every non-zero counter").1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TransformError;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn transforms() {
+        let mut a = VersionVec::from_vec(vec![(1u32, 3u16), (2, 0), (3, 1)]);
+        let b = VersionVec::from_vec(vec![(1, 2), (3, 1)]);
+        assert_eq!(a.cmp(&b), Ordering::Greater);
+
+        a.shift_counters(1000).unwrap();
+        assert_eq!(a.as_ref(), [(1, 1003), (2, 0), (3, 1001)]);
+        a.scale_counters(10).unwrap();
+        assert_eq!(a.as_ref(), [(1, 10030), (2, 0), (3, 10010)]);
+        assert_eq!(a.scale_counters(10), Err(TransformError::Overflow(10030)));
+        assert_eq!(a.scale_counters(0), Err(TransformError::NotMonotonic(0, 10010)));
+        assert_eq!(a.remap_counters(|c| Some(c / 100)), Err(TransformError::NotMonotonic(10010, 10030)));
+        assert_eq!(a.as_ref(), [(1, 10030), (2, 0), (3, 10010)]);
+
+        let mut b = b;
+        b.remap_counters(|c| Some(c * 15 - 10)).unwrap();
+        assert_eq!(b.as_ref(), [(1, 20), (3, 5)]);
+    }
+}
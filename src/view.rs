@@ -0,0 +1,177 @@
+//! Read-only views answering queries without materializing a clock.
+
+use alloc::vec::Vec;
+use core::cmp;
+
+use num_traits::Num;
+
+use storage::cmp_sorted;
+use {Ordering, VersionVec};
+
+#[derive(Clone, Debug)]
+/// Least upper bound of several clocks, computed lazily per query
+pub struct MergedView<'a, I: 'a, T: 'a> {
+    clocks: Vec<&'a VersionVec<I, T>>
+}
+
+impl<'a, I, T> MergedView<'a, I, T> where I: Ord + Clone, T: Ord + Copy + Num {
+    pub fn new<C: IntoIterator<Item = &'a VersionVec<I, T>>>(clocks: C) -> MergedView<'a, I, T> {
+        MergedView { clocks: clocks.into_iter().collect() }
+    }
+
+    /// Adds one more clock to the view
+    pub fn push(&mut self, clock: &'a VersionVec<I, T>) {
+        self.clocks.push(clock);
+    }
+
+    /// Maximum counter of `id` across all clocks
+    pub fn get(&self, id: &I) -> Option<T> {
+        self.clocks.iter().filter_map(|clock| clock.get(id)).max()
+    }
+
+    /// True if the merged clock descends `other`
+    pub fn descends(&self, other: &VersionVec<I, T>) -> bool {
+        other.inner.iter().all(|e| e.1 <= self.get(&e.0).unwrap_or_else(T::zero))
+    }
+
+    /// Compares the merged clock with `other`, same as `VersionVec::cmp`
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &VersionVec<I, T>) -> Ordering {
+        cmp_sorted(self.iter(), other.inner.iter().cloned())
+    }
+
+    /// Entries of the merged clock in id order
+    pub fn iter(&self) -> Entries<'a, I, T> {
+        Entries { cursors: self.clocks.iter().map(|clock| &clock.inner[..]).collect() }
+    }
+
+    /// Materializes the merged clock
+    pub fn to_version_vec(&self) -> VersionVec<I, T> {
+        VersionVec { inner: self.iter().collect() }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Entries of a clock matching a predicate, see `VersionVec::filtered`.
+///
+/// Queries taking another clock apply the predicate to its entries as
+/// well, so e.g. only datacenter-local actors are compared.
+pub struct FilteredView<'a, I: 'a, T: 'a, P> {
+    clock: &'a VersionVec<I, T>,
+    pred: P
+}
+
+impl<'a, I, T, P> FilteredView<'a, I, T, P> where I: Ord + Clone, T: Ord + Copy + Num, P: Fn(&I, &T) -> bool {
+    /// Counter of `id` if its entry matches
+    pub fn get(&self, id: &I) -> Option<T> {
+        self.clock.get(id).filter(|counter| (self.pred)(id, counter))
+    }
+
+    /// True if matching entries of the clock descend matching entries
+    /// of `other`
+    pub fn descends(&self, other: &VersionVec<I, T>) -> bool {
+        other.inner.iter()
+            .filter(|e| (self.pred)(&e.0, &e.1))
+            .all(|e| e.1 <= self.get(&e.0).unwrap_or_else(T::zero))
+    }
+
+    /// Compares matching entries of both clocks, same as `VersionVec::cmp`
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &VersionVec<I, T>) -> Ordering {
+        cmp_sorted(self.iter(), other.inner.iter().filter(|e| (self.pred)(&e.0, &e.1)).cloned())
+    }
+
+    /// Matching entries in id order
+    pub fn iter(&self) -> impl Iterator<Item = (I, T)> + '_ {
+        self.clock.inner.iter().filter(move |e| (self.pred)(&e.0, &e.1)).cloned()
+    }
+
+    /// Materializes matching entries
+    pub fn to_version_vec(&self) -> VersionVec<I, T> {
+        VersionVec { inner: self.iter().collect() }
+    }
+}
+
+impl<I, T> VersionVec<I, T> where I: Ord + Clone, T: Ord + Copy + Num {
+    /// Returns a view of entries matching `pred`, without copying them
+    pub fn filtered<P: Fn(&I, &T) -> bool>(&self, pred: P) -> FilteredView<'_, I, T, P> {
+        FilteredView { clock: self, pred }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Iterator returned by `MergedView::iter`
+pub struct Entries<'a, I: 'a, T: 'a> {
+    cursors: Vec<&'a [(I, T)]>
+}
+
+impl<'a, I, T> Iterator for Entries<'a, I, T> where I: Ord + Clone, T: Ord + Copy {
+    type Item = (I, T);
+
+    fn next(&mut self) -> Option<(I, T)> {
+        let id = self.cursors.iter().filter_map(|c| c.first()).map(|e| &e.0).min()?.clone();
+
+        let mut counter = None;
+        for cursor in self.cursors.iter_mut() {
+            if let Some((head, rest)) = cursor.split_first() {
+                if head.0 == id {
+                    counter = cmp::max(counter, Some(head.1));
+                    *cursor = rest;
+                }
+            }
+        }
+        counter.map(|counter| (id, counter))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::{String, ToString};
+
+    use super::MergedView;
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn merged_view() {
+        let a: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 3), (2, 1)]);
+        let b = VersionVec::from_vec(vec![(2, 4), (5, 1)]);
+        let c = VersionVec::from_vec(vec![(1, 1)]);
+        let view = MergedView::new(vec![&a, &b, &c]);
+
+        assert_eq!(view.get(&1), Some(3));
+        assert_eq!(view.get(&2), Some(4));
+        assert_eq!(view.get(&3), None);
+        assert_eq!(view.to_version_vec().as_ref(), a.merged(&b).merged(&c).as_ref());
+
+        assert!(view.descends(&VersionVec::from_vec(vec![(1, 2), (2, 4)])));
+        assert!(!view.descends(&VersionVec::from_vec(vec![(3, 1)])));
+        assert_eq!(view.cmp(&a.merged(&b)), Ordering::Equal);
+        assert_eq!(view.cmp(&b), Ordering::Greater);
+        assert_eq!(view.cmp(&VersionVec::from_vec(vec![(1, 3), (2, 4), (5, 2)])), Ordering::Less);
+        assert_eq!(view.cmp(&VersionVec::from_vec(vec![(6, 1)])), Ordering::Concurrent);
+        assert_eq!(MergedView::new(None).cmp(&VersionVec::<u32, u32>::new()), Ordering::Equal);
+
+        let named: VersionVec<String, u32> = VersionVec::from_vec(vec![("a".to_string(), 2)]);
+        let other = VersionVec::from_vec(vec![("a".to_string(), 1), ("b".to_string(), 1)]);
+        let view = MergedView::new(vec![&named, &other]);
+        assert_eq!(view.get(&"a".to_string()), Some(2));
+        assert_eq!(view.to_version_vec().as_ref(), named.merged(&other).as_ref());
+    }
+
+    #[test]
+    fn filtered_view() {
+        // actors below 100 are local
+        let local = |id: &u32, _: &u32| *id < 100;
+        let a: VersionVec<u32, u32> = VersionVec::from_vec(vec![(1, 3), (2, 1), (101, 7)]);
+        let b = VersionVec::from_vec(vec![(1, 3), (2, 1), (102, 1)]);
+        assert_eq!(a.cmp(&b), Ordering::Concurrent);
+
+        let view = a.filtered(local);
+        assert_eq!(view.cmp(&b), Ordering::Equal);
+        assert!(view.descends(&b));
+        assert_eq!(view.get(&101), None);
+        assert_eq!(view.get(&1), Some(3));
+        assert_eq!(view.to_version_vec().as_ref(), [(1, 3), (2, 1)]);
+        assert_eq!(b.filtered(local).cmp(&VersionVec::from_vec(vec![(1, 3), (2, 2)])), Ordering::Less);
+    }
+}
@@ -0,0 +1,96 @@
+//! Duplicate detection with a bounded history of applied dots.
+//!
+//! `VersionVec::apply_dot` reports every dot at or below the counter of
+//! its actor as a duplicate. On lossy transports with heavy redelivery
+//! that hides a different problem: an actor which lost its state and
+//! restarted counting issues dots which look old too. `WindowedClock`
+//! remembers the last `K` applied counters per actor; a dot among them
+//! is a duplicate, an older one is reported as a possible reset.
+
+use alloc::collections::{BTreeMap, VecDeque};
+
+use num_traits::Num;
+
+use {ApplyOutcome, Dot, VersionVec};
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq)]
+/// Result of `WindowedClock::apply_dot`
+pub enum WindowOutcome<T> {
+    /// Dot was the next expected one and has been applied
+    Applied,
+    /// Dot is one of the recently applied ones
+    Duplicate,
+    /// Earlier dots of the actor are missing, holds the expected counter
+    Gap { expected: T },
+    /// Dot is covered, but older than the remembered history: either a
+    /// very late redelivery or the actor restarted its counter
+    PossibleReset
+}
+
+#[derive(Clone, Debug)]
+/// Version vector remembering the last `window` applied counters of
+/// every actor
+pub struct WindowedClock<I, T> {
+    clock: VersionVec<I, T>,
+    recent: BTreeMap<I, VecDeque<T>>,
+    window: usize
+}
+
+impl<I, T> WindowedClock<I, T> where I: Ord + Copy, T: Ord + Copy + Num {
+    pub fn new(window: usize) -> WindowedClock<I, T> {
+        WindowedClock { clock: VersionVec::new(), recent: BTreeMap::new(), window }
+    }
+
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    /// Applies `dot` if it is the next one of its actor, see
+    /// `WindowOutcome` for the rest
+    pub fn apply_dot(&mut self, dot: Dot<I, T>) -> WindowOutcome<T> {
+        match self.clock.apply_dot(dot) {
+            ApplyOutcome::Applied => {
+                if self.window > 0 {
+                    let recent = self.recent.entry(dot.actor).or_default();
+                    if recent.len() == self.window {
+                        recent.pop_front();
+                    }
+                    recent.push_back(dot.counter);
+                }
+                WindowOutcome::Applied
+            },
+            ApplyOutcome::Gap { expected } => WindowOutcome::Gap { expected },
+            ApplyOutcome::Duplicate => {
+                let recent = self.recent.get(&dot.actor).is_some_and(|r| r.contains(&dot.counter));
+                if recent { WindowOutcome::Duplicate } else { WindowOutcome::PossibleReset }
+            }
+        }
+    }
+
+    /// Merges `other` in. Dots it covers aren't remembered as applied.
+    pub fn merge(&mut self, other: &VersionVec<I, T>) {
+        self.clock.merge(other);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{WindowOutcome, WindowedClock};
+    use {Dot, VersionVec};
+
+    #[test]
+    fn duplicates_and_resets() {
+        let mut clock = WindowedClock::new(2);
+        for counter in 1..5u32 {
+            assert_eq!(clock.apply_dot(Dot::new(1u8, counter)), WindowOutcome::Applied);
+        }
+        assert_eq!(clock.apply_dot(Dot::new(1, 4)), WindowOutcome::Duplicate);
+        assert_eq!(clock.apply_dot(Dot::new(1, 3)), WindowOutcome::Duplicate);
+        assert_eq!(clock.apply_dot(Dot::new(1, 1)), WindowOutcome::PossibleReset);
+        assert_eq!(clock.apply_dot(Dot::new(1, 7)), WindowOutcome::Gap { expected: 5 });
+
+        clock.merge(&VersionVec::from_dot(2, 3));
+        assert_eq!(clock.apply_dot(Dot::new(2, 3)), WindowOutcome::PossibleReset);
+        assert_eq!(clock.clock().as_ref(), [(1, 4), (2, 3)]);
+    }
+}
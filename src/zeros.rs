@@ -0,0 +1,140 @@
+//! Choosing what zero entries mean for a clock type.
+//!
+//! `VersionVec` keeps zero entries it is given, while `cmp` treats them
+//! as absent, so two vectors can compare `Equal` and still differ in
+//! storage. `Normalized` fixes one behaviour in its type:
+//!
+//! * `StripZeros` - zero entries are dropped on construction and merge,
+//!   stored entries and equality match `cmp`
+//! * `KeepZeros` - zero entries are preserved, e.g. to remember which
+//!   actors are known, and equality is structural
+//!
+//! Encodings see just the stored entries, so the policy carries over to
+//! whatever is built on `as_ref`. With the `serde` and `borsh` features
+//! `Normalized` is encoded like `VersionVec` and normalized on decode,
+//! so a `StripZeros` clock never comes back with zeros. The canonical
+//! encoding drops zeros regardless.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use num_traits::Num;
+
+use {Ordering, VersionVec};
+
+/// Treatment of zero entries, see module docs
+pub trait ZeroPolicy {
+    /// Brings `clock` into the form required by the policy
+    fn normalize<I: Ord + Copy, T: Ord + Copy + Num>(clock: &mut VersionVec<I, T>);
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq, Default)]
+/// Zero entries are preserved
+pub struct KeepZeros;
+
+impl ZeroPolicy for KeepZeros {
+    fn normalize<I: Ord + Copy, T: Ord + Copy + Num>(_: &mut VersionVec<I, T>) {}
+}
+
+#[derive(Copy, Clone, Eq, Debug, PartialEq, Default)]
+/// Zero entries are always dropped
+pub struct StripZeros;
+
+impl ZeroPolicy for StripZeros {
+    fn normalize<I: Ord + Copy, T: Ord + Copy + Num>(clock: &mut VersionVec<I, T>) {
+        clock.strip_zeros();
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Version vector with zero entries treated according to `Z`
+pub struct Normalized<I, T, Z = StripZeros> {
+    clock: VersionVec<I, T>,
+    policy: PhantomData<Z>
+}
+
+impl<I, T, Z> Normalized<I, T, Z> where I: Ord + Copy, T: Ord + Copy + Num, Z: ZeroPolicy {
+    pub fn new() -> Normalized<I, T, Z> {
+        Normalized { clock: VersionVec::new(), policy: PhantomData }
+    }
+
+    /// Constructs from tuples (id, version), like `VersionVec::from_vec`
+    pub fn from_vec(v: Vec<(I, T)>) -> Normalized<I, T, Z> {
+        Normalized::from(VersionVec::from_vec(v))
+    }
+
+    pub fn clock(&self) -> &VersionVec<I, T> {
+        &self.clock
+    }
+
+    pub fn into_version_vec(self) -> VersionVec<I, T> {
+        self.clock
+    }
+
+    pub fn bump_for(&mut self, id: I) {
+        self.clock.bump_for(id);
+    }
+
+    /// Merges `other` in, normalizing the result
+    pub fn merge(&mut self, other: &VersionVec<I, T>) {
+        self.clock.merge(other);
+        Z::normalize(&mut self.clock);
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn cmp(&self, other: &Normalized<I, T, Z>) -> Ordering {
+        self.clock.cmp(&other.clock)
+    }
+}
+
+impl<I, T, Z> Default for Normalized<I, T, Z> where I: Ord + Copy, T: Ord + Copy + Num, Z: ZeroPolicy {
+    fn default() -> Normalized<I, T, Z> {
+        Normalized::new()
+    }
+}
+
+impl<I, T, Z> From<VersionVec<I, T>> for Normalized<I, T, Z> where I: Ord + Copy, T: Ord + Copy + Num, Z: ZeroPolicy {
+    fn from(mut clock: VersionVec<I, T>) -> Normalized<I, T, Z> {
+        Z::normalize(&mut clock);
+        Normalized { clock, policy: PhantomData }
+    }
+}
+
+impl<I, T, Z> AsRef<[(I, T)]> for Normalized<I, T, Z> {
+    fn as_ref(&self) -> &[(I, T)] {
+        &self.clock.inner
+    }
+}
+
+/// Compares stored entries. With `StripZeros` that's the same as `cmp`
+/// returning `Equal`, with `KeepZeros` zero entries have to match too.
+impl<I: PartialEq, T: PartialEq, Z> PartialEq for Normalized<I, T, Z> {
+    fn eq(&self, other: &Normalized<I, T, Z>) -> bool {
+        self.clock.inner == other.clock.inner
+    }
+}
+
+impl<I: Eq, T: Eq, Z> Eq for Normalized<I, T, Z> {}
+
+#[cfg(test)]
+mod test {
+    use super::{KeepZeros, Normalized};
+    use {Ordering, VersionVec};
+
+    #[test]
+    fn policies() {
+        let stripped: Normalized<u32, u32> = Normalized::from_vec(vec![(1, 2), (2, 0)]);
+        let mut other: Normalized<u32, u32> = Normalized::from_vec(vec![(1, 2)]);
+        assert_eq!(stripped.as_ref(), [(1, 2)]);
+        assert!(stripped == other);
+        other.merge(&VersionVec::from_vec(vec![(3, 0)]));
+        assert_eq!(other.as_ref(), [(1, 2)]);
+
+        let kept: Normalized<u32, u32, KeepZeros> = Normalized::from_vec(vec![(1, 2), (2, 0)]);
+        let mut other: Normalized<u32, u32, KeepZeros> = Normalized::from_vec(vec![(1, 2)]);
+        assert_eq!(kept.cmp(&other), Ordering::Equal);
+        assert!(kept != other);
+        other.merge(&VersionVec::from_vec(vec![(2, 0)]));
+        assert!(kept == other);
+    }
+}
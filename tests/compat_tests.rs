@@ -0,0 +1,308 @@
+//! Cross-implementation fixtures.
+//!
+//! Files in `tests/compat` hold clocks encoded the way other version
+//! vector implementations put them on the wire, together with expected
+//! comparison results. Each clock is decoded into a `VersionVec`,
+//! compared, and encoded back, which must reproduce the fixture bytes.
+//!
+//! Fixture format, one item per line:
+//!
+//! * `# ...` - comment
+//! * `clock <name> <hex bytes>`
+//! * `expect <name> <ordering symbol> <name>`
+//! * `device <syncthing device id> <hex short id>` (syncthing only)
+
+extern crate version_vec;
+
+use std::collections::BTreeMap;
+
+use version_vec::{Ordering, VersionVec};
+
+type Clock = VersionVec<u64, u64>;
+
+/// Decoding and encoding of a foreign format. `Meta` keeps whatever the
+/// format stores besides counters, so encoding can be exact.
+trait Format {
+    type Meta;
+
+    fn decode(bytes: &[u8]) -> (Clock, Self::Meta);
+    fn encode(clock: &Clock, meta: &Self::Meta) -> Vec<u8>;
+}
+
+struct Fixture {
+    clocks: BTreeMap<String, Vec<u8>>,
+    expects: Vec<(String, Ordering, String)>,
+    devices: Vec<(String, u64)>
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    assert!(s.len().is_multiple_of(2), "odd hex length: {}", s);
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+fn load(text: &str) -> Fixture {
+    let mut fixture = Fixture { clocks: BTreeMap::new(), expects: vec![], devices: vec![] };
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["clock", name, bytes] => {
+                fixture.clocks.insert(name.to_string(), hex(bytes));
+            },
+            ["expect", a, op, b] => {
+                let order = Ordering::from_symbol(op).unwrap_or_else(|| panic!("bad ordering {}", op));
+                fixture.expects.push((a.to_string(), order, b.to_string()));
+            },
+            ["device", id, short] => {
+                fixture.devices.push((id.to_string(), u64::from_str_radix(short, 16).unwrap()));
+            },
+            _ => panic!("bad fixture line: {}", line)
+        }
+    }
+    fixture
+}
+
+fn check<F: Format>(text: &str) {
+    let fixture = load(text);
+    let mut decoded = BTreeMap::new();
+    for (name, bytes) in &fixture.clocks {
+        let (clock, meta) = F::decode(bytes);
+        assert_eq!(&F::encode(&clock, &meta), bytes, "clock {} doesn't round-trip", name);
+        decoded.insert(name.clone(), clock);
+    }
+
+    assert!(!fixture.expects.is_empty());
+    for (a, order, b) in &fixture.expects {
+        assert_eq!(decoded[a].cmp(&decoded[b]), *order, "{} {} {}", a, order, b);
+    }
+
+    #[cfg(feature = "codecs")]
+    for (device, short) in &fixture.devices {
+        use version_vec::codecs::syncthing::{parse_device_id, short_id};
+        assert_eq!(short_id(&parse_device_id(device).unwrap()), *short, "{}", device);
+    }
+}
+
+fn read_varint(input: &mut &[u8]) -> u64 {
+    let mut result = 0;
+    let mut shift = 0;
+    loop {
+        let byte = input[0];
+        *input = &input[1..];
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return result
+        }
+        shift += 7;
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Syncthing BEP `Vector` protobuf message
+struct Syncthing;
+
+impl Format for Syncthing {
+    type Meta = ();
+
+    fn decode(mut bytes: &[u8]) -> (Clock, ()) {
+        let mut entries = vec![];
+        while !bytes.is_empty() {
+            assert_eq!(read_varint(&mut bytes), 0x0a, "expected counters field");
+            let len = read_varint(&mut bytes) as usize;
+            let (mut counter, rest) = bytes.split_at(len);
+            bytes = rest;
+
+            let (mut id, mut value) = (0, 0);
+            while !counter.is_empty() {
+                match read_varint(&mut counter) {
+                    0x08 => id = read_varint(&mut counter),
+                    0x10 => value = read_varint(&mut counter),
+                    tag => panic!("unexpected Counter field tag {}", tag)
+                }
+            }
+            entries.push((id, value));
+        }
+        (VersionVec::from_vec(entries), ())
+    }
+
+    fn encode(clock: &Clock, _: &()) -> Vec<u8> {
+        let mut out = vec![];
+        for &(id, value) in clock.as_ref() {
+            let mut counter = vec![0x08];
+            write_varint(&mut counter, id);
+            if value != 0 {
+                counter.push(0x10);
+                write_varint(&mut counter, value);
+            }
+            out.push(0x0a);
+            write_varint(&mut out, counter.len() as u64);
+            out.extend_from_slice(&counter);
+        }
+        out
+    }
+}
+
+/// Riak `vclock()` in Erlang external term format
+struct Riak;
+
+impl Riak {
+    fn actor_id(actor: &[u8]) -> u64 {
+        assert!(actor.len() <= 8, "actor too long for u64 id");
+        actor.iter().fold(0, |id, &b| id << 8 | b as u64)
+    }
+
+    fn read_u32(input: &mut &[u8]) -> u32 {
+        let value = u32::from_be_bytes([input[0], input[1], input[2], input[3]]);
+        *input = &input[4..];
+        value
+    }
+
+    fn read_tag(input: &mut &[u8], expected: u8) {
+        assert_eq!(input[0], expected, "unexpected term tag");
+        *input = &input[1..];
+    }
+
+    fn read_integer(input: &mut &[u8]) -> u64 {
+        let tag = input[0];
+        *input = &input[1..];
+        match tag {
+            97 => {
+                let value = input[0] as u64;
+                *input = &input[1..];
+                value
+            },
+            98 => Riak::read_u32(input) as u64,
+            110 => {
+                let (len, sign) = (input[0] as usize, input[1]);
+                assert_eq!(sign, 0, "negative integer");
+                let value = input[2..2 + len].iter().rev().fold(0, |v, &b| v << 8 | b as u64);
+                *input = &input[2 + len..];
+                value
+            },
+            _ => panic!("unexpected integer tag {}", tag)
+        }
+    }
+
+    fn write_integer(out: &mut Vec<u8>, value: u64) {
+        if value < 256 {
+            out.extend_from_slice(&[97, value as u8]);
+        } else if value < 1 << 31 {
+            out.push(98);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        } else {
+            let digits: Vec<u8> = value.to_le_bytes().iter().cloned()
+                .take((64 - value.leading_zeros() as usize).div_ceil(8)).collect();
+            out.extend_from_slice(&[110, digits.len() as u8, 0]);
+            out.extend_from_slice(&digits);
+        }
+    }
+}
+
+impl Format for Riak {
+    /// Actors with their timestamps, in list order
+    type Meta = Vec<(Vec<u8>, u64)>;
+
+    fn decode(mut bytes: &[u8]) -> (Clock, Self::Meta) {
+        Riak::read_tag(&mut bytes, 131);
+        Riak::read_tag(&mut bytes, 108);
+        let count = Riak::read_u32(&mut bytes);
+
+        let mut entries = vec![];
+        let mut meta = vec![];
+        for _ in 0..count {
+            Riak::read_tag(&mut bytes, 104);
+            Riak::read_tag(&mut bytes, 2);
+            Riak::read_tag(&mut bytes, 109);
+            let len = Riak::read_u32(&mut bytes) as usize;
+            let actor = bytes[..len].to_vec();
+            bytes = &bytes[len..];
+
+            Riak::read_tag(&mut bytes, 104);
+            Riak::read_tag(&mut bytes, 2);
+            let counter = Riak::read_integer(&mut bytes);
+            let timestamp = Riak::read_integer(&mut bytes);
+
+            entries.push((Riak::actor_id(&actor), counter));
+            meta.push((actor, timestamp));
+        }
+        assert_eq!(bytes, [106], "expected proper list tail");
+        (VersionVec::from_vec(entries), meta)
+    }
+
+    fn encode(clock: &Clock, meta: &Self::Meta) -> Vec<u8> {
+        let mut out = vec![131, 108];
+        out.extend_from_slice(&(meta.len() as u32).to_be_bytes());
+        for (actor, timestamp) in meta {
+            out.extend_from_slice(&[104, 2, 109]);
+            out.extend_from_slice(&(actor.len() as u32).to_be_bytes());
+            out.extend_from_slice(actor);
+            out.extend_from_slice(&[104, 2]);
+            Riak::write_integer(&mut out, clock.get(&Riak::actor_id(actor)).unwrap());
+            Riak::write_integer(&mut out, *timestamp);
+        }
+        out.push(106);
+        out
+    }
+}
+
+/// Voldemort `VectorClock` binary serialization
+struct Voldemort;
+
+impl Format for Voldemort {
+    /// Clock timestamp
+    type Meta = u64;
+
+    fn decode(bytes: &[u8]) -> (Clock, u64) {
+        let count = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        let width = bytes[2] as usize;
+        let mut rest = &bytes[3..];
+
+        let mut entries = vec![];
+        for _ in 0..count {
+            let node = u16::from_be_bytes([rest[0], rest[1]]) as u64;
+            let version = rest[2..2 + width].iter().fold(0, |v, &b| v << 8 | b as u64);
+            entries.push((node, version));
+            rest = &rest[2 + width..];
+        }
+
+        let mut timestamp = [0; 8];
+        timestamp.copy_from_slice(rest);
+        (VersionVec::from_vec(entries), u64::from_be_bytes(timestamp))
+    }
+
+    fn encode(clock: &Clock, timestamp: &u64) -> Vec<u8> {
+        let max = clock.as_ref().iter().map(|e| e.1).max().unwrap_or(0);
+        let width = std::cmp::max((64 - max.leading_zeros() as usize).div_ceil(8), 1);
+
+        let mut out = vec![];
+        out.extend_from_slice(&(clock.as_ref().len() as u16).to_be_bytes());
+        out.push(width as u8);
+        for &(node, version) in clock.as_ref() {
+            out.extend_from_slice(&(node as u16).to_be_bytes());
+            out.extend_from_slice(&version.to_be_bytes()[8 - width..]);
+        }
+        out.extend_from_slice(&timestamp.to_be_bytes());
+        out
+    }
+}
+
+#[test]
+fn syncthing() {
+    check::<Syncthing>(include_str!("compat/syncthing.txt"));
+}
+
+#[test]
+fn riak() {
+    check::<Riak>(include_str!("compat/riak.txt"));
+}
+
+#[test]
+fn voldemort() {
+    check::<Voldemort>(include_str!("compat/voldemort.txt"));
+}
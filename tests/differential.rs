@@ -0,0 +1,134 @@
+//! Differential tests: random operation sequences are applied both to
+//! clocks and to a trivially correct `BTreeMap` model, and every
+//! observable result is compared.
+
+extern crate version_vec;
+
+use std::cmp;
+use std::collections::BTreeMap;
+
+use version_vec::storage::{AdaptiveClock, Storage};
+use version_vec::{CapPolicy, Ordering, VersionVec};
+
+type Model = BTreeMap<u8, u32>;
+
+/// splitmix64, good enough to drive test cases
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+fn random_entries(rng: &mut Rng) -> Vec<(u8, u32)> {
+    let mut model = Model::new();
+    for _ in 0..rng.below(6) {
+        model.insert(rng.below(8) as u8, rng.below(4) as u32);
+    }
+    model.into_iter().collect()
+}
+
+fn model_cmp(a: &Model, b: &Model) -> Ordering {
+    let (mut less, mut greater) = (false, false);
+    for id in a.keys().chain(b.keys()) {
+        let l = a.get(id).cloned().unwrap_or(0);
+        let r = b.get(id).cloned().unwrap_or(0);
+        less |= l < r;
+        greater |= l > r;
+    }
+    match (less, greater) {
+        (false, false) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (true, true) => Ordering::Concurrent
+    }
+}
+
+fn model_merge(a: &mut Model, b: &Model) {
+    for (id, counter) in b {
+        let entry = a.entry(*id).or_insert(0);
+        *entry = cmp::max(*entry, *counter);
+    }
+}
+
+fn check(clock: &VersionVec<u8, u32>, model: &Model) {
+    let entries: Vec<_> = model.iter().map(|(id, c)| (*id, *c)).collect();
+    assert_eq!(clock.as_ref(), &entries[..]);
+    for id in 0..10 {
+        assert_eq!(clock.get(&id), model.get(&id).cloned());
+    }
+}
+
+#[test]
+fn operations_match_model() {
+    let mut rng = Rng(0x5eed);
+
+    for _ in 0..500 {
+        let mut clock = VersionVec::new();
+        let mut adaptive = AdaptiveClock::new();
+        let mut model = Model::new();
+
+        for _ in 0..30 {
+            let other_entries = random_entries(&mut rng);
+            let other = VersionVec::from_vec(other_entries.clone());
+            let other_model: Model = other_entries.into_iter().collect();
+
+            assert_eq!(clock.cmp(&other), model_cmp(&model, &other_model));
+            assert_eq!(other.cmp(&clock), model_cmp(&other_model, &model));
+            assert_eq!(adaptive.cmp_with(&other), model_cmp(&model, &other_model));
+            let descends = model_cmp(&model, &other_model);
+            assert_eq!(clock.descends(&other), descends == Ordering::Equal || descends == Ordering::Greater);
+            if descends == Ordering::Equal {
+                assert_eq!(clock.digest(), other.digest());
+            }
+
+            match rng.below(5) {
+                0 | 1 => {
+                    let id = rng.below(10) as u8;
+                    clock.bump_for(id);
+                    adaptive.bump_for(id);
+                    *model.entry(id).or_insert(0) += 1;
+                },
+                2 => {
+                    let id = rng.below(10) as u8;
+                    let dot = clock.advance_from_context(&other, id);
+                    adaptive.merge_from(&other);
+                    adaptive.bump_for(id);
+                    model_merge(&mut model, &other_model);
+                    *model.entry(id).or_insert(0) += 1;
+                    assert_eq!((dot.actor, dot.counter), (id, model[&id]));
+                },
+                3 => {
+                    let n = rng.below(8) as usize;
+                    let mut capped = clock.clone();
+                    let dropped = capped.merge_max_n(&other, n, CapPolicy::DropNew);
+                    assert!(capped.as_ref().len() <= cmp::max(n, clock.as_ref().len()));
+                    let mut merged = capped.clone();
+                    merged.merge(&VersionVec::from_vec(dropped));
+                    assert_eq!(merged.cmp(&clock.merged(&other)), Ordering::Equal);
+
+                    clock.merge(&other);
+                    adaptive.merge_from(&other);
+                    model_merge(&mut model, &other_model);
+                },
+                _ => {
+                    clock = vec![clock, other.clone()].into_iter().sum();
+                    adaptive.merge_from(&other);
+                    model_merge(&mut model, &other_model);
+                }
+            }
+
+            check(&clock, &model);
+            assert_eq!(adaptive.to_version_vec().as_ref(), clock.as_ref());
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Exhaustive checks over small domains.
+//!
+//! Every clock over up to 3 actors with counters up to 3 (distinguishing
+//! missing entries from zero ones) is cross-checked pairwise against
+//! definitional semantics: a clock is the set of dots it has seen,
+//! `{(actor, n) | 1 <= n <= counter}`.
+
+extern crate version_vec;
+
+use std::collections::BTreeSet;
+
+use version_vec::{Ordering, VersionVec};
+
+const ACTORS: u8 = 3;
+const MAX_COUNTER: u8 = 3;
+
+type Dots = BTreeSet<(u8, u8)>;
+
+/// Generates all clocks over `actors` actors with counters up to `max`.
+/// Each actor is either missing or has a counter in `0..=max`.
+fn all_clocks(actors: u8, max: u8) -> Vec<VersionVec<u8, u8>> {
+    let mut result = vec![vec![]];
+    for actor in 0..actors {
+        let mut next = vec![];
+        for entries in &result {
+            next.push(entries.clone());
+            for counter in 0..=max {
+                let mut entries = entries.clone();
+                entries.push((actor, counter));
+                next.push(entries);
+            }
+        }
+        result = next;
+    }
+    result.into_iter().map(VersionVec::from_vec).collect()
+}
+
+fn dots(clock: &VersionVec<u8, u8>) -> Dots {
+    clock.as_ref().iter().flat_map(|&(actor, counter)| (1..=counter).map(move |n| (actor, n))).collect()
+}
+
+fn expected_ordering(a: &Dots, b: &Dots) -> Ordering {
+    match (a.is_subset(b), b.is_subset(a)) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => Ordering::Concurrent
+    }
+}
+
+#[test]
+fn generator_covers_domain() {
+    let clocks = all_clocks(ACTORS, MAX_COUNTER);
+    assert_eq!(clocks.len(), (MAX_COUNTER as usize + 2).pow(ACTORS as u32));
+}
+
+#[test]
+fn truth_table() {
+    let clocks = all_clocks(ACTORS, MAX_COUNTER);
+
+    for a in &clocks {
+        let a_dots = dots(a);
+        for b in &clocks {
+            let b_dots = dots(b);
+            let ordering = expected_ordering(&a_dots, &b_dots);
+
+            assert_eq!(a.cmp(b), ordering, "cmp {:?} {:?}", a, b);
+            assert_eq!(a.descends(b), b_dots.is_subset(&a_dots), "descends {:?} {:?}", a, b);
+            if ordering == Ordering::Equal {
+                assert_eq!(a.digest(), b.digest(), "digest {:?} {:?}", a, b);
+            }
+
+            let merged = a.merged(b);
+            assert_eq!(dots(&merged), a_dots.union(&b_dots).cloned().collect::<Dots>(), "merge {:?} {:?}", a, b);
+
+            let glb = a.glb(b);
+            assert_eq!(dots(&glb), a_dots.intersection(&b_dots).cloned().collect::<Dots>(), "glb {:?} {:?}", a, b);
+
+            // lattice laws
+            assert!(merged.descends(a) && merged.descends(b));
+            assert!(a.descends(&glb) && b.descends(&glb));
+            assert_eq!(merged.cmp(&b.merged(a)), Ordering::Equal);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Smoke tests of every cargo feature's public API.
+//!
+//! Each test is compiled only with its feature, so running the suite
+//! under each feature set of the CI matrix checks that optional
+//! subsystems still build and work through the public paths downstream
+//! crates use. `ci/features.sh` runs it with no features, each feature
+//! alone, all of them and every combination of `serde`, `borsh`, `uuid`
+//! and `arrow`; other feature pairs are only built, not tested.
+
+extern crate version_vec;
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+#[cfg(feature = "borsh")]
+extern crate borsh;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+#[cfg(feature = "quickcheck")]
+extern crate quickcheck;
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde_test;
+#[cfg(feature = "uuid")]
+extern crate uuid;
+
+use version_vec::{Dot, Ordering, VersionVec};
+
+type Clock = VersionVec<u32, u64>;
+
+fn sample() -> Clock {
+    VersionVec::from_vec(vec![(1, 3), (4, 1)])
+}
+
+#[test]
+fn core() {
+    let mut a = sample();
+    let b = a.clone();
+    a.bump_for(2);
+    assert_eq!(a.cmp(&b), Ordering::Greater);
+    assert_eq!(a.apply_dot(Dot::new(2, 2)), version_vec::ApplyOutcome::Applied);
+    assert_eq!(b.merged(&a).cmp(&a), Ordering::Equal);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn std() {
+    use version_vec::time::SystemTimeSource;
+    use version_vec::TimeSource;
+
+    let error: Box<dyn std::error::Error> = Box::new(version_vec::Conflict(Ordering::Less));
+    assert!(!error.to_string().is_empty());
+    assert!(SystemTimeSource.now() > 0);
+}
+
+#[cfg(feature = "codecs")]
+#[test]
+fn codecs() {
+    use version_vec::codecs::{canonical, token};
+
+    let clock = sample();
+    let decoded: Clock = canonical::decode(&clock.canonical_bytes()).unwrap();
+    assert_eq!(decoded.as_ref(), clock.as_ref());
+    let decoded: Clock = token::decode(&token::encode(&clock)).unwrap();
+    assert_eq!(decoded.as_ref(), clock.as_ref());
+}
+
+#[cfg(feature = "protocols")]
+#[test]
+fn protocols() {
+    use version_vec::protocols::SyncSession;
+
+    let mut a = SyncSession::new(sample());
+    let mut b = SyncSession::new(VersionVec::from_dot(2, 5));
+    let mut message = Some(a.start());
+    let sides = [&mut b, &mut a];
+    let mut turn = 0;
+    while let Some(clock) = message {
+        message = sides[turn].receive(&clock);
+        turn = 1 - turn;
+    }
+    assert_eq!(a.clock().as_ref(), [(1, 3), (2, 5), (4, 1)]);
+    assert_eq!(a.clock().as_ref(), b.clock().as_ref());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn rayon() {
+    use version_vec::filter::par_retain_not_dominated_by;
+
+    let mut clocks = vec![sample(), VersionVec::from_dot(1, 1), VersionVec::from_dot(9, 1)];
+    par_retain_not_dominated_by(&mut clocks, &sample());
+    assert_eq!(clocks.len(), 1);
+    assert_eq!(clocks[0].as_ref(), [(9, 1)]);
+}
+
+#[cfg(feature = "flatbuffers")]
+#[test]
+fn flatbuffers() {
+    use version_vec::codecs::flatbuf::{encode, ClockRef};
+
+    let bytes = encode(&sample());
+    let clock = ClockRef::from_bytes(&bytes).unwrap();
+    assert_eq!(clock.get(1), Some(3));
+    assert_eq!(clock.cmp(&sample()), Ordering::Equal);
+}
+
+#[cfg(feature = "borsh")]
+#[test]
+fn borsh() {
+    let bytes = borsh::to_vec(&sample()).unwrap();
+    let decoded: Clock = borsh::from_slice(&bytes).unwrap();
+    assert_eq!(decoded.as_ref(), sample().as_ref());
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn arbitrary() {
+    let bytes = [7u8; 64];
+    let clock: Clock = arbitrary::Unstructured::new(&bytes).arbitrary().unwrap();
+    assert!(clock.as_ref().windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[cfg(feature = "im")]
+#[test]
+fn im() {
+    use version_vec::storage::{PersistentClock, Storage};
+
+    let mut clock = PersistentClock::from(sample());
+    let snapshot = clock.snapshot();
+    clock.bump_for(1);
+    assert_eq!((clock.get(&1), snapshot.get(&1)), (Some(4), Some(3)));
+    assert_eq!(clock.cmp_with(&snapshot), Ordering::Greater);
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn arrow() {
+    use version_vec::codecs::arrow::{from_arrow, to_arrow};
+
+    let clocks = vec![sample(), VersionVec::new()];
+    let decoded: Vec<Clock> = from_arrow(&to_arrow(&clocks)).unwrap();
+    assert_eq!(decoded.len(), 2);
+    assert_eq!(decoded[0].as_ref(), sample().as_ref());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    use serde_test::{assert_ser_tokens, Configure, Token};
+
+    assert_ser_tokens(&Dot::new(1u32, 2u64).readable(), &[
+        Token::Struct { name: "Dot", len: 2 },
+        Token::Str("actor"), Token::U32(1),
+        Token::Str("counter"), Token::U64(2),
+        Token::StructEnd,
+    ]);
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uuid() {
+    use uuid::Uuid;
+    use version_vec::codecs::uuid::{decode, encode};
+
+    let clock = VersionVec::from_dot(Uuid::from_u128(5), 2u64);
+    let decoded: VersionVec<Uuid, u64> = decode(&encode(&clock)).unwrap();
+    assert_eq!(decoded.as_ref(), clock.as_ref());
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn proptest() {
+    use proptest::strategy::{Strategy, ValueTree};
+    use proptest::test_runner::TestRunner;
+    use version_vec::generate::ClockStrategy;
+
+    let mut runner = TestRunner::deterministic();
+    let clock: Clock = ClockStrategy::new().max_actors(2).proptest().new_tree(&mut runner).unwrap().current();
+    assert!(clock.as_ref().len() <= 2);
+}
+
+#[cfg(feature = "quickcheck")]
+#[test]
+fn quickcheck() {
+    use version_vec::generate::ClockStrategy;
+
+    let clock: Clock = ClockStrategy::new().max_actors(2).quickcheck(&mut quickcheck::Gen::new(8));
+    assert!(clock.as_ref().len() <= 2);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn rand() {
+    use rand::distributions::Distribution;
+    use rand::rngs::mock::StepRng;
+    use version_vec::generate::ClockStrategy;
+
+    let clock: Clock = ClockStrategy::new().max_actors(2).sample(&mut StepRng::new(1, 7));
+    assert!(clock.as_ref().len() <= 2);
+}